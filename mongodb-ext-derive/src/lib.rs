@@ -3,7 +3,7 @@
 //! Since recent changes, this crate has an unfortunate name.
 //! "derive" is not quite correct, because this crate's purpose is to provide macros, not **derive** macros explicitly.
 //!
-//! This crate currently provides one macro: [`case!`].
+//! This crate currently provides four macros: [`case!`], [`serde_case!`], [`paste_snake_case!`] and [`macro@merge_derives`].
 
 extern crate convert_case;
 extern crate proc_macro;
@@ -16,13 +16,15 @@ use {
     crate::{
         convert_case::{Case, Casing},
         proc_macro::TokenStream,
-        proc_macro2::Span,
+        proc_macro2::{Delimiter, Group, Ident, Span, TokenStream as TokenStream2, TokenTree},
         quote::ToTokens,
         syn::{
+            ext::IdentExt,
             parse::{Error as SynError, Parse, ParseStream, Result as SynResult},
+            punctuated::Punctuated,
             spanned::Spanned,
-            token::FatArrow,
-            LitStr, Path,
+            token::{Comma, FatArrow},
+            DeriveInput, LitStr, Path,
         },
     },
     std::convert::From,
@@ -142,3 +144,261 @@ pub fn case(input: TokenStream) -> TokenStream {
         .to_token_stream()
         .into()
 }
+
+struct SerdeCaseInput(LitStr);
+impl Parse for SerdeCaseInput {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        // parse field name, raw identifiers lose their `r#` just like serde does it
+        let field: Ident = input.call(Ident::parse_any)?;
+        let field_string: String = field.unraw().to_string();
+
+        // parse `=>`
+        let _: FatArrow = input.parse::<FatArrow>()?;
+
+        // parse rule, given as in `#[serde(rename_all = "...")]`
+        let rule: LitStr = input.parse::<LitStr>()?;
+        match apply_serde_rule(&field_string, &rule.value()) {
+            Some(renamed) => Ok(Self(LitStr::new(&renamed, field.span()))),
+            None => Err(SynError::new(
+                rule.span(),
+                "Unknown rule, expected one of \"lowercase\", \"UPPERCASE\", \"PascalCase\", \"camelCase\", \"snake_case\", \"SCREAMING_SNAKE_CASE\", \"kebab-case\", \"SCREAMING-KEBAB-CASE\"",
+            )),
+        }
+    }
+}
+
+/// Renames a field the same way `#[serde(rename_all = "...")]` does it.
+///
+/// Returns [`None`] if `rule` is not one of serde's rules.
+fn apply_serde_rule(field: &str, rule: &str) -> Option<String> {
+    let renamed = match rule {
+        "lowercase" | "snake_case" => field.to_owned(),
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => field.to_ascii_uppercase(),
+        "PascalCase" => {
+            // the character following an underscore is uppercased, all others are kept as they are
+            let mut pascal = String::with_capacity(field.len());
+            let mut capitalize = true;
+            for c in field.chars() {
+                if c == '_' {
+                    capitalize = true;
+                } else if capitalize {
+                    pascal.push(c.to_ascii_uppercase());
+                    capitalize = false;
+                } else {
+                    pascal.push(c);
+                }
+            }
+            pascal
+        }
+        "camelCase" => {
+            let pascal = apply_serde_rule(field, "PascalCase")?;
+            let mut chars = pascal.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                None => pascal,
+            }
+        }
+        "kebab-case" => field.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => field.to_ascii_uppercase().replace('_', "-"),
+        _ => return None,
+    };
+    Some(renamed)
+}
+
+/// Converts a field name the same way serde's `rename_all` attribute does it.
+///
+/// The general accepted format is: `serde_case!(field_name => "camelCase")`
+///
+/// Hereby
+/// - `field_name` is the identifier of a struct field, raw identifiers like `r#type` are accepted.
+/// - `=>` is just a fat arrow that separates the two parameters.
+/// - `"camelCase"` is any rule accepted by `#[serde(rename_all = "...")]`.
+///
+/// This macro always expands to a [`&str`] literal ([`LitStr`](struct@syn::LitStr)).
+///
+/// # Differences to [`case!`]
+///
+/// [`case!`] uses [`convert_case`], which splits words differently than serde does.
+/// Thus keys built by [`case!`] do not always match the keys serde serializes a field as:
+///
+/// ```rust
+/// use mongodb_ext_derive::{case, serde_case};
+///
+/// // acronyms are kept by serde
+/// assert_eq!(case!(user_URL => Camel), "userUrl");
+/// assert_eq!(serde_case!(user_URL => "camelCase"), "userURL");
+///
+/// // letters following a digit do not start a new word in serde
+/// assert_eq!(case!(x1y => Camel), "x1Y");
+/// assert_eq!(serde_case!(x1y => "camelCase"), "x1y");
+/// ```
+///
+/// # Examples
+///
+/// ```rust
+/// use mongodb_ext_derive::serde_case;
+///
+/// assert_eq!(serde_case!(user_id => "camelCase"), "userId");
+/// assert_eq!(serde_case!(area51_code => "camelCase"), "area51Code");
+/// assert_eq!(serde_case!(r#type => "camelCase"), "type");
+/// assert_eq!(serde_case!(user_id => "PascalCase"), "UserId");
+/// assert_eq!(serde_case!(user_id => "SCREAMING-KEBAB-CASE"), "USER-ID");
+/// ```
+#[proc_macro]
+pub fn serde_case(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as SerdeCaseInput)
+        .0
+        .to_token_stream()
+        .into()
+}
+
+/// Converts an identifier to `snake_case`.
+///
+/// Acronyms are kept together (`HTTPCache` => `http_cache`) and digits stick to the preceding word (`Collection1` => `collection1`).
+fn to_snake_case(ident: &str) -> String {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut snake = String::with_capacity(ident.len() + 4);
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                let previous = chars[i - 1];
+                let next_is_lowercase = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+                // start of a new word after a lowercase word or a number,
+                // or last letter of an acronym that is followed by a new word
+                if previous.is_lowercase()
+                    || previous.is_ascii_digit()
+                    || (previous.is_uppercase() && next_is_lowercase)
+                {
+                    snake.push('_');
+                }
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(*c);
+        }
+    }
+    snake
+}
+
+/// Recursively replaces all `ident:snake` segments inside `[<` / `>]` groups.
+fn replace_snake_segments(input: TokenStream2, in_paste_group: bool) -> TokenStream2 {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut output: Vec<TokenTree> = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        match (&tokens[i], tokens.get(i + 1), tokens.get(i + 2)) {
+            (
+                TokenTree::Ident(ident),
+                Some(TokenTree::Punct(colon)),
+                Some(TokenTree::Ident(modifier)),
+            ) if in_paste_group && colon.as_char() == ':' && modifier == "snake" => {
+                output.push(TokenTree::Ident(Ident::new(
+                    &to_snake_case(&ident.to_string()),
+                    ident.span(),
+                )));
+                i += 3;
+            }
+            (TokenTree::Group(group), _, _) => {
+                let is_paste_group = group.delimiter() == Delimiter::Bracket
+                    && matches!(group.stream().into_iter().next(), Some(TokenTree::Punct(p)) if p.as_char() == '<');
+                let mut new_group = Group::new(
+                    group.delimiter(),
+                    replace_snake_segments(group.stream(), is_paste_group),
+                );
+                new_group.set_span(group.span());
+                output.push(TokenTree::Group(new_group));
+                i += 1;
+            }
+            (token, _, _) => {
+                output.push(token.clone());
+                i += 1;
+            }
+        }
+    }
+    output.into_iter().collect()
+}
+
+/// Preprocessor for [`paste!`](https://docs.rs/paste) invocations.
+///
+/// `paste`'s `:snake` modifier inserts an underscore before every uppercase letter, eg. `HTTPCache` becomes `h_t_t_p_cache`.
+/// This macro replaces every `ident:snake` segment inside `[<` / `>]` with the identifier in `snake_case` beforehand, keeping acronyms together.
+/// Digits stay attached to the preceding word, just like `paste` does it.
+///
+/// All other tokens are passed through unchanged, thus the input is usually a complete `paste!` invocation.
+///
+/// # Examples
+///
+/// ```rust
+/// use mongodb_ext_derive::paste_snake_case;
+///
+/// macro_rules! get_name {
+///     ($name:ident) => {
+///         // `stringify!` is used to show the tokens `paste!` would receive
+///         paste_snake_case! {
+///             std::stringify!([<$name:snake _coll>]).replace(' ', "")
+///         }
+///     };
+/// }
+///
+/// assert_eq!(get_name!(HTTPCache), "[<http_cache_coll>]");
+/// assert_eq!(get_name!(UserID), "[<user_id_coll>]");
+/// assert_eq!(get_name!(Collection1), "[<collection1_coll>]");
+/// assert_eq!(get_name!(My2ndCollection), "[<my2nd_collection_coll>]");
+/// assert_eq!(get_name!(myCollection), "[<my_collection_coll>]");
+/// ```
+#[proc_macro]
+pub fn paste_snake_case(input: TokenStream) -> TokenStream {
+    replace_snake_segments(input.into(), false).into()
+}
+
+/// Adds the given derives to a struct, unless it derives them already.
+///
+/// Derives are compared by the last segment of their path, thus `std::fmt::Debug` and `Debug` are the same derive.
+/// The missing derives are added as another `#[derive(...)]` attribute behind the existing attributes.
+///
+/// # Examples
+///
+/// ```rust
+/// use mongodb_ext_derive::merge_derives;
+///
+/// #[merge_derives(Debug, Clone)]
+/// #[derive(std::fmt::Debug, PartialEq)]
+/// struct Point {
+///     x: i32,
+/// }
+///
+/// let point = Point { x: 1 };
+/// assert_eq!(point.clone(), point);
+/// assert_eq!(format!("{:?}", point), "Point { x: 1 }");
+/// ```
+#[proc_macro_attribute]
+pub fn merge_derives(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let derives = parse_macro_input!(attr with Punctuated::<Path, Comma>::parse_terminated);
+    let mut input = parse_macro_input!(item as DeriveInput);
+
+    let mut present: Vec<Ident> = Vec::new();
+    for attribute in input.attrs.iter().filter(|a| a.path.is_ident("derive")) {
+        match attribute.parse_args_with(Punctuated::<Path, Comma>::parse_terminated) {
+            Ok(paths) => present.extend(
+                paths
+                    .into_iter()
+                    .filter_map(|path| path.segments.last().map(|segment| segment.ident.clone())),
+            ),
+            Err(e) => return e.to_compile_error().into(),
+        }
+    }
+
+    let missing: Vec<Path> = derives
+        .into_iter()
+        .filter(|derive| {
+            !derive
+                .segments
+                .last()
+                .is_some_and(|segment| present.contains(&segment.ident))
+        })
+        .collect();
+    if !missing.is_empty() {
+        input.attrs.push(parse_quote!(#[derive(#(#missing),*)]));
+    }
+    input.into_token_stream().into()
+}
@@ -0,0 +1,67 @@
+//! This module contains the serde helpers used by fields declared with `#[as_i64]`.
+//!
+//! Use them directly with `#[serde(with = "mongodb_ext::as_i64")]` on structs not declared with [`mongo_db!`](crate::mongo_db).
+
+use crate::{
+    mongodb::bson::{Bson, DateTime, Deserializer as BsonDeserializer},
+    serde::{
+        de::{Deserialize, Deserializer, Error as DeError},
+        ser::Serializer,
+    },
+};
+
+/// Types that can be serialized as integers by [`serialize`] / [`deserialize`].
+pub trait AsI64: Sized {
+    /// Serializes `self` as 64-bit integer.
+    fn serialize_i64<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+    /// Deserializes `Self` from an integer.
+    fn deserialize_i64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+impl AsI64 for DateTime {
+    /// Serializes `self` as milliseconds since the Unix epoch.
+    fn serialize_i64<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.timestamp_millis())
+    }
+
+    /// Deserializes `Self` from milliseconds since the Unix epoch or a BSON date.
+    fn deserialize_i64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Bson::deserialize(deserializer)? {
+            Bson::Int64(millis) => Ok(DateTime::from_millis(millis)),
+            Bson::Int32(millis) => Ok(DateTime::from_millis(i64::from(millis))),
+            Bson::DateTime(date) => Ok(date),
+            other => Err(D::Error::custom(format!(
+                "expected milliseconds since the Unix epoch or a date, found {}",
+                other
+            ))),
+        }
+    }
+}
+
+impl<T: AsI64> AsI64 for Option<T> {
+    fn serialize_i64<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Some(value) => value.serialize_i64(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    fn deserialize_i64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Bson::deserialize(deserializer)? {
+            Bson::Null => Ok(None),
+            other => T::deserialize_i64(BsonDeserializer::new(other))
+                .map(Some)
+                .map_err(D::Error::custom),
+        }
+    }
+}
+
+/// Serializes `value` as 64-bit integer, eg. a [`DateTime`] as milliseconds since the Unix epoch.
+pub fn serialize<T: AsI64, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    value.serialize_i64(serializer)
+}
+
+/// Deserializes a value serialized by [`serialize`], accepts BSON dates too.
+pub fn deserialize<'de, T: AsI64, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    T::deserialize_i64(deserializer)
+}
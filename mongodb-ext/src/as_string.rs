@@ -0,0 +1,89 @@
+//! This module contains the serde helpers used by fields declared with `#[as_string]`.
+//!
+//! Use them directly with `#[serde(with = "mongodb_ext::as_string")]` on structs not declared with [`mongo_db!`](crate::mongo_db).
+
+use crate::{
+    mongodb::bson::{Bson, Deserializer as BsonDeserializer},
+    serde::{
+        de::{Deserialize, Deserializer, Error as DeError},
+        ser::Serializer,
+    },
+};
+
+use std::{fmt::Display, str::FromStr};
+
+/// Types that can be serialized as strings by [`serialize`] / [`deserialize`].
+pub trait AsString: Sized {
+    /// Serializes `self` as string.
+    fn serialize_string<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+    /// Deserializes `Self` from a string or a number.
+    fn deserialize_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+/// Parses `bson` as `T`, accepting strings and numbers.
+fn parse_bson<T, E>(bson: Bson) -> Result<T, E>
+where
+    T: FromStr,
+    T::Err: Display,
+    E: DeError,
+{
+    let string = match bson {
+        Bson::String(string) => string,
+        Bson::Int32(number) => number.to_string(),
+        Bson::Int64(number) => number.to_string(),
+        Bson::Double(number) => number.to_string(),
+        other => {
+            return Err(E::custom(format!(
+                "expected a string or a number, found {}",
+                other
+            )))
+        }
+    };
+    string.parse().map_err(E::custom)
+}
+
+macro_rules! impl_as_string {
+    ($($ty:ty),+) => {
+        $(
+            impl AsString for $ty {
+                fn serialize_string<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+
+                fn deserialize_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    parse_bson(Bson::deserialize(deserializer)?)
+                }
+            }
+        )+
+    };
+}
+
+impl_as_string!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+impl<T: AsString> AsString for Option<T> {
+    fn serialize_string<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Some(value) => value.serialize_string(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    fn deserialize_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Bson::deserialize(deserializer)? {
+            Bson::Null => Ok(None),
+            other => T::deserialize_string(BsonDeserializer::new(other))
+                .map(Some)
+                .map_err(D::Error::custom),
+        }
+    }
+}
+
+/// Serializes `value` as string, eg. to keep the precision of `u64` / `u128` or to match other clients.
+pub fn serialize<T: AsString, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    value.serialize_string(serializer)
+}
+
+/// Deserializes a value serialized by [`serialize`], accepts numbers too.
+pub fn deserialize<'de, T: AsString, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    T::deserialize_string(deserializer)
+}
@@ -0,0 +1,85 @@
+//! This module contains the serde helpers used by fields declared with `#[binary]`.
+//!
+//! Use them directly with `#[serde(with = "mongodb_ext::binary")]` on structs not declared with [`mongo_db!`](crate::mongo_db).
+//!
+//! Fields that need a specific [`BinarySubtype`], eg. [`BinarySubtype::Uuid`], can use [`Binary`] as field type instead.
+
+pub use crate::mongodb::bson::{spec::BinarySubtype, Binary};
+
+use crate::{
+    mongodb::bson::{Bson, Deserializer as BsonDeserializer},
+    serde::{
+        de::{Deserialize, Deserializer, Error as DeError},
+        ser::Serializer,
+    },
+};
+
+/// Types that can be serialized as BSON binary by [`serialize`] / [`deserialize`].
+pub trait BinaryBytes: Sized {
+    /// Serializes `self` as bytes, which is a BSON binary of subtype [`BinarySubtype::Generic`].
+    fn serialize_binary<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+    /// Deserializes `Self` from a BSON binary or an array of numbers.
+    fn deserialize_binary<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+/// Converts `bson` into bytes, accepting binaries of any subtype and arrays of numbers.
+fn bytes_from_bson<E: DeError>(bson: Bson) -> Result<Vec<u8>, E> {
+    match bson {
+        Bson::Binary(binary) => Ok(binary.bytes),
+        Bson::Array(array) => array
+            .into_iter()
+            .map(|byte| match byte {
+                Bson::Int32(byte) => u8::try_from(byte).map_err(E::custom),
+                Bson::Int64(byte) => u8::try_from(byte).map_err(E::custom),
+                other => Err(E::custom(format!("expected a byte, found {}", other))),
+            })
+            .collect(),
+        other => Err(E::custom(format!(
+            "expected a binary or an array of bytes, found {}",
+            other
+        ))),
+    }
+}
+
+impl BinaryBytes for Vec<u8> {
+    fn serialize_binary<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self)
+    }
+
+    fn deserialize_binary<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        bytes_from_bson(Bson::deserialize(deserializer)?)
+    }
+}
+
+impl<T: BinaryBytes> BinaryBytes for Option<T> {
+    fn serialize_binary<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Some(value) => value.serialize_binary(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    fn deserialize_binary<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Bson::deserialize(deserializer)? {
+            Bson::Null => Ok(None),
+            other => T::deserialize_binary(BsonDeserializer::new(other))
+                .map(Some)
+                .map_err(D::Error::custom),
+        }
+    }
+}
+
+/// Serializes `value` as BSON binary instead of an array of numbers.
+pub fn serialize<T: BinaryBytes, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    value.serialize_binary(serializer)
+}
+
+/// Deserializes a value serialized by [`serialize`], accepts arrays of numbers too.
+pub fn deserialize<'de, T: BinaryBytes, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    T::deserialize_binary(deserializer)
+}
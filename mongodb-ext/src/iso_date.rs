@@ -0,0 +1,69 @@
+//! This module contains the serde helpers used by fields declared with `#[date_as_iso]`.
+//!
+//! Use them directly with `#[serde(with = "mongodb_ext::iso_date")]` on structs not declared with [`mongo_db!`](crate::mongo_db).
+
+use crate::{
+    mongodb::bson::{Bson, DateTime, Deserializer as BsonDeserializer},
+    serde::{
+        de::{Deserialize, Deserializer, Error as DeError},
+        ser::{Error as SerError, Serialize, Serializer},
+    },
+};
+
+/// Types that can be serialized as ISO-8601 strings by [`serialize`] / [`deserialize`].
+pub trait IsoDate: Sized {
+    /// Serializes `self` as ISO-8601 string if `serializer` is human-readable, as BSON date otherwise.
+    fn serialize_iso<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+    /// Deserializes `Self` from an ISO-8601 string or a BSON date.
+    fn deserialize_iso<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+impl IsoDate for DateTime {
+    fn serialize_iso<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            let iso = self.try_to_rfc3339_string().map_err(S::Error::custom)?;
+            serializer.serialize_str(&iso)
+        } else {
+            self.serialize(serializer)
+        }
+    }
+
+    fn deserialize_iso<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Bson::deserialize(deserializer)? {
+            Bson::DateTime(date) => Ok(date),
+            Bson::String(iso) => DateTime::parse_rfc3339_str(iso).map_err(D::Error::custom),
+            other => Err(D::Error::custom(format!(
+                "expected an ISO-8601 string or a date, found {}",
+                other
+            ))),
+        }
+    }
+}
+
+impl<T: IsoDate> IsoDate for Option<T> {
+    fn serialize_iso<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Some(value) => value.serialize_iso(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    fn deserialize_iso<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Bson::deserialize(deserializer)? {
+            Bson::Null => Ok(None),
+            other => T::deserialize_iso(BsonDeserializer::new(other))
+                .map(Some)
+                .map_err(D::Error::custom),
+        }
+    }
+}
+
+/// Serializes `value` as ISO-8601 string (eg. JSON) or as BSON date (eg. the driver), depending on [`Serializer::is_human_readable`].
+pub fn serialize<T: IsoDate, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    value.serialize_iso(serializer)
+}
+
+/// Deserializes a value serialized by [`serialize`], accepts BSON dates in human-readable formats too.
+pub fn deserialize<'de, T: IsoDate, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    T::deserialize_iso(deserializer)
+}
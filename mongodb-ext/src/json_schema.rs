@@ -0,0 +1,117 @@
+//! This module contains the helpers used by the generated `json_schema()` functions, enabled by the _"json-schema"_ feature.
+//!
+//! Types are only known as written in [`mongo_db!`](crate::mongo_db), thus they are mapped by name on a best-effort basis:
+//! - Primitives map to their JSON Schema types, eg. `u32` to `integer` and `String` to `string`.
+//! - `Option<T>` allows `null` next to the schema of `T` and makes the field optional.
+//! - Sequences (eg. `Vec<T>`) map to arrays of `T`, maps (eg. `HashMap<String, T>`) map to objects with values of `T`.
+//! - `DateTime` maps to strings of format `date-time`, `ObjectId` / `DefaultId` map to strings of 24 hex digits.
+//! - All other types map to `{ "type": "object" }` with the type's name as `title`.
+//!
+//! Type aliases and `#[serde(...)]` attributes that change the serialized form (eg. `rename`, `with`) are not taken into account.
+
+use crate::serde_json::{json, Map, Value};
+
+/// Returns the JSON Schema of an object with the given fields.
+///
+/// Each field is given as its serialized name and its type as written in Rust, eg. `("firstName", "Option<String>")`.
+/// All fields that are not of type `Option<T>` are listed as `required`.
+pub fn object_schema(fields: &[(&str, &str)]) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for (name, ty) in fields {
+        let ty = strip_whitespace(ty);
+        if generic(&ty).map(|(outer, _)| outer) != Some("Option") {
+            required.push(Value::from(*name));
+        }
+        properties.insert(String::from(*name), type_schema(&ty));
+    }
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// Returns the JSON Schema of the Rust type `ty`, eg. `"Vec<u8>"`.
+pub fn type_schema(ty: &str) -> Value {
+    let ty = strip_whitespace(ty);
+    if let Some((outer, arguments)) = generic(&ty) {
+        let arguments = split_arguments(arguments);
+        return match (outer, arguments.as_slice()) {
+            ("Option", [inner]) => nullable(type_schema(inner)),
+            ("Box" | "Rc" | "Arc" | "Cow", [.., inner]) => type_schema(inner),
+            ("Vec" | "VecDeque" | "LinkedList" | "HashSet" | "BTreeSet", [inner]) => {
+                json!({ "type": "array", "items": type_schema(inner) })
+            }
+            ("HashMap" | "BTreeMap", [_, value]) => {
+                json!({ "type": "object", "additionalProperties": type_schema(value) })
+            }
+            _ => fallback(&ty),
+        };
+    }
+    match last_segment(&ty) {
+        "String" | "str" | "&str" | "char" => json!({ "type": "string" }),
+        "bool" => json!({ "type": "boolean" }),
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128"
+        | "usize" => json!({ "type": "integer" }),
+        "f32" | "f64" => json!({ "type": "number" }),
+        "DateTime" => json!({ "type": "string", "format": "date-time" }),
+        "ObjectId" | "DefaultId" => json!({ "type": "string", "pattern": "^[0-9a-f]{24}$" }),
+        "Document" => json!({ "type": "object" }),
+        _ => fallback(&ty),
+    }
+}
+
+/// Schema of types that cannot be mapped.
+fn fallback(ty: &str) -> Value {
+    json!({ "type": "object", "title": ty })
+}
+
+/// Allows `null` next to the values described by `schema`.
+fn nullable(mut schema: Value) -> Value {
+    match schema.get("type").and_then(Value::as_str) {
+        Some(ty) => {
+            let ty = String::from(ty);
+            schema["type"] = json!([ty, "null"]);
+            schema
+        }
+        None => json!({ "anyOf": [schema, { "type": "null" }] }),
+    }
+}
+
+/// Removes all whitespace that [`stringify!`] puts between tokens, eg. `Option < String >`.
+fn strip_whitespace(ty: &str) -> String {
+    ty.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// Returns the last segment of a path, eg. `DateTime` for `mongodb::bson::DateTime`.
+fn last_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// Splits a generic type into the last segment of its path and its (unsplit) arguments, eg. `Vec<u8>` into `Vec` and `u8`.
+fn generic(ty: &str) -> Option<(&str, &str)> {
+    let start = ty.find('<')?;
+    let arguments = ty[start + 1..].strip_suffix('>')?;
+    Some((last_segment(&ty[..start]), arguments))
+}
+
+/// Splits generic arguments at top-level commas, eg. `String,Vec<(u8,u8)>` into `String` and `Vec<(u8,u8)>`.
+fn split_arguments(arguments: &str) -> Vec<&str> {
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut split = Vec::new();
+    for (i, c) in arguments.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                split.push(&arguments[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    split.push(&arguments[start..]);
+    split
+}
@@ -13,6 +13,44 @@
 //! ## `mongodb-gridfs`
 //!
 //! Enabling this feature creates automatic implementations of the then-available trait `GridFSDb`.
+//!
+//! ## `testing`
+//!
+//! Enabling this feature generates additional helpers on each collection that are useful in tests, eg. `matches_document`.
+//!
+//! It also generates `schema_drift(document)` on each collection, which reports the fields of a (eg. production) document that do not match the collection, see [`testing::schema_drift`].
+//!
+//! [`MongoClient::reset`] drops the database and creates all collections, indexes and views again, which gives each test a clean slate.
+//!
+//! ## `change-streams`
+//!
+//! Enabling this feature generates `watch_{collection_name}` and `watch_{collection_name}_from` on the database handler to open change streams on single collections.
+//!
+//! ## `json-schema`
+//!
+//! Enabling this feature generates `json_schema()` on each collection, which returns a JSON Schema of the collection's fields as [`serde_json::Value`], eg. to feed OpenAPI generators.
+//!
+//! Field types are mapped by name on a best-effort basis, see [`json_schema`].
+//!
+//! ## `zstd-compression`, `snappy-compression` and `zlib-compression`
+//!
+//! Enabling these features enables the driver feature of the same name, which is needed to use the compressor with [`MongoClient::new_with_compressors`].
+//!
+//! ## `default-derives`
+//!
+//! Enabling this feature derives `Debug` and `Clone` for every collection struct, in addition to the derives declared on the collection.
+//! Derives that are declared anyways are not duplicated, and collections with [sensitive fields](mongo_db#sensitive-fields) keep their redacting `Debug` implementation.
+//! Thus all field types need to implement `Debug` and `Clone`.
+//!
+//! ## `shutdown-on-drop`
+//!
+//! Enabling this feature allows the `shutdown_on_drop` database parameter, which shuts down the client when the database handler is dropped.
+//! It pulls in `tokio` to block on the shutdown, see [`mongo_db`](mongo_db#shutting-down-on-drop) for details.
+//!
+//! ## `utoipa`
+//!
+//! Enabling this feature allows the `to_schema` collection parameter, which derives [`utoipa::ToSchema`] for the collection to reference it in OpenAPI documents.
+//! See [`mongo_db`](mongo_db#openapi-schemas) for details.
 
 /// To make [`mongo_db`] work reliably a couple of re-exports are needed, these are not relevant for using the macro.
 #[doc(hidden)]
@@ -22,12 +60,51 @@ pub use {async_trait, mongodb, mongodb_ext_derive, paste, serde, typed_builder};
 pub mod traits;
 
 #[doc(hidden)]
-pub use crate::mongodb_ext_derive::case;
+pub mod read_only;
+
+#[cfg(feature = "shutdown-on-drop")]
+#[doc(hidden)]
+pub mod shutdown;
+
+pub mod iso_date;
+
+pub mod binary;
+
+pub mod as_string;
+
+pub mod as_i64;
+
+pub mod object_id;
+
+pub mod validation;
+
+pub mod normalize;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "json-schema")]
+pub mod json_schema;
+
+#[cfg(feature = "json-schema")]
+#[doc(hidden)]
+pub use serde_json;
+
+#[cfg(feature = "utoipa")]
+#[doc(hidden)]
+pub use utoipa;
+
+#[doc(hidden)]
+pub use crate::mongodb_ext_derive::{case, paste_snake_case, serde_case};
 
 #[cfg(feature = "mongodb-gridfs")]
 pub use crate::traits::GridFSDb;
 
-pub use crate::traits::{MongoClient, MongoCollection};
+pub use crate::traits::{MongoClient, MongoCollection, MongoCollectionId, MongoView};
+
+pub use crate::read_only::ReadOnlyCollection;
+
+pub use crate::validation::ValidationError;
 
 /// Defines the default type inside an [`Option`] for the `_id` field.
 ///
@@ -38,786 +115,7121 @@ pub use mongodb::bson::oid::ObjectId as DefaultId;
 /// Defines the default value used as schema version in [`MongoCollection::SCHEMA_VERSION`] if not specified otherwise.
 pub const DEFAULT_SCHEMA_VERSION: i32 = 1;
 
+/// Name of the collection that stores the counters of collections declared with the `sequence` parameter.
+///
+/// Each counter is a document `{ _id: <collection name>, seq: <last id> }`.
+pub const COUNTERS_COLLECTION: &str = "_counters";
+
+/// Result of [`MongoClient::health_report`].
+///
+/// Each check is stored independently, thus a failing check does not hide the results of the others.
+#[derive(Debug, Clone)]
+pub struct HealthReport {
+    /// Round trip time of a `ping` command, see [`MongoClient::ping_latency`].
+    pub ping_latency: mongodb::error::Result<std::time::Duration>,
+    /// Version of the server as reported by the `buildInfo` command, eg. `"6.0.5"`.
+    pub server_version: mongodb::error::Result<String>,
+    /// Name of each collection in [`MongoClient::COLLECTION_NAMES`] and whether it exists in the database.
+    pub collections: mongodb::error::Result<Vec<(&'static str, bool)>>,
+}
+
+impl HealthReport {
+    /// Returns `true` if all checks succeeded and all collections exist.
+    pub fn is_healthy(&self) -> bool {
+        self.ping_latency.is_ok()
+            && self.server_version.is_ok()
+            && self
+                .collections
+                .as_ref()
+                .is_ok_and(|collections| collections.iter().all(|(_, exists)| *exists))
+    }
+}
+
+/// Deserializes `document` that was read from the collection called `collection_name`.
+///
+/// Unlike [`from_document`](mongodb::bson::from_document), errors name the collection and the document's `_id`,
+/// eg. ``failed to deserialize document with `_id` 42 of `users`: missing field `name` ``.
+/// The generated helpers that return deserialized documents themselves (eg. `find_by_{field_name}`) use this.
+pub fn deserialize_document<T>(
+    collection_name: &str,
+    document: mongodb::bson::Document,
+) -> mongodb::error::Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let id = document.get("_id").cloned();
+    mongodb::bson::from_document(document).map_err(|error| {
+        let context = match id {
+            Some(id) => format!("document with `_id` {} of `{}`", id, collection_name),
+            None => format!("document without `_id` of `{}`", collection_name),
+        };
+        <mongodb::bson::de::Error as serde::de::Error>::custom(format!(
+            "failed to deserialize {}: {}",
+            context, error
+        ))
+        .into()
+    })
+}
+
+/// Deserializes the top-level field `key` of `raw`, which was read from the collection called `collection_name`.
+///
+/// Only the field's value is converted, the rest of the document is neither parsed nor copied.
+/// Returns [`None`] if `raw` has no field `key`. Errors name the collection and the key, like [`deserialize_document`].
+/// The generated `read_field` of each collection maps declared field names to keys and forwards to this.
+pub fn read_raw_field<T>(
+    collection_name: &str,
+    raw: &mongodb::bson::RawDocument,
+    key: &str,
+) -> mongodb::error::Result<Option<T>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let context = |error: &dyn std::fmt::Display| -> mongodb::error::Error {
+        <mongodb::bson::de::Error as serde::de::Error>::custom(format!(
+            "failed to read field `{}` of `{}`: {}",
+            key, collection_name, error
+        ))
+        .into()
+    };
+    let value = match raw.get(key).map_err(|e| context(&e))? {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    let value = mongodb::bson::Bson::try_from(value).map_err(|e| context(&e))?;
+    mongodb::bson::from_bson(value)
+        .map(Some)
+        .map_err(|e| context(&e))
+}
+
+/// Adds `deletedAt: null` to `filter` if `T` is declared with the `soft_delete` parameter, see [`MongoCollection::SOFT_DELETE`].
+///
+/// The generated find and count helpers use this to exclude soft deleted documents.
+/// Filters that contain `deletedAt` already are returned unchanged, thus soft deleted documents can still be selected explicitly.
+pub fn exclude_soft_deleted<T>(
+    filter: Option<mongodb::bson::Document>,
+) -> Option<mongodb::bson::Document>
+where
+    T: MongoCollection,
+{
+    if !T::SOFT_DELETE {
+        return filter;
+    }
+    let mut filter = filter.unwrap_or_default();
+    if !filter.contains_key("deletedAt") {
+        filter.insert("deletedAt", mongodb::bson::Bson::Null);
+    }
+    Some(filter)
+}
+
+/// Checks `document` with [`MongoCollection::validate`] before it is written by one of the generated write helpers.
+///
+/// The [`ValidationError`] is returned inside of a mongodb error, get it with [`Error::get_custom`](mongodb::error::Error::get_custom).
+pub fn validate_before_write<T>(document: &T) -> mongodb::error::Result<()>
+where
+    T: MongoCollection,
+{
+    document.validate().map_err(mongodb::error::Error::custom)
+}
+
+/// Builds the update that turns the `stored` document into `document`, or returns [`None`] if they are equal.
+///
+/// Keys of `document` with a different or no stored value are set with `$set`, stored keys missing in `document` are removed with `$unset`.
+/// The `_id` is never part of the update.
+/// The generated `save_changes` helpers use this on the serialized document, thus fields that serialize to nothing, eg. [`None`] with `#[null_as(omit)]`, are removed.
+pub fn changes_update(
+    stored: &mongodb::bson::Document,
+    document: &mongodb::bson::Document,
+) -> Option<mongodb::bson::Document> {
+    let set: mongodb::bson::Document = document
+        .iter()
+        .filter(|(key, value)| *key != "_id" && stored.get(key.as_str()) != Some(*value))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    let unset: mongodb::bson::Document = stored
+        .keys()
+        .filter(|key| *key != "_id" && !document.contains_key(key.as_str()))
+        .map(|key| (key.clone(), mongodb::bson::Bson::String(String::new())))
+        .collect();
+    let mut update = mongodb::bson::Document::new();
+    if !set.is_empty() {
+        update.insert("$set", set);
+    }
+    if !unset.is_empty() {
+        update.insert("$unset", unset);
+    }
+    if update.is_empty() {
+        None
+    } else {
+        Some(update)
+    }
+}
+
+/// Maximum number of operations in one write command sent by the generated `upsert` helpers, the server's default `maxWriteBatchSize`.
+pub const MAX_WRITE_BATCH_SIZE: usize = 100_000;
+
+/// Maximum size in bytes of the operations in one write command sent by the generated `upsert` helpers.
+///
+/// Commands are limited to 16 MiB like all documents, 16 KiB of that are left for the command's other fields.
+pub const MAX_WRITE_BATCH_BYTES: usize = 16 * 1024 * 1024 - 16 * 1024;
+
+/// Result of the generated `upsert` helpers, summed up over all batches.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UpsertManyResult {
+    /// Number of documents that replaced a stored document with the same `_id`.
+    pub matched_count: u64,
+    /// Number of replaced documents that actually changed.
+    pub modified_count: u64,
+    /// `_id`s of the inserted documents by their index in the upserted documents.
+    pub upserted_ids: std::collections::HashMap<usize, mongodb::bson::Bson>,
+}
+
+/// Error of the generated `upsert` helpers if some documents could not be written, get it with [`Error::get_custom`](mongodb::error::Error::get_custom).
+///
+/// The other documents are written anyway.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpsertManyError {
+    /// Counts of the documents that were written.
+    pub result: UpsertManyResult,
+    /// The `writeErrors` of all batches, their `index` is the index in the upserted documents.
+    pub write_errors: Vec<mongodb::bson::Document>,
+    /// The `writeConcernError` of each batch that has one, the documents were written but not acknowledged as requested.
+    pub write_concern_errors: Vec<mongodb::bson::Document>,
+}
+
+/// Splits `operations` into batches of at most [`MAX_WRITE_BATCH_SIZE`] operations and [`MAX_WRITE_BATCH_BYTES`] bytes, keeping their order.
+///
+/// An operation that exceeds [`MAX_WRITE_BATCH_BYTES`] on its own gets a batch of its own, the server rejects it then.
+pub fn write_batches(
+    operations: Vec<mongodb::bson::Document>,
+) -> Vec<Vec<mongodb::bson::Document>> {
+    let mut batches = Vec::new();
+    let mut batch = Vec::new();
+    let mut batch_bytes = 0;
+    for operation in operations {
+        // the key of each array element takes up to 6 bytes and a type byte and a null byte are added
+        let bytes = mongodb::bson::to_vec(&operation).map_or(0, |bytes| bytes.len()) + 8;
+        if !batch.is_empty()
+            && (batch.len() == MAX_WRITE_BATCH_SIZE || batch_bytes + bytes > MAX_WRITE_BATCH_BYTES)
+        {
+            batches.push(std::mem::take(&mut batch));
+            batch_bytes = 0;
+        }
+        batch.push(operation);
+        batch_bytes += bytes;
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    batches
+}
+
+/// Reads a count of an `update` command's response, which may be stored as `Int32` or `Int64`.
+fn response_count(response: &mongodb::bson::Document, key: &str) -> u64 {
+    match response.get(key) {
+        Some(mongodb::bson::Bson::Int32(count)) => u64::try_from(*count).unwrap_or_default(),
+        Some(mongodb::bson::Bson::Int64(count)) => u64::try_from(*count).unwrap_or_default(),
+        _ => 0,
+    }
+}
+
+/// Reads the `index` of an entry of `upserted` or `writeErrors` in an `update` command's response.
+fn response_index(entry: &mongodb::bson::Document) -> Option<usize> {
+    match entry.get("index") {
+        Some(mongodb::bson::Bson::Int32(index)) => usize::try_from(*index).ok(),
+        Some(mongodb::bson::Bson::Int64(index)) => usize::try_from(*index).ok(),
+        _ => None,
+    }
+}
+
+/// Inserts or replaces `replacements` by their `_id` in the collection called `collection_name`, used by the generated `upsert` helpers.
+///
+/// The replacements are sent in unordered `update` commands, split by [`write_batches`].
+/// Returns the indexes of the written replacements along with the result, thus callers can act on them even if writing others failed.
+/// The result is an error containing an [`UpsertManyError`] if some replacements could not be written or a write concern failed.
+/// Other errors, eg. a lost connection, are returned as they are and the remaining batches are not sent.
+pub async fn upsert_many(
+    database: &mongodb::Database,
+    collection_name: &str,
+    replacements: Vec<mongodb::bson::Document>,
+) -> (Vec<usize>, mongodb::error::Result<UpsertManyResult>) {
+    let operations = replacements
+        .into_iter()
+        .map(|replacement| {
+            mongodb::bson::doc! {
+                "q": { "_id": replacement.get("_id").cloned().unwrap_or(mongodb::bson::Bson::Null) },
+                "u": replacement,
+                "upsert": true,
+            }
+        })
+        .collect();
+    let mut written = Vec::new();
+    let mut result = UpsertManyResult::default();
+    let mut write_errors = Vec::new();
+    let mut write_concern_errors = Vec::new();
+    let mut offset = 0;
+    for batch in write_batches(operations) {
+        let len = batch.len();
+        let command = mongodb::bson::doc! {
+            "update": collection_name,
+            "updates": batch,
+            "ordered": false,
+        };
+        let response = match database.run_command(command, None).await {
+            Ok(response) => response,
+            Err(error) => return (written, Err(error)),
+        };
+        let mut failed = Vec::new();
+        if let Ok(errors) = response.get_array("writeErrors") {
+            for error in errors.iter().filter_map(mongodb::bson::Bson::as_document) {
+                let mut error = error.clone();
+                if let Some(index) = response_index(&error) {
+                    failed.push(index);
+                    error.insert("index", (offset + index) as i64);
+                }
+                write_errors.push(error);
+            }
+        }
+        if let Ok(error) = response.get_document("writeConcernError") {
+            write_concern_errors.push(error.clone());
+        }
+        let mut upserted = 0;
+        if let Ok(entries) = response.get_array("upserted") {
+            for entry in entries.iter().filter_map(mongodb::bson::Bson::as_document) {
+                if let (Some(index), Some(id)) = (response_index(entry), entry.get("_id")) {
+                    result.upserted_ids.insert(offset + index, id.clone());
+                    upserted += 1;
+                }
+            }
+        }
+        result.matched_count += response_count(&response, "n").saturating_sub(upserted);
+        result.modified_count += response_count(&response, "nModified");
+        written.extend(
+            (0..len)
+                .filter(|index| !failed.contains(index))
+                .map(|index| offset + index),
+        );
+        offset += len;
+    }
+    if write_errors.is_empty() && write_concern_errors.is_empty() {
+        (written, Ok(result))
+    } else {
+        (
+            written,
+            Err(mongodb::error::Error::custom(UpsertManyError {
+                result,
+                write_errors,
+                write_concern_errors,
+            })),
+        )
+    }
+}
+
+/// Builder for aggregation pipelines.
+///
+/// Each method appends one stage, thus stages run in the order the methods are called.
+/// The stage operator is named by the method, only its value is given, eg. `match_(doc! { "active": true })` appends `{ "$match": { "active": true } }`.
+/// Stages without a method can be appended with [`Pipeline::stage`].
+///
+/// A [`Pipeline`] can be passed wherever the driver expects a pipeline, eg. to [`Collection::aggregate`](mongodb::Collection::aggregate) or the generated `aggregate_{collection_name}` helpers.
+///
+/// ```rust
+/// use mongodb_ext::Pipeline;
+/// use mongodb::bson::doc;
+///
+/// let pipeline = Pipeline::new()
+///     .match_(doc! { "active": true })
+///     .group(doc! { "_id": "$country", "users": { "$sum": 1 } })
+///     .sort(doc! { "users": -1 })
+///     .limit(10)
+///     .build();
+///
+/// assert_eq!(
+///     pipeline,
+///     vec![
+///         doc! { "$match": { "active": true } },
+///         doc! { "$group": { "_id": "$country", "users": { "$sum": 1 } } },
+///         doc! { "$sort": { "users": -1 } },
+///         doc! { "$limit": 10_i64 },
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pipeline {
+    stages: Vec<mongodb::bson::Document>,
+}
+
+impl Pipeline {
+    /// Creates an empty pipeline.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a complete stage document, eg. `doc! { "$sample": { "size": 5 } }`.
+    pub fn stage(mut self, stage: mongodb::bson::Document) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Appends a `$match` stage that filters documents by `filter`.
+    pub fn match_(self, filter: mongodb::bson::Document) -> Self {
+        self.stage(mongodb::bson::doc! { "$match": filter })
+    }
+
+    /// Appends a `$project` stage.
+    pub fn project(self, projection: mongodb::bson::Document) -> Self {
+        self.stage(mongodb::bson::doc! { "$project": projection })
+    }
+
+    /// Appends an `$addFields` stage.
+    pub fn add_fields(self, fields: mongodb::bson::Document) -> Self {
+        self.stage(mongodb::bson::doc! { "$addFields": fields })
+    }
+
+    /// Appends a `$group` stage, `group` has to contain the `_id` to group by.
+    pub fn group(self, group: mongodb::bson::Document) -> Self {
+        self.stage(mongodb::bson::doc! { "$group": group })
+    }
+
+    /// Appends a `$sort` stage.
+    pub fn sort(self, sort: mongodb::bson::Document) -> Self {
+        self.stage(mongodb::bson::doc! { "$sort": sort })
+    }
+
+    /// Appends a `$skip` stage.
+    pub fn skip(self, skip: i64) -> Self {
+        self.stage(mongodb::bson::doc! { "$skip": skip })
+    }
+
+    /// Appends a `$limit` stage.
+    pub fn limit(self, limit: i64) -> Self {
+        self.stage(mongodb::bson::doc! { "$limit": limit })
+    }
+
+    /// Appends an `$unwind` stage for the array at `path`, eg. `"$tags"`.
+    pub fn unwind(self, path: &str) -> Self {
+        self.stage(mongodb::bson::doc! { "$unwind": path })
+    }
+
+    /// Appends a `$lookup` stage.
+    pub fn lookup(self, lookup: mongodb::bson::Document) -> Self {
+        self.stage(mongodb::bson::doc! { "$lookup": lookup })
+    }
+
+    /// Appends a `$count` stage that outputs a single document with the number of documents in `field`.
+    pub fn count(self, field: &str) -> Self {
+        self.stage(mongodb::bson::doc! { "$count": field })
+    }
+
+    /// Returns the stages of this pipeline.
+    pub fn build(self) -> Vec<mongodb::bson::Document> {
+        self.stages
+    }
+}
+
+impl From<Pipeline> for Vec<mongodb::bson::Document> {
+    fn from(pipeline: Pipeline) -> Self {
+        pipeline.build()
+    }
+}
+
+impl IntoIterator for Pipeline {
+    type Item = mongodb::bson::Document;
+    type IntoIter = std::vec::IntoIter<mongodb::bson::Document>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.stages.into_iter()
+    }
+}
+
+// Helper macros of the feature flags, one for each state of a flag.
+// Checking a feature flag inside of `mongo_db!` would check the feature flags of the calling crate instead of this crate.
+
+/// Expands to the given tokens if the _"testing"_ feature is enabled, otherwise expands to nothing.
+#[cfg(feature = "testing")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_testing {
+    ($($tokens:tt)*) => {
+        $($tokens)*
+    };
+}
+
+/// Expands to the given tokens if the _"testing"_ feature is enabled, otherwise expands to nothing.
+#[cfg(not(feature = "testing"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_testing {
+    ($($tokens:tt)*) => {};
+}
+
+/// Expands to the given tokens if the _"change-streams"_ feature is enabled, otherwise expands to nothing.
+#[cfg(feature = "change-streams")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_change_streams {
+    ($($tokens:tt)*) => {
+        $($tokens)*
+    };
+}
+
+/// Expands to the given tokens if the _"change-streams"_ feature is enabled, otherwise expands to nothing.
+#[cfg(not(feature = "change-streams"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_change_streams {
+    ($($tokens:tt)*) => {};
+}
+
+/// Expands to the given tokens if the _"json-schema"_ feature is enabled, otherwise expands to nothing.
+#[cfg(feature = "json-schema")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_json_schema {
+    ($($tokens:tt)*) => {
+        $($tokens)*
+    };
+}
+
+/// Expands to the given tokens if the _"json-schema"_ feature is enabled, otherwise expands to nothing.
+#[cfg(not(feature = "json-schema"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_json_schema {
+    ($($tokens:tt)*) => {};
+}
+
+/// Expands the given struct, deriving [`ToSchema`](utoipa::ToSchema) if it is declared with `to_schema` and the _"utoipa"_ feature is enabled.
+#[cfg(feature = "utoipa")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_utoipa {
+    ([to_schema] $($tokens:tt)*) => {
+        #[derive($crate::utoipa::ToSchema)]
+        $($tokens)*
+    };
+    ([] $($tokens:tt)*) => {
+        $($tokens)*
+    };
+}
+
+/// Expands the given struct, deriving [`ToSchema`](utoipa::ToSchema) if it is declared with `to_schema` and the _"utoipa"_ feature is enabled.
+#[cfg(not(feature = "utoipa"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_utoipa {
+    ([to_schema] $($tokens:tt)*) => {
+        std::compile_error!("`to_schema` needs the \"utoipa\" feature of mongodb-ext");
+    };
+    ([] $($tokens:tt)*) => {
+        $($tokens)*
+    };
+}
+
+/// Expands the [`Drop`] implementation of database handlers declared with `<shutdown_on_drop>` if the _"shutdown-on-drop"_ feature is enabled.
+#[cfg(feature = "shutdown-on-drop")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_shutdown_on_drop {
+    ((shutdown) $db_name:ident) => {};
+    ((shutdown shutdown_on_drop) $db_name:ident) => {
+        impl std::ops::Drop for $db_name {
+            fn drop(&mut self) {
+                $crate::shutdown::shutdown_blocking(self.client.clone());
+            }
+        }
+    };
+}
+
+/// Expands the [`Drop`] implementation of database handlers declared with `<shutdown_on_drop>` if the _"shutdown-on-drop"_ feature is enabled.
+#[cfg(not(feature = "shutdown-on-drop"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_shutdown_on_drop {
+    ((shutdown) $db_name:ident) => {};
+    ((shutdown shutdown_on_drop) $db_name:ident) => {
+        std::compile_error!(
+            "`shutdown_on_drop` needs the \"shutdown-on-drop\" feature of mongodb-ext"
+        );
+    };
+}
+
+/// Expands the given collection struct, adding `Debug` and `Clone` to its derives if the _"default-derives"_ feature is enabled.
+///
+/// The markers of the collection's fields are searched for `sensitive` first, such collections implement `Debug` themselves.
+#[cfg(feature = "default-derives")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_default_derives {
+    ($schema:tt $debug:tt [sensitive $($markers:ident)*] $($tokens:tt)*) => {
+        $crate::expand_default_derives! { $schema [] [] $($tokens)* }
+    };
+    ($schema:tt $debug:tt [$marker:ident $($markers:ident)*] $($tokens:tt)*) => {
+        $crate::expand_default_derives! { $schema $debug [$($markers)*] $($tokens)* }
+    };
+    ($schema:tt [$($debug:ident)?] [] $($tokens:tt)*) => {
+        $crate::expand_utoipa! {
+            $schema
+            #[$crate::mongodb_ext_derive::merge_derives($($debug,)? Clone)]
+            $($tokens)*
+        }
+    };
+}
+
+/// Expands the given collection struct, adding `Debug` and `Clone` to its derives if the _"default-derives"_ feature is enabled.
+#[cfg(not(feature = "default-derives"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_default_derives {
+    ($schema:tt $debug:tt $markers:tt $($tokens:tt)*) => {
+        $crate::expand_utoipa! {
+            $schema
+            $($tokens)*
+        }
+    };
+}
+
 /// This macro parses the per-collection parameters in a more usable format.
+///
+/// Parameters are munched one by one, each parameter fills its own slot.
+/// Slots are positional, new slots are appended at the end.
 #[macro_export]
 #[doc(hidden)]
 macro_rules! parse_collection_params {
+    // collection level attributes that configure the collection are turned into parameters, all others are kept
     (
-        version: $version:literal,
-        _id: $id:ident
+        @attrs [$($params:tt)*] [$($kept:tt)*]
+        #[ttl(field = $field:ident, seconds = $seconds:literal $(,)?)]
         $($rest:tt)*
     ) => {
-        $crate::expand_collection_version! {
-            version = $version;
-            id = $id;
+        $crate::parse_collection_params! {
+            @attrs [@ttl $field $seconds, $($params)*] [$($kept)*]
             $($rest)*
         }
     };
     (
-        _id: $id:ident,
-        version: $version:literal
+        @attrs [$($params:tt)*] [$($kept:tt)*]
+        #[ttl $($invalid:tt)*]
         $($rest:tt)*
     ) => {
-        $crate::expand_collection_version! {
-            version = $version;
-            id = $id;
+        std::compile_error!("`ttl` expects a date field and the seconds until documents expire, eg. `#[ttl(field = created_at, seconds = 3600)]`");
+    };
+    (
+        @attrs [$($params:tt)*] [$($kept:tt)*]
+        #[$($attr:tt)*]
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @attrs [$($params)*] [$($kept)* #[$($attr)*]]
             $($rest)*
         }
     };
     (
-        version: $version:literal
+        @attrs [$($params:tt)*] [$($kept:tt)*]
+        $coll_name:ident
         $($rest:tt)*
     ) => {
-        $crate::expand_collection_version! {
-            version = $version;
-            id = ;
+        $crate::parse_collection_params! {
+            @rename_all [$($params)*] []
+            $($kept)*
+            $coll_name
             $($rest)*
         }
     };
+    // `rename_all` is moved in front of all other parameters, since they may store keys in the collection's case
     (
-        _id: $id:ident
+        @rename_all [rename_all: $case:tt $(, $($params:tt)*)?] [$($kept:tt)*]
         $($rest:tt)*
     ) => {
-        $crate::expand_collection_version! {
-            version = ;
-            id = $id;
+        $crate::parse_collection_params! {
+            @init [rename_all: $case, $($kept)* $($($params)*)?]
             $($rest)*
         }
     };
     (
+        @rename_all [@ttl $field:ident $seconds:literal, $($params:tt)*] [$($kept:tt)*]
         $($rest:tt)*
     ) => {
-        $crate::expand_collection_version! {
-            version = ;
-            id = ;
+        $crate::parse_collection_params! {
+            @rename_all [$($params)*] [$($kept)* @ttl $field $seconds,]
             $($rest)*
         }
     };
-}
-
-/// Expands schema version that is given in `<` / `>` behind each collection.
-#[macro_export]
-#[doc(hidden)]
-macro_rules! expand_collection_version {
     (
-        version = ;
+        @rename_all [$param:ident $(: $($value:tt)::+ $($word:ident $(= $($word_value:tt)::+)?)?)? $(, $($params:tt)*)?] [$($kept:tt)*]
         $($rest:tt)*
     ) => {
-        $crate::expand_collection_id!{
-            version = $crate::DEFAULT_SCHEMA_VERSION;
+        $crate::parse_collection_params! {
+            @rename_all [$($($params)*)?] [$($kept)* $param $(: $($value)::+ $($word $(= $($word_value)::+)?)?)?,]
             $($rest)*
         }
     };
     (
-        version = $version:literal;
+        @rename_all [] [$($kept:tt)*]
         $($rest:tt)*
     ) => {
-        $crate::expand_collection_id!{
-            version = $version;
+        $crate::parse_collection_params! {
+            @init [$($kept)*]
             $($rest)*
         }
     };
-}
-
-/// Expands collection _id that is given in `<` / `>` behind each collection.
-#[macro_export]
-#[doc(hidden)]
-macro_rules! expand_collection_id {
+    // entry point, initialize all slots
+    // the case of the stored keys is kept as first item, thus it is known to all parameters that need it
     (
-        version = $version:expr;
-        id = ;
+        @init [$($params:tt)*]
         $($rest:tt)*
     ) => {
-        $crate::expand_collection!{
-            @add_id
-            version = $version;
-            id = $crate::DefaultId;
+        $crate::parse_collection_params! {
+            @parse [$($params)*]
+            (version)
+            (id)
+            (id_gen)
+            (attrs #[serde(rename_all = "camelCase")])
+            (items const RENAME_ALL: &'static str = "camelCase";)
+            (accessor)
+            (sequence)
+            (partial)
+            (soft_delete)
+            (schema)
             $($rest)*
         }
     };
+    (@parse [rename_all: camel $($params:tt)*] $($rest:tt)*) => {
+        $crate::parse_collection_params! { @parse [rename_all: "camelCase" $($params)*] $($rest)* }
+    };
+    (@parse [rename_all: snake $($params:tt)*] $($rest:tt)*) => {
+        $crate::parse_collection_params! { @parse [rename_all: "snake_case" $($params)*] $($rest)* }
+    };
+    (@parse [rename_all: pascal $($params:tt)*] $($rest:tt)*) => {
+        $crate::parse_collection_params! { @parse [rename_all: "PascalCase" $($params)*] $($rest)* }
+    };
+    (@parse [rename_all: kebab $($params:tt)*] $($rest:tt)*) => {
+        $crate::parse_collection_params! { @parse [rename_all: "kebab-case" $($params)*] $($rest)* }
+    };
+    (@parse [rename_all: lower $($params:tt)*] $($rest:tt)*) => {
+        $crate::parse_collection_params! { @parse [rename_all: "lowercase" $($params)*] $($rest)* }
+    };
+    (@parse [rename_all: upper $($params:tt)*] $($rest:tt)*) => {
+        $crate::parse_collection_params! { @parse [rename_all: "UPPERCASE" $($params)*] $($rest)* }
+    };
+    (@parse [rename_all: screaming_snake $($params:tt)*] $($rest:tt)*) => {
+        $crate::parse_collection_params! { @parse [rename_all: "SCREAMING_SNAKE_CASE" $($params)*] $($rest)* }
+    };
+    (@parse [rename_all: screaming_kebab $($params:tt)*] $($rest:tt)*) => {
+        $crate::parse_collection_params! { @parse [rename_all: "SCREAMING-KEBAB-CASE" $($params)*] $($rest)* }
+    };
+    (@parse [rename_all: none $($params:tt)*] $($rest:tt)*) => {
+        $crate::parse_collection_params! { @parse [rename_all: "none" $($params)*] $($rest)* }
+    };
+    (@parse [rename_all: $case:ident $($params:tt)*] $($rest:tt)*) => {
+        std::compile_error!(std::concat!(
+            "Unknown case `", std::stringify!($case), "`, expected one of ",
+            "`camel`, `snake`, `pascal`, `kebab`, `lower`, `upper`, `screaming_snake`, `screaming_kebab` or `none`"
+        ));
+    };
+    // `rename_all` is the first parameter, thus the attributes only hold the default `rename_all` attribute
     (
-        version = $version:expr;
-        id = none;
+        @parse [rename_all: "none" $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        (items const RENAME_ALL: &'static str = $old:tt; $($items:tt)*)
         $($rest:tt)*
     ) => {
-        $crate::expand_collection!{
-            @final
-            version = $version;
-            id = none;
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            (attrs)
+            (items const RENAME_ALL: &'static str = "none"; $($items)*)
             $($rest)*
         }
     };
     (
-        version = $version:expr;
-        id = $id:ty;
+        @parse [rename_all: $case:tt $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        (items const RENAME_ALL: &'static str = $old:tt; $($items:tt)*)
         $($rest:tt)*
     ) => {
-        $crate::expand_collection!{
-            @add_id
-            version = $version;
-            id = $id;
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            (attrs #[serde(rename_all = $case)])
+            (items const RENAME_ALL: &'static str = $case; $($items)*)
             $($rest)*
         }
     };
-}
-
-/// Expands one collection.
-///
-/// Needed internally, but has no big use on its own.
-/// Thus hidden from documentation.
-#[macro_export]
-#[doc(hidden)]
-macro_rules! expand_collection {
-    // invoked with `_id: none`, thus assume `_id` is added already and finally expand to collection
     (
-        @final
-        version = $schema_version:expr;
-        id = none;
+        @parse [version: $version:literal $(, $($params:tt)*)?]
+        (version $($old:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            (version $version)
+            $($rest)*
+        }
+    };
+    (
+        @parse [_id: $id:ident $(, $($params:tt)*)?]
+        $version:tt
+        (id $($old:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            (id $id)
+            $($rest)*
+        }
+    };
+    (
+        @parse [_id: $id:ident gen = $id_gen:path $(, $($params:tt)*)?]
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [_id: $id, id_gen: $id_gen $(, $($params)*)?]
+            $($rest)*
+        }
+    };
+    (
+        @parse [id_gen: $id_gen:path $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        (id_gen $($old:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            (id_gen $id_gen)
+            $($rest)*
+        }
+    };
+    (
+        @parse [deny_unknown_fields $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        (attrs $($attrs:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            (attrs $($attrs)* #[serde(deny_unknown_fields)])
+            $($rest)*
+        }
+    };
+    (
+        @parse [all_default $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        (attrs $($attrs:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            (attrs $($attrs)* #[derive(std::default::Default)] #[serde(default)])
+            $($rest)*
+        }
+    };
+    (
+        @parse [@ttl $field:ident $seconds:literal, $($params:tt)*]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        (items const RENAME_ALL: &'static str = $case:tt; $($items:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($params)*]
+            $version
+            $id
+            $id_gen
+            $attrs
+            (items
+                const RENAME_ALL: &'static str = $case;
+                $($items)*
+                fn ttl() -> std::option::Option<(&'static str, std::time::Duration)> {
+                    std::option::Option::Some((
+                        $crate::expand_key!($field => $case),
+                        std::time::Duration::from_secs($seconds),
+                    ))
+                }
+            )
+            $($rest)*
+        }
+    };
+    (
+        @parse [indexes: [$([$($index:tt)+])+] $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        (items const RENAME_ALL: &'static str = $case:tt; $($items:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            $attrs
+            (items
+                const RENAME_ALL: &'static str = $case;
+                $($items)*
+                fn indexes() -> std::vec::Vec<$crate::mongodb::IndexModel> {
+                    std::vec![$($crate::expand_index!(@keys [$case] [] $($index)+)),+]
+                }
+            )
+            $($rest)*
+        }
+    };
+    (
+        @parse [default_find: {$($option:ident: $value:expr),*$(,)?} $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        (items $($items:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            $attrs
+            (items
+                $($items)*
+                fn default_find_options() -> std::option::Option<$crate::mongodb::options::FindOptions> {
+                    std::option::Option::Some(
+                        $crate::mongodb::options::FindOptions::builder()
+                            $(.$option($value))*
+                            .build()
+                    )
+                }
+            )
+            $($rest)*
+        }
+    };
+    (
+        @parse [order_by: [$($keys:tt)+] $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        (items const RENAME_ALL: &'static str = $case:tt; $($items:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            $attrs
+            (items
+                const RENAME_ALL: &'static str = $case;
+                $($items)*
+                fn order_by() -> std::option::Option<$crate::mongodb::bson::document::Document> {
+                    std::option::Option::Some($crate::expand_sort!($case [] $($keys)+))
+                }
+            )
+            $($rest)*
+        }
+    };
+    (
+        @parse [order_by: $field:ident $direction:ident $(, $($params:tt)*)?]
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [order_by: [$field $direction] $(, $($params)*)?]
+            $($rest)*
+        }
+    };
+    (
+        @parse [order_by: $field:ident $(, $($params:tt)*)?]
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [order_by: [$field] $(, $($params)*)?]
+            $($rest)*
+        }
+    };
+    (
+        @parse [version_tag: $version_tag:literal $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        (items $($items:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            $attrs
+            (items
+                $($items)*
+                const SCHEMA_VERSION_TAG: &'static str = $version_tag;
+            )
+            $($rest)*
+        }
+    };
+    (
+        @parse [display $(: $display_field:ident)? $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        $items:tt
+        $accessor:tt
+        $sequence:tt
+        $partial:tt
+        $soft_delete:tt
+        $schema:tt
+        $(#[$additional_coll_attr:meta])*
+        $coll_name:ident
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            $attrs
+            $items
+            $accessor
+            $sequence
+            $partial
+            $soft_delete
+            $schema
+            $(#[$additional_coll_attr])*
+            $coll_name
+            $($rest)*
+        }
+        $crate::expand_display! { $id [$($($params)*)?] $coll_name $($display_field)? }
+    };
+    (
+        @parse [order_key: $order_key:ident $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        $items:tt
+        $accessor:tt
+        $sequence:tt
+        $partial:tt
+        $soft_delete:tt
+        $schema:tt
+        $(#[$additional_coll_attr:meta])*
+        $coll_name:ident
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            $attrs
+            $items
+            $accessor
+            $sequence
+            $partial
+            $soft_delete
+            $schema
+            $(#[$additional_coll_attr])*
+            $coll_name
+            $($rest)*
+        }
+        $crate::expand_order_key! { $coll_name $order_key }
+    };
+    (
+        @parse [round_trip $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        $items:tt
+        $accessor:tt
+        $sequence:tt
+        $partial:tt
+        $soft_delete:tt
+        $schema:tt
+        $(#[$additional_coll_attr:meta])*
+        $coll_name:ident
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            $attrs
+            $items
+            $accessor
+            $sequence
+            $partial
+            $soft_delete
+            $schema
+            $(#[$additional_coll_attr])*
+            $coll_name
+            $($rest)*
+        }
+        $crate::expand_round_trip_test! { $coll_name }
+    };
+    (
+        @parse [flatten: $base:ident $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        $items:tt
+        $accessor:tt
+        $sequence:tt
+        $partial:tt
+        $soft_delete:tt
+        $schema:tt
         $(#[$additional_coll_attr:meta])*
         $coll_name:ident {
-            $(
-                $(#[$additional_field_attr:meta])*
-                $field:ident: $field_type:ty
-            ),*$(,)?
+            $($fields:tt)*
         }
-        $(-{
-            $($inner_tokens2:tt)+
-        })?
+        $($rest:tt)*
     ) => {
-        $crate::paste::paste! {
-            #[doc = "Represents the [`" $coll_name "`] collection in mongodb."]
-            #[derive($crate::serde::Deserialize, $crate::serde::Serialize, $crate::typed_builder::TypedBuilder)]
-            #[serde(rename_all = "camelCase")]
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            $attrs
+            $items
+            $accessor
+            $sequence
+            $partial
+            $soft_delete
+            $schema
             $(#[$additional_coll_attr])*
-            pub struct $coll_name {
-                $(
-                    $(#[$additional_field_attr])*
-                    pub $field: $field_type
-                ),*
-            }
-
-            impl $crate::MongoCollection for $coll_name {
-                const NAME: &'static str = $crate::case!($coll_name => Camel);
-                const SCHEMA_VERSION: i32 = $schema_version;
+            $coll_name {
+                #[serde(flatten)]
+                base: $base,
+                $($fields)*
             }
-
-            $(
-                impl $coll_name {
-                    $($inner_tokens2)+
-                }
-            )?
+            $($rest)*
         }
     };
-    // specific type for `_id` given, add it and invoke again with `_id: none` to avoid adding the `_id` field again
     (
-        @add_id
-        version = $schema_version:expr;
-        id = $explicit_id_type:ty;
+        @parse [omit_none $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        $items:tt
+        $accessor:tt
+        $sequence:tt
+        $partial:tt
+        $soft_delete:tt
+        $schema:tt
         $(#[$additional_coll_attr:meta])*
         $coll_name:ident {
-            $(
-                $(#[$additional_field_attr:meta])*
-                $field:ident: $field_type:ty
-            ),*$(,)?
+            $($fields:tt)*
         }
-        $(-{
-            $($inner_tokens2:tt)+
-        })?
+        $($rest:tt)*
     ) => {
-        $crate::expand_collection! {
-            @final
-            version = $schema_version;
-            id = none;
+        $crate::expand_omit_none! {
+            [
+                @parse [$($($params)*)?]
+                $version
+                $id
+                $id_gen
+                $attrs
+                $items
+                $accessor
+                $sequence
+                $partial
+                $soft_delete
+                $schema
+                $(#[$additional_coll_attr])*
+                $coll_name
+            ]
+            [$($rest)*]
+            []
+            []
+            $($fields)*
+        }
+    };
+    (
+        @parse [validated $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        (attrs $($attrs:tt)*)
+        $items:tt
+        $accessor:tt
+        $sequence:tt
+        $partial:tt
+        $soft_delete:tt
+        $schema:tt
+        $(#[$additional_coll_attr:meta])*
+        $coll_name:ident
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            (attrs
+                $($attrs)*
+                #[builder(build_method(name = try_build, into = std::result::Result<$coll_name, $crate::ValidationError>))]
+            )
+            $items
+            $accessor
+            $sequence
+            $partial
+            $soft_delete
+            $schema
+            $(#[$additional_coll_attr])*
+            $coll_name
+            $($rest)*
+        }
+        $crate::expand_validated! { $coll_name }
+    };
+    (
+        @parse [no_accessor $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        $items:tt
+        (accessor $($old:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            $attrs
+            $items
+            (accessor no_accessor)
+            $($rest)*
+        }
+    };
+    (
+        @parse [sequence $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        $items:tt
+        $accessor:tt
+        (sequence $($old:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            $attrs
+            $items
+            $accessor
+            (sequence sequence)
+            $($rest)*
+        }
+    };
+    (
+        @parse [partial $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        $items:tt
+        $accessor:tt
+        $sequence:tt
+        (partial $($old:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            $attrs
+            $items
+            $accessor
+            $sequence
+            (partial partial)
+            $($rest)*
+        }
+    };
+    (
+        @parse [to_schema $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        $items:tt
+        $accessor:tt
+        $sequence:tt
+        $partial:tt
+        $soft_delete:tt
+        (schema $($old:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            $attrs
+            $items
+            $accessor
+            $sequence
+            $partial
+            $soft_delete
+            (schema to_schema)
+            $($rest)*
+        }
+    };
+    (
+        @parse [soft_delete $(, $($params:tt)*)?]
+        $version:tt
+        $id:tt
+        $id_gen:tt
+        $attrs:tt
+        (items $($items:tt)*)
+        $accessor:tt
+        $sequence:tt
+        $partial:tt
+        (soft_delete $($old:tt)*)
+        $schema:tt
+        $(#[$additional_coll_attr:meta])*
+        $coll_name:ident {
+            $($fields:tt)*
+        }
+        $($rest:tt)*
+    ) => {
+        $crate::parse_collection_params! {
+            @parse [$($($params)*)?]
+            $version
+            $id
+            $id_gen
+            $attrs
+            (items
+                $($items)*
+                const SOFT_DELETE: bool = true;
+            )
+            $accessor
+            $sequence
+            $partial
+            (soft_delete soft_delete)
+            $schema
             $(#[$additional_coll_attr])*
             $coll_name {
-                #[serde(skip_serializing_if = "std::option::Option::is_none")]
-                #[serde(rename = "_id")]
+                #[serde(default)]
                 #[builder(default)]
-                _id: std::option::Option<$explicit_id_type>,
-                $(
-                    $(#[$additional_field_attr])*
-                    $field: $field_type
-                ),*
-            }-{
-                #[doc = "Returns a reference to the `_id` field."]
-                #[allow(dead_code)]
-                pub fn id(&self) -> &Option<$explicit_id_type> {
-                    &self._id
-                }
-                $($($inner_tokens2)+)?
+                #[null_as(null)]
+                #[__schema_object]
+                #[serde(rename = "deletedAt")]
+                deleted_at: std::option::Option<$crate::mongodb::bson::DateTime>,
+                $($fields)*
             }
+            $($rest)*
+        }
+    };
+    // all parameters parsed
+    (
+        @parse []
+        (version $($version:literal)?)
+        (id $($id:ident)?)
+        (id_gen $($id_gen:path)?)
+        (attrs $($attrs:tt)*)
+        (items const RENAME_ALL: &'static str = $case:tt; $($items:tt)*)
+        (accessor $($accessor:ident)?)
+        (sequence $($sequence:ident)?)
+        (partial $($partial:ident)?)
+        (soft_delete $($soft_delete:ident)?)
+        (schema $($schema:ident)?)
+        $($rest:tt)*
+    ) => {
+        $crate::expand_collection_version! {
+            version = $($version)?;
+            id = $($id)?;
+            sequence = $($sequence)?;
+            id_gen = $($id_gen)?;
+            accessor = $($accessor)?;
+            items = { const RENAME_ALL: &'static str = $case; $($items)* };
+            schema = $($schema)?;
+            $($attrs)*
+            $($rest)*
+        }
+        $crate::expand_partial! {
+            [$($partial)?] [$($id)?] $case
+            $($rest)*
+        }
+        $crate::expand_soft_delete! {
+            [$($soft_delete)?] [$($id)?]
+            $($rest)*
         }
     };
+    (
+        @parse [$param:ident: $($value:tt)::+ $word:ident $($params:tt)*]
+        $($rest:tt)*
+    ) => {
+        std::compile_error!(std::concat!(
+            "Unexpected `", std::stringify!($word), "` in collection parameter `", std::stringify!($param), "`"
+        ));
+    };
+    (
+        @parse [$param:ident $($params:tt)*]
+        $($rest:tt)*
+    ) => {
+        std::compile_error!(std::concat!("Unknown collection parameter `", std::stringify!($param), "`"));
+    };
 }
 
-/// Expands the main database client.
-///
-/// Needed internally, but has no big use on its own.
-/// Thus hidden from documentation.
+/// Expands schema version that is given in `<` / `>` behind each collection.
 #[macro_export]
 #[doc(hidden)]
-macro_rules! expand_main_client {
+macro_rules! expand_collection_version {
     (
-        $(#[$additional_db_attr:meta])*
-        $db_name:ident {
-            $(
-                $(#[$additional_coll_attr:meta])*
-                $coll_name:ident<_id: none> {
-                    $(
-                        $(#[$additional_field_attr:meta])*
-                        $field:ident: $field_type:ty
-                    ),*$(,)?
-                }
-            ),+
+        version = ;
+        $($rest:tt)*
+    ) => {
+        $crate::expand_collection_id!{
+            version = $crate::DEFAULT_SCHEMA_VERSION;
+            $($rest)*
         }
-        $(-{
-            $($impl:tt)+
-        })?
+    };
+    (
+        version = $version:literal;
+        $($rest:tt)*
     ) => {
-        $crate::paste::paste! {
-            #[doc = "Client to interact with the `" $db_name "` database."]
-            $(#[$additional_db_attr])*
-            pub struct $db_name {
-                pub client: $crate::mongodb::Client,
-                pub database: $crate::mongodb::Database,
-                $(
-                    #[doc = "Handle to the `" $coll_name "` collection"]
-                    pub [<$coll_name:snake:lower _coll>]: $crate::mongodb::Collection<schema::$coll_name>
-                ),+
-            }
-
-            #[$crate::async_trait::async_trait]
-            impl $crate::MongoClient for $db_name {
-                const NAME: &'static str = $crate::case!($db_name => Camel);
+        $crate::expand_collection_id!{
+            version = $version;
+            $($rest)*
+        }
+    };
+}
 
-                async fn new(connection_str: &str) -> $crate::mongodb::error::Result<Self> {
-                    let client = match $crate::mongodb::Client::with_uri_str(connection_str).await {
-                        $crate::mongodb::error::Result::Ok(client) => client,
-                        $crate::mongodb::error::Result::Err(e) => return $crate::mongodb::error::Result::Err(e),
-                    };
-                    <Self as $crate::MongoClient>::new_with_client(client)
-                }
+/// Expands collection _id that is given in `<` / `>` behind each collection.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_collection_id {
+    (
+        version = $version:expr;
+        id = i32;
+        sequence = $(sequence)?;
+        $($rest:tt)*
+    ) => {
+        $crate::expand_collection!{
+            @add_id
+            version = $version;
+            id = i32;
+            $($rest)*
+        }
+    };
+    (
+        version = $version:expr;
+        id = i64;
+        sequence = $(sequence)?;
+        $($rest:tt)*
+    ) => {
+        $crate::expand_collection!{
+            @add_id
+            version = $version;
+            id = i64;
+            $($rest)*
+        }
+    };
+    (
+        version = $version:expr;
+        id = $($id:ident)?;
+        sequence = sequence;
+        $($rest:tt)*
+    ) => {
+        std::compile_error!("`sequence` can only be used together with `_id: i32` or `_id: i64`");
+    };
+    (
+        version = $version:expr;
+        id = $($id:ident)?;
+        sequence = ;
+        $($rest:tt)*
+    ) => {
+        $crate::expand_collection_id!{
+            version = $version;
+            id = $($id)?;
+            $($rest)*
+        }
+    };
+    // the default `ObjectId` can always be generated on the client
+    (
+        version = $version:expr;
+        id = ;
+        id_gen = ;
+        $($rest:tt)*
+    ) => {
+        $crate::expand_collection_id!{
+            version = $version;
+            id = ;
+            id_gen = $crate::DefaultId::new;
+            $($rest)*
+        }
+    };
+    (
+        version = $version:expr;
+        id = ;
+        $($rest:tt)*
+    ) => {
+        $crate::expand_collection!{
+            @add_id
+            version = $version;
+            id = $crate::DefaultId;
+            id_attrs = [
+                #[__schema_object]
+                #[serde(default, deserialize_with = "__object_id::deserialize")]
+            ];
+            $($rest)*
+        }
+    };
+    (
+        version = $version:expr;
+        id = none;
+        id_gen = $id_gen:path;
+        $($rest:tt)*
+    ) => {
+        std::compile_error!("`gen` / `id_gen` cannot be used together with `_id: none`");
+    };
+    (
+        version = $version:expr;
+        id = none;
+        id_gen = ;
+        accessor = no_accessor;
+        $($rest:tt)*
+    ) => {
+        std::compile_error!("`no_accessor` cannot be used together with `_id: none`");
+    };
+    (
+        version = $version:expr;
+        id = none;
+        id_gen = ;
+        accessor = ;
+        $($rest:tt)*
+    ) => {
+        $crate::expand_collection!{
+            @final
+            version = $version;
+            id = none;
+            $($rest)*
+        }
+    };
+    (
+        version = $version:expr;
+        id = $id:ty;
+        $($rest:tt)*
+    ) => {
+        $crate::expand_collection!{
+            @add_id
+            version = $version;
+            id = $id;
+            $($rest)*
+        }
+    };
+}
 
-                fn new_with_client(client: $crate::mongodb::Client) -> $crate::mongodb::error::Result<Self> {
-                    let database = client.database(<Self as $crate::MongoClient>::NAME);
-                    $(
-                        let [<$coll_name:snake:lower _coll>] = database.collection(<schema::$coll_name as $crate::MongoCollection>::NAME);
-                    )+
-                    $crate::mongodb::error::Result::Ok(Self {
-                        client,
-                        database,
-                        $([<$coll_name:snake:lower _coll>]),+
-                    })
+/// Expands the comparison implementations of a collection declared with the `order_key` parameter.
+///
+/// The key is converted to `snake_case`, thus both the field name and the stored name of the field can be given.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_order_key {
+    ($coll_name:ident $order_key:ident) => {
+        $crate::paste_snake_case! {
+            $crate::paste::paste! {
+                impl std::cmp::PartialEq for $coll_name {
+                    fn eq(&self, other: &Self) -> bool {
+                        self.[<$order_key:snake>] == other.[<$order_key:snake>]
+                    }
                 }
 
-                async fn ping(&self) -> $crate::mongodb::error::Result<$crate::mongodb::bson::document::Document> {
-                    self.database.run_command($crate::mongodb::bson::doc!{"ping": 1}, std::option::Option::None).await
+                impl std::cmp::Eq for $coll_name {}
+
+                impl std::cmp::PartialOrd for $coll_name {
+                    fn partial_cmp(&self, other: &Self) -> std::option::Option<std::cmp::Ordering> {
+                        std::option::Option::Some(std::cmp::Ord::cmp(self, other))
+                    }
                 }
 
-                fn database(&self) -> &$crate::mongodb::Database {
-                    &self.database
+                impl std::cmp::Ord for $coll_name {
+                    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                        std::cmp::Ord::cmp(&self.[<$order_key:snake>], &other.[<$order_key:snake>])
+                    }
                 }
-                fn client(&self) -> &$crate::mongodb::Client {
-                    &self.client
+            }
+        }
+    };
+}
+
+/// Expands the [`Display`](std::fmt::Display) implementation of a collection declared with the `display` parameter.
+///
+/// Displaying the `_id` needs one, thus the `_id` slot and the collection parameters that are not parsed yet are searched for `_id: none` first.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_display {
+    ((id none) $params:tt $coll_name:ident $(_id)?) => {
+        std::compile_error!("`display` without a field displays the `_id`, it cannot be used together with `_id: none`");
+    };
+    ($id:tt [_id: none $(, $($params:tt)*)?] $coll_name:ident $(_id)?) => {
+        std::compile_error!("`display` without a field displays the `_id`, it cannot be used together with `_id: none`");
+    };
+    ($id:tt [@ttl $field:ident $seconds:literal, $($params:tt)*] $coll_name:ident $(_id)?) => {
+        $crate::expand_display! { $id [$($params)*] $coll_name }
+    };
+    ($id:tt [$param:ident $(: $($value:tt)::+ $($word:ident $(= $($word_value:tt)::+)?)?)? $(, $($params:tt)*)?] $coll_name:ident $(_id)?) => {
+        $crate::expand_display! { $id [$($($params)*)?] $coll_name }
+    };
+    ($id:tt [] $coll_name:ident $(_id)?) => {
+        impl std::fmt::Display for $coll_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match &self._id {
+                    std::option::Option::Some(id) => std::fmt::Display::fmt(id, f),
+                    std::option::Option::None => f.write_str("None"),
                 }
             }
-            $(
-                impl $db_name {
-                    $($impl)+
+        }
+    };
+    ($id:tt $params:tt $coll_name:ident $display_field:ident) => {
+        impl std::fmt::Display for $coll_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Display::fmt(&self.$display_field, f)
+            }
+        }
+    };
+}
+
+/// Expands the serde round trip test of a collection declared with the `round_trip` parameter.
+///
+/// `#[cfg(test)]` is evaluated in the calling crate, thus the test only exists in the caller's test builds.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_round_trip_test {
+    ($coll_name:ident) => {
+        $crate::paste::paste! {
+            #[cfg(test)]
+            #[test]
+            fn [<$coll_name:snake _round_trip>]() {
+                let expected = <$coll_name as std::default::Default>::default();
+                let document = $crate::mongodb::bson::to_raw_document_buf(&expected)
+                    .expect(std::concat!("Could not serialize default `", std::stringify!($coll_name), "`"))
+                    .to_document()
+                    .expect(std::concat!("Could not read serialized `", std::stringify!($coll_name), "`"));
+                let actual: $coll_name = $crate::mongodb::bson::from_document(document.clone())
+                    .unwrap_or_else(|e| std::panic!(
+                        "Could not deserialize `{}` from {}: {}",
+                        std::stringify!($coll_name),
+                        document,
+                        e
+                    ));
+                std::assert_eq!(actual, expected, "`{}` changed in a round trip through {}", std::stringify!($coll_name), document);
+            }
+        }
+    };
+}
+
+/// Expands the `Partial{CollectionName}` struct and the `find_projected` helper of collections declared with the `partial` parameter.
+///
+/// Fields are munched one by one, only field attributes that also work on [`Option`] are kept.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_partial {
+    ([] [$($id:ident)?] $case:tt $($rest:tt)*) => {};
+    (
+        [partial] [$($id:ident)?] "none"
+        $(#[$additional_coll_attr:meta])*
+        $coll_name:ident {
+            $($fields:tt)*
+        }
+        $($rest:tt)*
+    ) => {
+        $crate::expand_partial! {
+            @id [$($id)?]
+            [$(#[$additional_coll_attr])*] $coll_name
+            $($fields)*
+        }
+    };
+    (
+        [partial] [$($id:ident)?] $case:tt
+        $(#[$additional_coll_attr:meta])*
+        $coll_name:ident {
+            $($fields:tt)*
+        }
+        $($rest:tt)*
+    ) => {
+        $crate::expand_partial! {
+            @id [$($id)?]
+            [#[serde(rename_all = $case)] $(#[$additional_coll_attr])*] $coll_name
+            $($fields)*
+        }
+    };
+    // `_id` is added to the collection later on, thus add it here too
+    (@id [none] $attrs:tt $coll_name:ident $($fields:tt)*) => {
+        $crate::expand_partial! { @fields $attrs $coll_name [] [] $($fields)* }
+    };
+    (@id [] $attrs:tt $coll_name:ident $($fields:tt)*) => {
+        $crate::expand_partial! {
+            @fields $attrs $coll_name
+            [#[serde(rename = "_id", deserialize_with = "__object_id::deserialize")] _id: $crate::DefaultId,]
+            []
+            $($fields)*
+        }
+    };
+    (@id [$id:ty] $attrs:tt $coll_name:ident $($fields:tt)*) => {
+        $crate::expand_partial! {
+            @fields $attrs $coll_name
+            [#[serde(rename = "_id")] _id: $id,]
+            []
+            $($fields)*
+        }
+    };
+    (@fields $attrs:tt $coll_name:ident [$($done:tt)*] [$($cur:tt)*] #[serde(rename = $rename:literal)] $($rest:tt)*) => {
+        $crate::expand_partial! { @fields $attrs $coll_name [$($done)*] [$($cur)* #[serde(rename = $rename)]] $($rest)* }
+    };
+    (@fields $attrs:tt $coll_name:ident [$($done:tt)*] [$($cur:tt)*] #[serde(flatten)] $($rest:tt)*) => {
+        $crate::expand_partial! { @fields $attrs $coll_name [$($done)*] [$($cur)* #[serde(flatten)]] $($rest)* }
+    };
+    (@fields $attrs:tt $coll_name:ident [$($done:tt)*] [$($cur:tt)*] #[binary] $($rest:tt)*) => {
+        $crate::expand_partial! { @fields $attrs $coll_name [$($done)*] [$($cur)* #[serde(with = "__binary")]] $($rest)* }
+    };
+    (@fields $attrs:tt $coll_name:ident [$($done:tt)*] [$($cur:tt)*] #[date_as_iso] $($rest:tt)*) => {
+        $crate::expand_partial! { @fields $attrs $coll_name [$($done)*] [$($cur)* #[serde(with = "__iso_date")]] $($rest)* }
+    };
+    (@fields $attrs:tt $coll_name:ident [$($done:tt)*] [$($cur:tt)*] #[as_string] $($rest:tt)*) => {
+        $crate::expand_partial! { @fields $attrs $coll_name [$($done)*] [$($cur)* #[serde(with = "__as_string")]] $($rest)* }
+    };
+    (@fields $attrs:tt $coll_name:ident [$($done:tt)*] [$($cur:tt)*] #[as_i64] $($rest:tt)*) => {
+        $crate::expand_partial! { @fields $attrs $coll_name [$($done)*] [$($cur)* #[serde(with = "__as_i64")]] $($rest)* }
+    };
+    (@fields $attrs:tt $coll_name:ident $done:tt $cur:tt #[$($other:tt)*] $($rest:tt)*) => {
+        $crate::expand_partial! { @fields $attrs $coll_name $done $cur $($rest)* }
+    };
+    (@fields $attrs:tt $coll_name:ident [$($done:tt)*] [$($cur:tt)*] $field:ident: $field_type:ty $(, $($rest:tt)*)?) => {
+        $crate::expand_partial! {
+            @fields $attrs $coll_name
+            [$($done)* $($cur)* $field: $field_type,]
+            []
+            $($($rest)*)?
+        }
+    };
+    (
+        @fields [$($attr:tt)*] $coll_name:ident
+        [$($(#[$field_attr:meta])* $field:ident: $field_type:ty,)*]
+        []
+    ) => {
+        $crate::paste::paste! {
+            #[doc = "All fields of the [`" $coll_name "`] collection as [`Option`], deserialized from projections."]
+            #[doc = ""]
+            #[doc = "Read it with `" $coll_name "Repository::find_projected`."]
+            #[derive($crate::serde::Deserialize)]
+            $($attr)*
+            pub struct [<Partial $coll_name>] {
+                $(
+                    $(#[$field_attr])*
+                    #[serde(default)]
+                    pub $field: std::option::Option<$field_type>,
+                )*
+            }
+
+            // inherent impls may live in any module of the crate, the repository is declared next to the database handler
+            impl super::[<$coll_name Repository>] {
+                #[doc = "Finds all documents in the `" $coll_name "` collection matching `filter`, only reading the given `fields`."]
+                #[doc = ""]
+                #[doc = "`fields` are the serialized field names, eg. `firstName`, the server always includes `_id`."]
+                #[allow(dead_code)]
+                pub async fn find_projected(
+                    &self,
+                    filter: $crate::mongodb::bson::document::Document,
+                    fields: &[&str],
+                ) -> $crate::mongodb::error::Result<std::vec::Vec<[<Partial $coll_name>]>> {
+                    let projection = fields
+                        .iter()
+                        .map(|field| (std::string::String::from(*field), $crate::mongodb::bson::Bson::Int32(1)))
+                        .collect::<$crate::mongodb::bson::document::Document>();
+                    let options = $crate::mongodb::options::FindOptions::builder()
+                        .projection(projection)
+                        .build();
+                    let mut cursor = self
+                        .database
+                        .collection::<$crate::mongodb::bson::document::Document>(<$coll_name as $crate::MongoCollection>::NAME)
+                        .find(filter, options)
+                        .await?;
+                    let mut documents = std::vec::Vec::new();
+                    while cursor.advance().await? {
+                        documents.push($crate::deserialize_document(
+                            <$coll_name as $crate::MongoCollection>::NAME,
+                            cursor.deserialize_current()?,
+                        )?);
+                    }
+                    $crate::mongodb::error::Result::Ok(documents)
                 }
-            )?
+            }
         }
     };
 }
 
-/// Model a mongodb database.
+/// Marks all fields of type [`Option`] with `#[null_as(omit)]` for the `omit_none` collection parameter, then continues parsing the parameters.
 ///
-/// This macro creates structs / functions / constants / modules that represent a mongoDB database.
-/// Being a macro (which is expanded at compile time) there is no run time performance penalty when using this macro.
+/// Fields are munched one by one: `[parameter parser state] [tokens after the fields] [finished fields] [attributes of current field] fields`.
+/// Fields that already have a `null_as` or `skip_serializing_if` attribute are kept unchanged.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_omit_none {
+    // explicit choices are kept, the field is finished right away
+    ([$($state:tt)*] $rest:tt [$($done:tt)*] [$($cur:tt)*] #[null_as $($args:tt)*] $($fields:tt)*) => {
+        $crate::expand_omit_none! { @keep [$($state)*] $rest [$($done)*] [$($cur)* #[null_as $($args)*]] $($fields)* }
+    };
+    ([$($state:tt)*] $rest:tt [$($done:tt)*] [$($cur:tt)*] #[serde(skip_serializing_if = $skip:literal)] $($fields:tt)*) => {
+        $crate::expand_omit_none! { @keep [$($state)*] $rest [$($done)*] [$($cur)* #[serde(skip_serializing_if = $skip)]] $($fields)* }
+    };
+    ([$($state:tt)*] $rest:tt [$($done:tt)*] [$($cur:tt)*] #[$($attr:tt)*] $($fields:tt)*) => {
+        $crate::expand_omit_none! { [$($state)*] $rest [$($done)*] [$($cur)* #[$($attr)*]] $($fields)* }
+    };
+    (@keep $state:tt $rest:tt [$($done:tt)*] [$($cur:tt)*] #[$($attr:tt)*] $($fields:tt)*) => {
+        $crate::expand_omit_none! { @keep $state $rest [$($done)*] [$($cur)* #[$($attr)*]] $($fields)* }
+    };
+    (@keep $state:tt $rest:tt [$($done:tt)*] [$($cur:tt)*] $field:ident: $field_type:ty $(, $($fields:tt)*)?) => {
+        $crate::expand_omit_none! { $state $rest [$($done)* $($cur)* $field: $field_type,] [] $($($fields)*)? }
+    };
+    // supported spellings of `Option`
+    ($state:tt $rest:tt $done:tt $cur:tt $field:ident: Option<$inner:ty> $(, $($fields:tt)*)?) => {
+        $crate::expand_omit_none! { @omit $state $rest $done $cur $field $inner $(, $($fields)*)? }
+    };
+    ($state:tt $rest:tt $done:tt $cur:tt $field:ident: std::option::Option<$inner:ty> $(, $($fields:tt)*)?) => {
+        $crate::expand_omit_none! { @omit $state $rest $done $cur $field $inner $(, $($fields)*)? }
+    };
+    ($state:tt $rest:tt $done:tt $cur:tt $field:ident: ::std::option::Option<$inner:ty> $(, $($fields:tt)*)?) => {
+        $crate::expand_omit_none! { @omit $state $rest $done $cur $field $inner $(, $($fields)*)? }
+    };
+    ($state:tt $rest:tt $done:tt $cur:tt $field:ident: core::option::Option<$inner:ty> $(, $($fields:tt)*)?) => {
+        $crate::expand_omit_none! { @omit $state $rest $done $cur $field $inner $(, $($fields)*)? }
+    };
+    ($state:tt $rest:tt $done:tt $cur:tt $field:ident: ::core::option::Option<$inner:ty> $(, $($fields:tt)*)?) => {
+        $crate::expand_omit_none! { @omit $state $rest $done $cur $field $inner $(, $($fields)*)? }
+    };
+    (@omit $state:tt $rest:tt [$($done:tt)*] [$($cur:tt)*] $field:ident $inner:ty $(, $($fields:tt)*)?) => {
+        $crate::expand_omit_none! {
+            $state $rest
+            [$($done)* $($cur)* #[null_as(omit)] $field: std::option::Option<$inner>,]
+            []
+            $($($fields)*)?
+        }
+    };
+    ($state:tt $rest:tt [$($done:tt)*] [$($cur:tt)*] $field:ident: $field_type:ty $(, $($fields:tt)*)?) => {
+        $crate::expand_omit_none! { $state $rest [$($done)* $($cur)* $field: $field_type,] [] $($($fields)*)? }
+    };
+    // all fields munched
+    ([$($state:tt)*] [$($rest:tt)*] [$($done:tt)*] []) => {
+        $crate::parse_collection_params! {
+            $($state)* {
+                $($done)*
+            }
+            $($rest)*
+        }
+    };
+}
+
+/// Expands the sort document of the `order_by` collection parameter.
 ///
-/// For a detailled syntax demonstration see [Examples](#examples).
+/// Each key is a field name optionally followed by `asc` / `desc`, keys are converted to `camelCase` except for `_id`.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_sort {
+    ($case:tt [$($keys:tt)*] $field:ident desc $(, $($rest:tt)*)?) => {
+        $crate::expand_sort!($case [$($keys)* ($field, -1)] $($($rest)*)?)
+    };
+    ($case:tt [$($keys:tt)*] $field:ident asc $(, $($rest:tt)*)?) => {
+        $crate::expand_sort!($case [$($keys)* ($field, 1)] $($($rest)*)?)
+    };
+    ($case:tt [$($keys:tt)*] $field:ident $(, $($rest:tt)*)?) => {
+        $crate::expand_sort!($case [$($keys)* ($field, 1)] $($($rest)*)?)
+    };
+    (@key $case:tt _id) => {
+        "_id"
+    };
+    (@key $case:tt $field:ident) => {
+        $crate::expand_key!($field => $case)
+    };
+    ($case:tt [$(($field:ident, $order:expr))+]) => {{
+        let mut sort = $crate::mongodb::bson::document::Document::new();
+        $(
+            sort.insert($crate::expand_sort!(@key $case $field), $order);
+        )+
+        sort
+    }};
+}
+
+/// Expands the conversion used by `try_build` of a collection declared with the `validated` parameter.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_validated {
+    ($coll_name:ident) => {
+        impl std::convert::From<$coll_name>
+            for std::result::Result<$coll_name, $crate::ValidationError>
+        {
+            fn from(collection: $coll_name) -> Self {
+                $crate::MongoCollection::validate(&collection)?;
+                std::result::Result::Ok(collection)
+            }
+        }
+    };
+}
+
+/// Expands the soft delete helpers of a collection declared with the `soft_delete` parameter.
+///
+/// The `deleted_at` field and [`MongoCollection::SOFT_DELETE`] are added while parsing the parameters already.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_soft_delete {
+    ([] [$($id:ident)?] $($rest:tt)*) => {};
+    ([soft_delete] [none] $($rest:tt)*) => {
+        std::compile_error!("`soft_delete` needs an `_id` to address documents, it cannot be used together with `_id: none`");
+    };
+    (
+        [soft_delete] [$($id:ident)?]
+        $(#[$additional_coll_attr:meta])*
+        $coll_name:ident
+        $($rest:tt)*
+    ) => {
+        $crate::expand_soft_delete! { @repository $coll_name }
+    };
+    (@repository $coll_name:ident) => {
+        $crate::paste::paste! {
+            impl super::[<$coll_name Repository>] {
+                #[doc = "Finds all `" $coll_name "` documents matching `filter` including soft deleted ones, unlike `find`."]
+                #[allow(dead_code)]
+                pub async fn find_with_deleted(
+                    &self,
+                    filter: impl std::convert::Into<std::option::Option<$crate::mongodb::bson::document::Document>>,
+                    options: impl std::convert::Into<std::option::Option<$crate::mongodb::options::FindOptions>>,
+                ) -> $crate::mongodb::error::Result<$crate::mongodb::Cursor<$coll_name>> {
+                    let options = options
+                        .into()
+                        .or_else(<$coll_name as $crate::MongoCollection>::default_find_options);
+                    self.collection.find(filter, options).await
+                }
+
+                #[doc = "Counts the `" $coll_name "` documents matching `filter` including soft deleted ones, unlike `count_with`."]
+                #[allow(dead_code)]
+                pub async fn count_with_deleted(
+                    &self,
+                    filter: $crate::mongodb::bson::document::Document,
+                    options: $crate::mongodb::options::CountOptions,
+                ) -> $crate::mongodb::error::Result<u64> {
+                    self.collection.count_documents(filter, options).await
+                }
+            }
+        }
+    };
+}
+
+/// Expands the writing soft delete helpers of the repository, scanning the collection parameters for `soft_delete`.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_soft_delete_writes {
+    ($args:tt [_id: none $(, $($params:tt)*)?] $coll_name:ident) => {};
+    ([$handle:ident $audit:tt] [soft_delete $(, $($params:tt)*)?] $coll_name:ident) => {
+        $crate::paste::paste! {
+            $crate::expand_collection_handle! {
+                @write $handle;
+                #[doc = "Marks the `" $coll_name "` document with the given `_id` as deleted by setting `deletedAt` to now."]
+                #[doc = ""]
+                #[doc = "The document is kept, undo it with `restore_by_id`."]
+                #[allow(dead_code)]
+                pub async fn soft_delete_by_id(
+                    &self,
+                    id: &<schema::$coll_name as $crate::MongoCollectionId>::Id,
+                ) -> $crate::mongodb::error::Result<$crate::mongodb::results::UpdateResult> {
+                    let id = $crate::mongodb::bson::to_bson(id)?;
+                    let result = self
+                        .collection
+                        .update_one(
+                            $crate::mongodb::bson::doc! { "_id": id.clone() },
+                            $crate::mongodb::bson::doc! { "$set": { "deletedAt": $crate::mongodb::bson::DateTime::now() } },
+                            std::option::Option::None,
+                        )
+                        .await?;
+                    $crate::expand_audit! {
+                        $audit self.database, "soft_delete", <schema::$coll_name as $crate::MongoCollection>::NAME, id
+                    }
+                    $crate::mongodb::error::Result::Ok(result)
+                }
+
+                #[doc = "Restores the soft deleted `" $coll_name "` document with the given `_id` by setting `deletedAt` to `null`."]
+                #[allow(dead_code)]
+                pub async fn restore_by_id(
+                    &self,
+                    id: &<schema::$coll_name as $crate::MongoCollectionId>::Id,
+                ) -> $crate::mongodb::error::Result<$crate::mongodb::results::UpdateResult> {
+                    let id = $crate::mongodb::bson::to_bson(id)?;
+                    let result = self
+                        .collection
+                        .update_one(
+                            $crate::mongodb::bson::doc! { "_id": id.clone() },
+                            $crate::mongodb::bson::doc! { "$set": { "deletedAt": $crate::mongodb::bson::Bson::Null } },
+                            std::option::Option::None,
+                        )
+                        .await?;
+                    $crate::expand_audit! {
+                        $audit self.database, "restore", <schema::$coll_name as $crate::MongoCollection>::NAME, id
+                    }
+                    $crate::mongodb::error::Result::Ok(result)
+                }
+            }
+        }
+    };
+    ($args:tt [$param:ident $(: $($value:tt)::+ $($word:ident $(= $($word_value:tt)::+)?)?)? $(, $($params:tt)*)?] $coll_name:ident) => {
+        $crate::expand_soft_delete_writes! { $args [$($($params)*)?] $coll_name }
+    };
+    ($args:tt [] $coll_name:ident) => {};
+}
+
+/// Expands `bump_schema_version` of the repository if the collection has a field named `schema_version`.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_schema_version_writes {
+    ([$handle:ident $audit:tt] $coll_name:ident [schema_version $($fields:ident)*]) => {
+        $crate::paste::paste! {
+            $crate::expand_collection_handle! {
+                @write $handle;
+                #[doc = "Sets the stored `schemaVersion` of all `" $coll_name "` documents with version `from` to the collection's current `MongoCollection::SCHEMA_VERSION`."]
+                #[doc = ""]
+                #[doc = "Run it after transforming the documents of a migration, it does not change any other field."]
+                #[allow(dead_code)]
+                pub async fn bump_schema_version(
+                    &self,
+                    from: i32,
+                ) -> $crate::mongodb::error::Result<$crate::mongodb::results::UpdateResult> {
+                    let key = $crate::expand_index!(@key $coll_name schema_version);
+                    let filter = $crate::mongodb::bson::doc! { key: from };
+                    let result = self
+                        .collection
+                        .update_many(
+                            filter.clone(),
+                            $crate::mongodb::bson::doc! {
+                                "$set": { key: <schema::$coll_name as $crate::MongoCollection>::SCHEMA_VERSION }
+                            },
+                            std::option::Option::None,
+                        )
+                        .await?;
+                    $crate::expand_audit! {
+                        $audit self.database, "bump_schema_version", <schema::$coll_name as $crate::MongoCollection>::NAME, filter
+                    }
+                    $crate::mongodb::error::Result::Ok(result)
+                }
+            }
+        }
+    };
+    ($args:tt $coll_name:ident [$field:ident $($fields:ident)*]) => {
+        $crate::expand_schema_version_writes! { $args $coll_name [$($fields)*] }
+    };
+    ($args:tt $coll_name:ident []) => {};
+}
+
+/// Expands the atomic `next_id` of the repository of collections declared with the `sequence` parameter.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_sequence_id {
+    ([$handle:ident] [sequence $(, $($params:tt)*)?] $coll_name:ident) => {
+        $crate::paste::paste! {
+            $crate::expand_collection_handle! {
+                @write $handle;
+                #[doc = "Atomically increments the counter of the `" $coll_name "` collection in `_counters` and returns its new value."]
+                #[doc = ""]
+                #[doc = "The counter document is created on first use, thus the first id is `1`."]
+                #[allow(dead_code)]
+                pub async fn next_id(
+                    &self,
+                ) -> $crate::mongodb::error::Result<<schema::$coll_name as $crate::MongoCollectionId>::Id> {
+                    let options = $crate::mongodb::options::FindOneAndUpdateOptions::builder()
+                        .upsert(true)
+                        .return_document($crate::mongodb::options::ReturnDocument::After)
+                        .build();
+                    let counter = self
+                        .database
+                        .collection::<$crate::mongodb::bson::document::Document>($crate::COUNTERS_COLLECTION)
+                        .find_one_and_update(
+                            $crate::mongodb::bson::doc! { "_id": <schema::$coll_name as $crate::MongoCollection>::NAME },
+                            $crate::mongodb::bson::doc! { "$inc": { "seq": 1_i64 } },
+                            options,
+                        )
+                        .await?;
+                    let next = match counter.as_ref().map(|counter| counter.get("seq")) {
+                        std::option::Option::Some(std::option::Option::Some($crate::mongodb::bson::Bson::Int32(seq))) => std::option::Option::Some(i64::from(*seq)),
+                        std::option::Option::Some(std::option::Option::Some($crate::mongodb::bson::Bson::Int64(seq))) => std::option::Option::Some(*seq),
+                        _ => {
+                            return $crate::mongodb::error::Result::Err($crate::mongodb::error::Error::custom(std::format!(
+                                "counter of `{}` is not an integer: {:?}",
+                                <schema::$coll_name as $crate::MongoCollection>::NAME,
+                                counter
+                            )))
+                        }
+                    };
+                    match next.and_then(|next| std::convert::TryFrom::try_from(next).ok()) {
+                        std::option::Option::Some(next) => $crate::mongodb::error::Result::Ok(next),
+                        std::option::Option::None => $crate::mongodb::error::Result::Err($crate::mongodb::error::Error::custom(std::format!(
+                            "counter of `{}` overflows `{}`",
+                            <schema::$coll_name as $crate::MongoCollection>::NAME,
+                            std::any::type_name::<<schema::$coll_name as $crate::MongoCollectionId>::Id>()
+                        ))),
+                    }
+                }
+            }
+        }
+    };
+    ($args:tt [$param:ident $(: $($value:tt)::+ $($word:ident $(= $($word_value:tt)::+)?)?)? $(, $($params:tt)*)?] $coll_name:ident) => {
+        $crate::expand_sequence_id! { $args [$($($params)*)?] $coll_name }
+    };
+    ($args:tt [] $coll_name:ident) => {};
+}
+
+/// Expands `assign_id` of the repository of collections with an `_id`.
+///
+/// Scans the collection parameters for the `_id` type, its generator and `sequence`: `(id type) (gen function) (sequence)`.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_assign_id {
+    ($args:tt [_id: none $(, $($params:tt)*)?] $id:tt $gen:tt $sequence:tt $coll_name:ident) => {};
+    ($args:tt [_id: $id:ident gen = $gen:path $(, $($params:tt)*)?] $old_id:tt $old_gen:tt $sequence:tt $coll_name:ident) => {
+        $crate::expand_assign_id! { $args [$($($params)*)?] (id $id) (gen $gen) $sequence $coll_name }
+    };
+    ($args:tt [_id: $id:ident $(, $($params:tt)*)?] $old_id:tt $gen:tt $sequence:tt $coll_name:ident) => {
+        $crate::expand_assign_id! { $args [$($($params)*)?] (id $id) $gen $sequence $coll_name }
+    };
+    ($args:tt [id_gen: $gen:path $(, $($params:tt)*)?] $id:tt $old_gen:tt $sequence:tt $coll_name:ident) => {
+        $crate::expand_assign_id! { $args [$($($params)*)?] $id (gen $gen) $sequence $coll_name }
+    };
+    ($args:tt [sequence $(, $($params:tt)*)?] $id:tt $gen:tt $old_sequence:tt $coll_name:ident) => {
+        $crate::expand_assign_id! { $args [$($($params)*)?] $id $gen (sequence) $coll_name }
+    };
+    ($args:tt [$param:ident $(: $($value:tt)::+ $($word:ident $(= $($word_value:tt)::+)?)?)? $(, $($params:tt)*)?] $id:tt $gen:tt $sequence:tt $coll_name:ident) => {
+        $crate::expand_assign_id! { $args [$($($params)*)?] $id $gen $sequence $coll_name }
+    };
+    // ids from the counter document
+    ([$handle:ident] [] $id:tt $gen:tt (sequence) $coll_name:ident) => {
+        $crate::expand_assign_id! {
+            @write $handle $coll_name
+            "Sets the `_id` of `document` to the next value of the counter if it is `None`, see `next_id`.",
+            (self, document) {
+                if document._id.is_none() {
+                    document._id = std::option::Option::Some(self.next_id().await?);
+                }
+                $crate::mongodb::error::Result::Ok(())
+            }
+        }
+    };
+    // ids generated on the client, the default `ObjectId` is generated too
+    ([$handle:ident] [] (id $($id:ident)?) (gen $($gen:path)?) () $coll_name:ident) => {
+        $crate::expand_assign_id! {
+            @assign $handle $coll_name (id $($id)?) (gen $($gen)?)
+        }
+    };
+    (@assign $handle:ident $coll_name:ident (id $id:ident) (gen)) => {
+        $crate::expand_assign_id! {
+            @write $handle $coll_name
+            "Fails if `document` has no `_id`, since the collection is declared without a generator.",
+            (self, document) {
+                if document._id.is_none() {
+                    return $crate::mongodb::error::Result::Err($crate::mongodb::error::Error::custom(std::format!(
+                        "cannot insert into `{}` without `_id`, declare the collection with `_id: {} gen = {{function}}` to generate it",
+                        <schema::$coll_name as $crate::MongoCollection>::NAME,
+                        std::stringify!($id)
+                    )));
+                }
+                $crate::mongodb::error::Result::Ok(())
+            }
+        }
+    };
+    (@assign $handle:ident $coll_name:ident $id:tt $gen:tt) => {
+        $crate::expand_assign_id! {
+            @write $handle $coll_name
+            "Generates the `_id` of `document` if it is `None`, see `ensure_id`.",
+            (self, document) {
+                document.ensure_id();
+                $crate::mongodb::error::Result::Ok(())
+            }
+        }
+    };
+    (@write $handle:ident $coll_name:ident $doc:literal, ($self:ident, $document:ident) $body:block) => {
+        $crate::paste::paste! {
+            $crate::expand_collection_handle! {
+                @write $handle;
+                #[doc = "Assigns an `_id` to `document` before it is inserted into the `" $coll_name "` collection, keeping an existing one."]
+                #[doc = ""]
+                #[doc = $doc]
+                #[allow(dead_code)]
+                pub async fn assign_id(
+                    &$self,
+                    $document: &mut schema::$coll_name,
+                ) -> $crate::mongodb::error::Result<()> $body
+            }
+        }
+    };
+}
+
+/// Expands a struct with the given fields, consuming this crate's field attributes.
+///
+/// Fields are munched one by one, the state is kept in brackets in front of the remaining fields:
+/// `(context) [struct header] [finished fields] [attributes of current field] [markers of current field] [(marker field type) of finished fields]`.
+/// The context is either `(collection Name [to_schema if declared] { items of MongoCollection } [(field (check args)) of finished fields] [(check args) of current field])` or `(base)`.
+/// Validator attributes, eg. `#[max_len(10)]`, are collected as checks of the collection's `MongoCollection::validate`.
+/// Markers are field attributes that generate code besides the struct, eg. `#[alternate_key]`, a collection field named `schema_version` is marked too.
+/// Each field of a collection is marked as `field` after its other markers.
+/// All other attributes are passed through unchanged.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_fields {
+    // index models of a marked field
+    (@index $models:ident $case:tt alternate_key $field:ident) => {
+        $models.push(
+            $crate::mongodb::IndexModel::builder()
+                .keys($crate::mongodb::bson::doc! { $crate::expand_key!($field => $case): 1 })
+                .options($crate::mongodb::options::IndexOptions::builder().unique(true).build())
+                .build()
+        );
+    };
+    // `#[unique]` is an alternate key by another name
+    (@index $models:ident $case:tt unique $field:ident) => {
+        $crate::expand_fields!(@index $models $case alternate_key $field)
+    };
+    (@index $models:ident $case:tt schema_version $field:ident) => {};
+    (@index $models:ident $case:tt sensitive $field:ident) => {};
+    (@index $models:ident $case:tt field $field:ident) => {};
+    (@index $models:ident $case:tt lowercase $field:ident) => {};
+    (@index $models:ident $case:tt trim $field:ident) => {};
+    // repository helpers of a marked field
+    (@repository $case:tt $coll_name:ident alternate_key $field:ident $field_type:ty) => {
+        $crate::paste::paste! {
+            // inherent impls may live in any module of the crate, the repository is declared next to the database handler
+            impl super::[<$coll_name Repository>] {
+                #[doc = "Finds the `" $coll_name "` document by its alternate key `" $field "`."]
+                #[allow(dead_code)]
+                pub async fn [<find_by_ $field>](
+                    &self,
+                    $field: &$field_type,
+                ) -> $crate::mongodb::error::Result<std::option::Option<$coll_name>> {
+                    let filter = $crate::mongodb::bson::doc! {
+                        $crate::expand_key!($field => $case): $crate::mongodb::bson::to_bson($field)?
+                    };
+                    self.database
+                        .collection::<$crate::mongodb::bson::document::Document>(<$coll_name as $crate::MongoCollection>::NAME)
+                        .find_one(filter, std::option::Option::None)
+                        .await?
+                        .map(|document| $crate::deserialize_document(<$coll_name as $crate::MongoCollection>::NAME, document))
+                        .transpose()
+                }
+            }
+        }
+    };
+    (@repository $case:tt $coll_name:ident unique $field:ident $field_type:ty) => {
+        $crate::expand_fields! { @repository $case $coll_name alternate_key $field $field_type }
+    };
+    (@repository $case:tt $coll_name:ident sensitive $field:ident $field_type:ty) => {};
+    (@repository $case:tt $coll_name:ident field $field:ident $field_type:ty) => {};
+    (@repository $case:tt $coll_name:ident lowercase $field:ident $field_type:ty) => {};
+    (@repository $case:tt $coll_name:ident trim $field:ident $field_type:ty) => {};
+    // normalizations of a marked field, `self` is passed in like for `@debug_field`
+    (@normalize $this:ident lowercase $field:ident) => {
+        $crate::normalize::lowercase(&mut $this.$field)
+    };
+    (@normalize $this:ident trim $field:ident) => {
+        $crate::normalize::trim(&mut $this.$field)
+    };
+    (@normalize $this:ident $marker:ident $field:ident) => {};
+    (@normalize_document $document:ident lowercase $field:ident) => {
+        $crate::normalize::document_field($document, Self::field_key(std::stringify!($field)), $crate::normalize::lowercase::<std::string::String>)
+    };
+    (@normalize_document $document:ident trim $field:ident) => {
+        $crate::normalize::document_field($document, Self::field_key(std::stringify!($field)), $crate::normalize::trim::<std::string::String>)
+    };
+    (@normalize_document $document:ident $marker:ident $field:ident) => {};
+    // associated types of the `{CollectionName}FieldTypes` trait, one per `field` marker
+    (@field_type field $field:ident) => {
+        type $field;
+    };
+    (@field_type $marker:ident $field:ident) => {};
+    (@field_type field $field:ident $field_type:ty) => {
+        type $field = $field_type;
+    };
+    (@field_type $marker:ident $field:ident $field_type:ty) => {};
+    // `Debug` implementation that redacts sensitive fields
+    // collects `(plain field)` / `(redacted field)` from the marked fields, `sensitive` is pending until the field's `field` marker
+    (@debug $coll_name:ident $entries:tt $found:tt [] (sensitive $field:ident $field_type:ty) $($rest:tt)*) => {
+        $crate::expand_fields! { @debug $coll_name $entries [found] [redacted] $($rest)* }
+    };
+    (@debug $coll_name:ident [$($entries:tt)*] $found:tt [] (field $field:ident $field_type:ty) $($rest:tt)*) => {
+        $crate::expand_fields! { @debug $coll_name [$($entries)* (plain $field)] $found [] $($rest)* }
+    };
+    (@debug $coll_name:ident [$($entries:tt)*] $found:tt [redacted] (field $field:ident $field_type:ty) $($rest:tt)*) => {
+        $crate::expand_fields! { @debug $coll_name [$($entries)* (redacted $field)] $found [] $($rest)* }
+    };
+    (@debug $coll_name:ident $entries:tt $found:tt $pending:tt ($marker:ident $field:ident $field_type:ty) $($rest:tt)*) => {
+        $crate::expand_fields! { @debug $coll_name $entries $found $pending $($rest)* }
+    };
+    (@debug $coll_name:ident $entries:tt [] []) => {};
+    (@debug $coll_name:ident [$(($kind:ident $field:ident))*] [found] []) => {
+        impl std::fmt::Debug for $coll_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let mut debug = f.debug_struct(std::stringify!($coll_name));
+                $($crate::expand_fields!(@debug_field debug self $kind $field);)*
+                debug.finish()
+            }
+        }
+    };
+    // `self` is passed in, it would not resolve to the method's receiver otherwise
+    (@debug_field $debug:ident $this:ident plain $field:ident) => {
+        $debug.field(std::stringify!($field), &$this.$field)
+    };
+    (@debug_field $debug:ident $this:ident redacted $field:ident) => {
+        $debug.field(std::stringify!($field), &"<redacted>")
+    };
+    // `bump_schema_version` writes, thus it is expanded next to the database handler, see `expand_schema_version_writes`
+    (@repository $case:tt $coll_name:ident schema_version $field:ident $field_type:ty) => {};
+    (
+        $context:tt $header:tt $fields:tt [$($field_attrs:tt)*] $markers:tt $marked:tt
+        #[null_as(omit)]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context $header $fields
+            [$($field_attrs)* #[serde(skip_serializing_if = "std::option::Option::is_none")]]
+            $markers $marked
+            $($rest)*
+        }
+    };
+    (
+        $context:tt $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[null_as(null)]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context $header $fields $field_attrs $markers $marked
+            $($rest)*
+        }
+    };
+    (
+        $context:tt $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[null_as($($other:tt)*)]
+        $($rest:tt)*
+    ) => {
+        std::compile_error!(std::concat!(
+            "Unknown `null_as` value `", std::stringify!($($other)*), "`, expected `omit` or `null`"
+        ));
+    };
+    (
+        $context:tt $header:tt $fields:tt [$($field_attrs:tt)*] $markers:tt $marked:tt
+        #[computed]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context $header $fields
+            [$($field_attrs)* #[serde(skip_serializing, default)] #[builder(default)]]
+            $markers $marked
+            $($rest)*
+        }
+    };
+    (
+        $context:tt $header:tt $fields:tt [$($field_attrs:tt)*] $markers:tt $marked:tt
+        #[binary]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context $header $fields
+            [$($field_attrs)* #[serde(with = "__binary")]]
+            $markers $marked
+            $($rest)*
+        }
+    };
+    (
+        $context:tt $header:tt $fields:tt [$($field_attrs:tt)*] $markers:tt $marked:tt
+        #[date_as_iso]
+        $($rest:tt)*
+    ) => {
+        // `with` takes a path in a string literal which cannot contain `$crate`, see `__iso_date` in `mongo_db`
+        $crate::expand_fields! {
+            $context $header $fields
+            [$($field_attrs)* #[serde(with = "__iso_date")]]
+            $markers $marked
+            $($rest)*
+        }
+    };
+    (
+        $context:tt $header:tt $fields:tt [$($field_attrs:tt)*] $markers:tt $marked:tt
+        #[as_string]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context $header $fields
+            [$($field_attrs)* #[serde(with = "__as_string")]]
+            $markers $marked
+            $($rest)*
+        }
+    };
+    (
+        $context:tt $header:tt $fields:tt [$($field_attrs:tt)*] $markers:tt $marked:tt
+        #[as_i64]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context $header $fields
+            [$($field_attrs)* #[serde(with = "__as_i64")]]
+            $markers $marked
+            $($rest)*
+        }
+    };
+    (
+        (collection $coll_name:ident $($context:tt)*) $header:tt $fields:tt [$($field_attrs:tt)*] $markers:tt $marked:tt
+        #[versioned_field(since = $since:literal)]
+        $($rest:tt)*
+    ) => {
+        const _: () = std::assert!(
+            $since <= <$coll_name as $crate::MongoCollection>::SCHEMA_VERSION,
+            std::concat!("`versioned_field` of `", std::stringify!($coll_name), "` is newer than the collection's schema version")
+        );
+
+        $crate::expand_fields! {
+            (collection $coll_name $($context)*) $header $fields
+            [$($field_attrs)* #[serde(default)]]
+            $markers $marked
+            $($rest)*
+        }
+    };
+    (
+        (base) $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[versioned_field $($args:tt)*]
+        $($rest:tt)*
+    ) => {
+        std::compile_error!(
+            "`versioned_field` can only be used in collections, base structs and views do not have a schema version"
+        );
+    };
+    (
+        $context:tt $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[versioned_field $($args:tt)*]
+        $($rest:tt)*
+    ) => {
+        std::compile_error!(std::concat!(
+            "Unknown `versioned_field` arguments `", std::stringify!($($args)*), "`, expected `(since = {version})`"
+        ));
+    };
+    // fields added by this crate whose types have no OpenAPI schema, documented as objects with `to_schema`
+    (
+        (collection $coll_name:ident [to_schema] $($context:tt)*) $header:tt $fields:tt [$($field_attrs:tt)*] $markers:tt $marked:tt
+        #[__schema_object]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            (collection $coll_name [to_schema] $($context)*) $header $fields
+            [$($field_attrs)* #[schema(value_type = Option<Object>)]]
+            $markers $marked
+            $($rest)*
+        }
+    };
+    (
+        $context:tt $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[__schema_object]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context $header $fields $field_attrs $markers $marked
+            $($rest)*
+        }
+    };
+    // validator attributes are checked by `MongoCollection::validate`
+    (
+        (collection $coll_name:ident $schema:tt $items:tt $validations:tt [$($pending:tt)*]) $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[min_len($min:expr)]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            (collection $coll_name $schema $items $validations [$($pending)* (min_len $min)]) $header $fields $field_attrs $markers $marked
+            $($rest)*
+        }
+    };
+    (
+        (collection $coll_name:ident $schema:tt $items:tt $validations:tt [$($pending:tt)*]) $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[max_len($max:expr)]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            (collection $coll_name $schema $items $validations [$($pending)* (max_len $max)]) $header $fields $field_attrs $markers $marked
+            $($rest)*
+        }
+    };
+    (
+        (collection $coll_name:ident $schema:tt $items:tt $validations:tt [$($pending:tt)*]) $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[min($min:expr)]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            (collection $coll_name $schema $items $validations [$($pending)* (min $min)]) $header $fields $field_attrs $markers $marked
+            $($rest)*
+        }
+    };
+    (
+        (collection $coll_name:ident $schema:tt $items:tt $validations:tt [$($pending:tt)*]) $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[max($max:expr)]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            (collection $coll_name $schema $items $validations [$($pending)* (max $max)]) $header $fields $field_attrs $markers $marked
+            $($rest)*
+        }
+    };
+    // `#[array(...)]` is munched one argument at a time
+    (
+        (collection $coll_name:ident $schema:tt $items:tt $validations:tt [$($pending:tt)*]) $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[array(max_items = $max:expr $(, $($args:tt)*)?)]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            (collection $coll_name $schema $items $validations [$($pending)* (max_items $max)]) $header $fields $field_attrs $markers $marked
+            #[array($($($args)*)?)]
+            $($rest)*
+        }
+    };
+    (
+        (collection $coll_name:ident $schema:tt $items:tt $validations:tt [$($pending:tt)*]) $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[array(unique_items $(, $($args:tt)*)?)]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            (collection $coll_name $schema $items $validations [$($pending)* (unique_items)]) $header $fields $field_attrs $markers $marked
+            #[array($($($args)*)?)]
+            $($rest)*
+        }
+    };
+    (
+        (collection $($context:tt)*) $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[array()]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            (collection $($context)*) $header $fields $field_attrs $markers $marked
+            $($rest)*
+        }
+    };
+    (
+        (collection $($context:tt)*) $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[array($arg:ident $($args:tt)*)]
+        $($rest:tt)*
+    ) => {
+        std::compile_error!(std::concat!(
+            "Unknown argument `", std::stringify!($arg), "` of `array`, expected `max_items = n` or `unique_items`"
+        ));
+    };
+    (
+        (base) $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[array $($args:tt)*]
+        $($rest:tt)*
+    ) => {
+        std::compile_error!("`array` can only be used in collections, base structs and views are not validated");
+    };
+    (
+        (base) $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[min_len $($args:tt)*]
+        $($rest:tt)*
+    ) => {
+        std::compile_error!("`min_len` can only be used in collections, base structs and views are not validated");
+    };
+    (
+        (base) $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[max_len $($args:tt)*]
+        $($rest:tt)*
+    ) => {
+        std::compile_error!("`max_len` can only be used in collections, base structs and views are not validated");
+    };
+    (
+        (base) $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[min $($args:tt)*]
+        $($rest:tt)*
+    ) => {
+        std::compile_error!("`min` can only be used in collections, base structs and views are not validated");
+    };
+    (
+        (base) $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[max $($args:tt)*]
+        $($rest:tt)*
+    ) => {
+        std::compile_error!("`max` can only be used in collections, base structs and views are not validated");
+    };
+    (
+        $context:tt $header:tt $fields:tt $field_attrs:tt [$($markers:ident)*] $marked:tt
+        #[sensitive]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context $header $fields $field_attrs [$($markers)* sensitive] $marked
+            $($rest)*
+        }
+    };
+    (
+        $context:tt $header:tt $fields:tt $field_attrs:tt [$($markers:ident)*] $marked:tt
+        #[alternate_key]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context $header $fields $field_attrs [$($markers)* alternate_key] $marked
+            $($rest)*
+        }
+    };
+    (
+        $context:tt $header:tt $fields:tt $field_attrs:tt [$($markers:ident)*] $marked:tt
+        #[unique]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context $header $fields $field_attrs [$($markers)* unique] $marked
+            $($rest)*
+        }
+    };
+    // `#[normalize(...)]` marks the field once per normalization
+    (
+        $context:tt $header:tt $fields:tt $field_attrs:tt [$($markers:ident)*] $marked:tt
+        #[normalize(lowercase $(, $($args:tt)*)?)]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context $header $fields $field_attrs [$($markers)* lowercase] $marked
+            #[normalize($($($args)*)?)]
+            $($rest)*
+        }
+    };
+    (
+        $context:tt $header:tt $fields:tt $field_attrs:tt [$($markers:ident)*] $marked:tt
+        #[normalize(trim $(, $($args:tt)*)?)]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context $header $fields $field_attrs [$($markers)* trim] $marked
+            #[normalize($($($args)*)?)]
+            $($rest)*
+        }
+    };
+    (
+        $context:tt $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[normalize()]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context $header $fields $field_attrs $markers $marked
+            $($rest)*
+        }
+    };
+    (
+        $context:tt $header:tt $fields:tt $field_attrs:tt $markers:tt $marked:tt
+        #[normalize($normalization:ident $($args:tt)*)]
+        $($rest:tt)*
+    ) => {
+        std::compile_error!(std::concat!(
+            "Unknown normalization `", std::stringify!($normalization), "`, expected `lowercase` or `trim`"
+        ));
+    };
+    (
+        $context:tt $header:tt $fields:tt [$($field_attrs:tt)*] $markers:tt $marked:tt
+        #[$($attr:tt)*]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context $header $fields [$($field_attrs)* #[$($attr)*]] $markers $marked
+            $($rest)*
+        }
+    };
+    // a stored schema version is tracked by collections, but not by base structs
+    (
+        (collection $coll_name:ident $schema:tt $items:tt [$($validations:tt)*] [$($pending:tt)*])
+        $header:tt [$($fields:tt)*] [$($field_attrs:tt)*] [$($markers:ident)*] [$($marked:tt)*]
+        schema_version: $field_type:ty,
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            (collection $coll_name $schema $items [$($validations)* $((schema_version $pending))*] [])
+            $header
+            [$($fields)* $($field_attrs)* pub schema_version: $field_type,]
+            []
+            []
+            [$($marked)* $(($markers schema_version $field_type))* (schema_version schema_version $field_type) (field schema_version $field_type)]
+            $($rest)*
+        }
+    };
+    // fields of collections are listed as `field` markers too, eg. for the `Debug` implementation of sensitive fields
+    (
+        (collection $coll_name:ident $schema:tt $items:tt [$($validations:tt)*] [$($pending:tt)*])
+        $header:tt [$($fields:tt)*] [$($field_attrs:tt)*] [$($markers:ident)*] [$($marked:tt)*]
+        $field:ident: $field_type:ty,
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            (collection $coll_name $schema $items [$($validations)* $(($field $pending))*] [])
+            $header
+            [$($fields)* $($field_attrs)* pub $field: $field_type,]
+            []
+            []
+            [$($marked)* $(($markers $field $field_type))* (field $field $field_type)]
+            $($rest)*
+        }
+    };
+    (
+        $context:tt $header:tt [$($fields:tt)*] [$($field_attrs:tt)*] [$($markers:ident)*] [$($marked:tt)*]
+        $field:ident: $field_type:ty,
+        $($rest:tt)*
+    ) => {
+        $crate::expand_fields! {
+            $context
+            $header
+            [$($fields)* $($field_attrs)* pub $field: $field_type,]
+            []
+            []
+            [$($marked)* $(($markers $field $field_type))*]
+            $($rest)*
+        }
+    };
+    // all fields munched
+    (
+        (collection $coll_name:ident [$($schema:ident)?] { const RENAME_ALL: &'static str = $case:tt; $($items:tt)* } [$(($v_field:ident ($v_check:ident $($v_arg:tt)*)))*] [])
+        [$($header:tt)*] [$($fields:tt)*] [] []
+        [$(($marker:ident $marked_field:ident $marked_type:ty))*]
+    ) => {
+        $crate::expand_default_derives! {
+            [$($schema)?] [Debug] [$($marker)*]
+            $($header)* {
+                $($fields)*
+            }
+        }
+
+        impl $crate::MongoCollection for $coll_name {
+            const RENAME_ALL: &'static str = $case;
+            $($items)*
+
+            fn validate(&self) -> std::result::Result<(), $crate::ValidationError> {
+                $(
+                    $crate::validation::$v_check(&self.$v_field, $($v_arg)*).map_err(|message| {
+                        $crate::ValidationError::new(<Self as $crate::MongoCollection>::NAME, std::stringify!($v_field), message)
+                    })?;
+                )*
+                std::result::Result::Ok(())
+            }
+
+            fn normalize(&mut self) {
+                $($crate::expand_fields!(@normalize self $marker $marked_field);)*
+            }
+
+            fn normalize_document(document: &mut $crate::mongodb::bson::document::Document) {
+                $($crate::expand_fields!(@normalize_document document $marker $marked_field);)*
+            }
+
+            fn index_models() -> std::vec::Vec<$crate::mongodb::IndexModel> {
+                #[allow(unused_mut)]
+                let mut models = <Self as $crate::MongoCollection>::indexes();
+                $($crate::expand_fields!(@index models $case $marker $marked_field);)*
+                if let std::option::Option::Some((key, expire_after)) = <Self as $crate::MongoCollection>::ttl() {
+                    models.push(
+                        $crate::mongodb::IndexModel::builder()
+                            .keys($crate::mongodb::bson::doc! { key: 1 })
+                            .options($crate::mongodb::options::IndexOptions::builder().expire_after(expire_after).build())
+                            .build()
+                    );
+                }
+                models
+            }
+        }
+
+        $crate::paste::paste! {
+            #[doc = "Types of the fields of [`" $coll_name "`], used by the finders of unique indexes."]
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            pub trait [<$coll_name FieldTypes>] {
+                $($crate::expand_fields!(@field_type $marker $marked_field);)*
+            }
+
+            impl [<$coll_name FieldTypes>] for $coll_name {
+                $($crate::expand_fields!(@field_type $marker $marked_field $marked_type);)*
+            }
+        }
+
+        $(
+            $crate::expand_fields! { @repository $case $coll_name $marker $marked_field $marked_type }
+        )*
+
+        $crate::expand_fields! { @debug $coll_name [] [] [] $(($marker $marked_field $marked_type))* }
+    };
+    (
+        (base)
+        [$($header:tt)*] [$($fields:tt)*] [] [] []
+    ) => {
+        $($header)* {
+            $($fields)*
+        }
+    };
+    (
+        (base)
+        $header:tt $fields:tt [] [] [$(($marker:ident $marked_field:ident $marked_type:ty))+]
+    ) => {
+        std::compile_error!(std::concat!(
+            "`", $(std::stringify!($marker), "` of `", std::stringify!($marked_field), "`",)+
+            " cannot be used in base structs, declare the field in each collection instead"
+        ));
+    };
+}
+
+/// Expands to the key a field is stored as, given the collection's case, the field and its attributes: `expand_field_key!("camelCase" field [#[attr] ...])`.
+///
+/// Expands to an `Option<&'static str>` expression, which is `None` for flattened and skipped fields.
+/// The arguments of each `#[serde(...)]` attribute are munched one by one, the key of a `rename` is kept in brackets until all attributes are read.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_field_key {
+    ($case:tt $field:ident [$($attrs:tt)*]) => {
+        $crate::expand_field_key!(@attrs $case $field [] $($attrs)*)
+    };
+    (@attrs $case:tt $field:ident $key:tt #[serde($($args:tt)*)] $($rest:tt)*) => {
+        $crate::expand_field_key!(@args $case $field $key [$($rest)*] $($args)*)
+    };
+    (@attrs $case:tt $field:ident $key:tt #[$($attr:tt)*] $($rest:tt)*) => {
+        $crate::expand_field_key!(@attrs $case $field $key $($rest)*)
+    };
+    (@attrs $case:tt $field:ident [$key:literal]) => {
+        std::option::Option::Some($key)
+    };
+    (@attrs $case:tt $field:ident []) => {
+        std::option::Option::Some($crate::expand_key!($field => $case))
+    };
+    // fields that are not stored as a top-level key
+    (@args $case:tt $field:ident $key:tt $rest:tt flatten $(, $($args:tt)*)?) => {
+        std::option::Option::None
+    };
+    (@args $case:tt $field:ident $key:tt $rest:tt skip $(, $($args:tt)*)?) => {
+        std::option::Option::None
+    };
+    (@args $case:tt $field:ident $key:tt $rest:tt skip_serializing $(, $($args:tt)*)?) => {
+        std::option::Option::None
+    };
+    // the key is the serialized name
+    (@args $case:tt $field:ident $old:tt $rest:tt rename = $key:literal $(, $($args:tt)*)?) => {
+        $crate::expand_field_key!(@args $case $field [$key] $rest $($($args)*)?)
+    };
+    (@args $case:tt $field:ident $old:tt $rest:tt rename(serialize = $key:literal $(, deserialize = $de_key:literal)?) $(, $($args:tt)*)?) => {
+        $crate::expand_field_key!(@args $case $field [$key] $rest $($($args)*)?)
+    };
+    (@args $case:tt $field:ident $old:tt $rest:tt rename(deserialize = $de_key:literal, serialize = $key:literal) $(, $($args:tt)*)?) => {
+        $crate::expand_field_key!(@args $case $field [$key] $rest $($($args)*)?)
+    };
+    (@args $case:tt $field:ident $key:tt $rest:tt $arg:ident $(= $value:literal)? $(($($inner:tt)*))? $(, $($args:tt)*)?) => {
+        $crate::expand_field_key!(@args $case $field $key $rest $($($args)*)?)
+    };
+    (@args $case:tt $field:ident $key:tt [$($rest:tt)*]) => {
+        $crate::expand_field_key!(@attrs $case $field $key $($rest)*)
+    };
+}
+
+/// Expands to the key of a field in a collection with the given case.
+///
+/// `"none"` keeps the field name, just like serde's `snake_case` does for fields.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_key {
+    ($field:ident => "none") => {
+        $crate::serde_case!($field => "snake_case")
+    };
+    ($field:ident => $case:tt) => {
+        $crate::serde_case!($field => $case)
+    };
+}
+
+/// Expands one collection.
+///
+/// Needed internally, but has no big use on its own.
+/// Thus hidden from documentation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_collection {
+    // invoked with `_id: none`, thus assume `_id` is added already and finally expand to collection
+    (
+        @final
+        version = $schema_version:expr;
+        id = none;
+        items = { const RENAME_ALL: &'static str = $case:tt; $($items:tt)* };
+        schema = $($schema:ident)?;
+        $(#[$additional_coll_attr:meta])*
+        $coll_name:ident {
+            $(
+                $(#[$($additional_field_attr:tt)*])*
+                $field:ident: $field_type:ty
+            ),*$(,)?
+        }
+        $(-{
+            $($inner_tokens2:tt)+
+        })?
+    ) => {
+        $crate::paste::paste! {
+            $crate::expand_fields! {
+                (collection $coll_name [$($schema)?] {
+                    const RENAME_ALL: &'static str = $case;
+                    const NAME: &'static str = $crate::case!($coll_name => Camel);
+                    const SCHEMA_VERSION: i32 = $schema_version;
+                    $($items)*
+                } [] [])
+                [
+                    #[doc = "Represents the [`" $coll_name "`] collection in mongodb."]
+                    #[derive($crate::serde::Deserialize, $crate::serde::Serialize, $crate::typed_builder::TypedBuilder)]
+                    $(#[$additional_coll_attr])*
+                    pub struct $coll_name
+                ]
+                []
+                []
+                []
+                []
+                $(
+                    $(#[$($additional_field_attr)*])*
+                    $field: $field_type,
+                )*
+            }
+
+            impl std::convert::TryFrom<$crate::mongodb::bson::document::Document> for $coll_name {
+                type Error = $crate::mongodb::bson::de::Error;
+
+                fn try_from(document: $crate::mongodb::bson::document::Document) -> std::result::Result<Self, Self::Error> {
+                    $crate::mongodb::bson::from_document(document)
+                }
+            }
+
+            impl $coll_name {
+                #[doc = "Returns the key the field `field` of `" $coll_name "` is stored as, eg. `\"firstName\"` for `first_name`."]
+                #[doc = ""]
+                #[doc = "Returns `None` for unknown fields and for fields that are not stored as a top-level key, ie. flattened or skipped fields."]
+                #[allow(dead_code)]
+                pub fn field_key(field: &str) -> std::option::Option<&'static str> {
+                    match field {
+                        $(
+                            std::stringify!($field) => $crate::expand_field_key!($case $field [$(#[$($additional_field_attr)*])*]),
+                        )*
+                        _ => std::option::Option::None,
+                    }
+                }
+
+                #[doc = "Returns the fields of `" $coll_name "` with the keys they are stored as, in the order of declaration, see `field_key`."]
+                #[doc = ""]
+                #[doc = "Fields that are not stored as a top-level key, ie. flattened or skipped fields, are left out."]
+                #[allow(dead_code)]
+                pub fn field_mapping() -> std::vec::Vec<(&'static str, &'static str)> {
+                    #[allow(unused_mut)]
+                    let mut mapping = std::vec::Vec::new();
+                    $(
+                        if let std::option::Option::Some(key) = $crate::expand_field_key!($case $field [$(#[$($additional_field_attr)*])*]) {
+                            mapping.push((std::stringify!($field), key));
+                        }
+                    )*
+                    mapping
+                }
+
+                #[doc = "Builds a `" $coll_name "` from a row of columns, eg. read from a SQL table during a migration."]
+                #[doc = ""]
+                #[doc = "Columns named like a field, eg. `first_name`, are stored under the field's key, see `field_key`."]
+                #[doc = "All other columns, eg. `_id` or keys of flattened fields, are kept as they are."]
+                #[allow(dead_code)]
+                pub fn from_map(
+                    map: std::collections::HashMap<std::string::String, $crate::mongodb::bson::Bson>,
+                ) -> std::result::Result<Self, $crate::mongodb::bson::de::Error> {
+                    let document: $crate::mongodb::bson::document::Document = map
+                        .into_iter()
+                        .map(|(column, value)| match Self::field_key(&column) {
+                            std::option::Option::Some(key) => (std::string::String::from(key), value),
+                            std::option::Option::None => (column, value),
+                        })
+                        .collect();
+                    $crate::mongodb::bson::from_document(document)
+                }
+
+                #[doc = "Deserializes only the field `field` of the raw `" $coll_name "` document `raw`, see `mongodb_ext::read_raw_field`."]
+                #[doc = ""]
+                #[doc = "Useful for large documents, eg. `RawDocumentBuf`s from a `Collection::clone_with_type`, of which only a few fields are needed."]
+                #[doc = "Returns `None` if `raw` does not contain the field, fails if `" $coll_name "` has no such field."]
+                #[allow(dead_code)]
+                pub fn read_field<T>(
+                    raw: &$crate::mongodb::bson::RawDocument,
+                    field: &str,
+                ) -> $crate::mongodb::error::Result<std::option::Option<T>>
+                where
+                    T: $crate::serde::de::DeserializeOwned,
+                {
+                    match Self::field_key(field) {
+                        std::option::Option::Some(key) => $crate::read_raw_field(<Self as $crate::MongoCollection>::NAME, raw, key),
+                        std::option::Option::None => std::result::Result::Err(
+                            <$crate::mongodb::bson::de::Error as $crate::serde::de::Error>::custom(std::format!(
+                                "`{}` has no field `{}` that is stored as a top-level key",
+                                <Self as $crate::MongoCollection>::NAME,
+                                field
+                            ))
+                            .into()
+                        ),
+                    }
+                }
+            }
+
+            $crate::expand_testing! {
+                impl $coll_name {
+                    #[doc = "Serializes `self` and compares it to `document`."]
+                    #[doc = ""]
+                    #[doc = "If `self` does not serialize an `_id` (eg. because it is `None`), the `_id` of `document` is ignored."]
+                    #[doc = "Returns `false` if `self` cannot be serialized."]
+                    #[allow(dead_code)]
+                    pub fn matches_document(&self, document: &$crate::mongodb::bson::document::Document) -> bool {
+                        let expected = match $crate::mongodb::bson::to_raw_document_buf(self)
+                            .ok()
+                            .and_then(|raw| raw.to_document().ok())
+                        {
+                            std::option::Option::Some(expected) => expected,
+                            std::option::Option::None => return false,
+                        };
+                        if expected.contains_key("_id") {
+                            expected == *document
+                        } else {
+                            let mut actual = document.clone();
+                            actual.remove("_id");
+                            expected == actual
+                        }
+                    }
+
+                    #[doc = "Reports the fields of `document` that are missing in or unknown to this collection, see `mongodb_ext::testing::schema_drift`."]
+                    #[allow(dead_code)]
+                    pub fn schema_drift(document: &$crate::mongodb::bson::document::Document) -> $crate::testing::SchemaDrift {
+                        $crate::testing::schema_drift::<Self>(document)
+                    }
+                }
+            }
+
+            $crate::expand_json_schema! {
+                impl $coll_name {
+                    #[doc = "Returns a JSON Schema of the fields of this collection, see `mongodb_ext::json_schema`."]
+                    #[allow(dead_code)]
+                    pub fn json_schema() -> $crate::serde_json::Value {
+                        $crate::json_schema::object_schema(&[
+                            $(
+                                (
+                                    match std::stringify!($field) {
+                                        "_id" => "_id",
+                                        _ => $crate::expand_key!($field => $case),
+                                    },
+                                    std::stringify!($field_type),
+                                ),
+                            )*
+                        ])
+                    }
+                }
+            }
+
+            $(
+                impl $coll_name {
+                    $($inner_tokens2)+
+                }
+            )?
+        }
+    };
+    // specific type for `_id` given, add it and invoke again with `_id: none` to avoid adding the `_id` field again
+    (
+        @add_id
+        version = $schema_version:expr;
+        id = $explicit_id_type:ty;
+        $(id_attrs = [$($id_attrs:tt)*];)?
+        id_gen = $($id_gen:path)?;
+        accessor = $($accessor:ident)?;
+        items = { $($items:tt)* };
+        schema = $($schema:ident)?;
+        $(#[$additional_coll_attr:meta])*
+        $coll_name:ident {
+            $(
+                $(#[$($additional_field_attr:tt)*])*
+                $field:ident: $field_type:ty
+            ),*$(,)?
+        }
+        $(-{
+            $($inner_tokens2:tt)+
+        })?
+    ) => {
+        $crate::expand_collection! {
+            @final
+            version = $schema_version;
+            id = none;
+            items = { $($items)* };
+            schema = $($schema)?;
+            $(#[$additional_coll_attr])*
+            $coll_name {
+                #[serde(skip_serializing_if = "std::option::Option::is_none")]
+                #[serde(rename = "_id")]
+                #[builder(default)]
+                $($($id_attrs)*)?
+                _id: std::option::Option<$explicit_id_type>,
+                $(
+                    $(#[$($additional_field_attr)*])*
+                    $field: $field_type
+                ),*
+            }-{
+                $crate::expand_collection! { @accessor [$($accessor)?] $explicit_id_type }
+                $(
+                    #[doc = "Sets the `_id` field to a newly generated id if it is `None`, returns a reference to the (new) `_id`."]
+                    #[allow(dead_code)]
+                    pub fn ensure_id(&mut self) -> &$explicit_id_type {
+                        self._id.get_or_insert_with($id_gen)
+                    }
+                )?
+                $($($inner_tokens2)+)?
+            }
+        }
+
+        impl $crate::MongoCollectionId for $coll_name {
+            type Id = $explicit_id_type;
+        }
+
+        $crate::paste::paste! {
+            // inherent impls may live in any module of the crate, the repository is declared next to the database handler
+            impl super::[<$coll_name Repository>] {
+                #[doc = "Finds the `" $coll_name "` document with the given `_id`."]
+                #[doc = ""]
+                #[doc = "Excludes soft deleted documents if the collection is declared with `soft_delete`, see `exclude_soft_deleted`."]
+                #[allow(dead_code)]
+                pub async fn find_by_id(
+                    &self,
+                    id: &$explicit_id_type,
+                ) -> $crate::mongodb::error::Result<std::option::Option<$coll_name>> {
+                    let filter = $crate::mongodb::bson::doc! { "_id": $crate::mongodb::bson::to_bson(id)? };
+                    self.database
+                        .collection::<$crate::mongodb::bson::document::Document>(<$coll_name as $crate::MongoCollection>::NAME)
+                        .find_one($crate::exclude_soft_deleted::<$coll_name>(std::option::Option::Some(filter)), std::option::Option::None)
+                        .await?
+                        .map(|document| $crate::deserialize_document(<$coll_name as $crate::MongoCollection>::NAME, document))
+                        .transpose()
+                }
+            }
+        }
+    };
+    // `id()` accessor of the `_id` field, omitted with `no_accessor`
+    (@accessor [] $explicit_id_type:ty) => {
+        #[doc = "Returns a reference to the `_id` field."]
+        #[allow(dead_code)]
+        pub fn id(&self) -> &Option<$explicit_id_type> {
+            &self._id
+        }
+    };
+    (@accessor [no_accessor] $explicit_id_type:ty) => {};
+}
+
+/// Expands the finder of one index declaration on the collection's repository if it is a unique index without partial filter.
+///
+/// Keys are collected first, the finder takes one argument per key: `expand_unique_finder!(Collection [] key1 desc, key2 unique)`.
+/// `expand_unique_finder!(@params Collection [params])` expands the finders of the `indexes` collection parameter.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_unique_finder {
+    // indexes of the `indexes` collection parameter
+    (@params $coll:ident [indexes: [$([$($index:tt)+])+] $(, $($params:tt)*)?]) => {
+        $($crate::expand_unique_finder! { $coll [] $($index)+ })+
+        $crate::expand_unique_finder! { @params $coll [$($($params)*)?] }
+    };
+    (@params $coll:ident [$param:ident $(: $($value:tt)::+ $($word:ident $(= $($word_value:tt)::+)?)?)? $(, $($params:tt)*)?]) => {
+        $crate::expand_unique_finder! { @params $coll [$($($params)*)?] }
+    };
+    (@params $coll:ident []) => {};
+    // parse keys, the direction does not matter for lookups
+    ($coll:ident [$($keys:ident)*] $field:ident $(asc)? $(desc)?, $($rest:tt)+) => {
+        $crate::expand_unique_finder! { $coll [$($keys)* $field] $($rest)+ }
+    };
+    ($coll:ident [$($keys:ident)*] $field:ident asc $($rest:tt)*) => {
+        $crate::expand_unique_finder! { @options $coll [$($keys)* $field] [] $($rest)* }
+    };
+    ($coll:ident [$($keys:ident)*] $field:ident desc $($rest:tt)*) => {
+        $crate::expand_unique_finder! { @options $coll [$($keys)* $field] [] $($rest)* }
+    };
+    ($coll:ident [$($keys:ident)*] $field:ident $($rest:tt)*) => {
+        $crate::expand_unique_finder! { @options $coll [$($keys)* $field] [] $($rest)* }
+    };
+    // parse options, documents outside of a partial filter are not unique
+    (@options $coll:ident $keys:tt $unique:tt unique $($rest:tt)*) => {
+        $crate::expand_unique_finder! { @options $coll $keys [unique] $($rest)* }
+    };
+    (@options $coll:ident $keys:tt $unique:tt partial { $($filter:tt)+ } $($rest:tt)*) => {};
+    (@options $coll:ident $keys:tt $unique:tt ttl $seconds:literal $($rest:tt)*) => {
+        $crate::expand_unique_finder! { @options $coll $keys $unique $($rest)* }
+    };
+    (@options $coll:ident $keys:tt $unique:tt name $name:literal $($rest:tt)*) => {
+        $crate::expand_unique_finder! { @options $coll $keys $unique $($rest)* }
+    };
+    (@options $coll:ident $keys:tt $unique:tt hidden $($rest:tt)*) => {
+        $crate::expand_unique_finder! { @options $coll $keys $unique $($rest)* }
+    };
+    (@options $coll:ident $keys:tt $unique:tt background $($rest:tt)*) => {
+        $crate::expand_unique_finder! { @options $coll $keys $unique $($rest)* }
+    };
+    (@options $coll:ident $keys:tt []) => {};
+    (@options $coll:ident [$first:ident $($keys:ident)*] [unique]) => {
+        $crate::paste::paste! {
+            impl [<$coll Repository>] {
+                #[doc = "Finds the `" $coll "` document by the unique index on `" $first $(", " $keys)* "`."]
+                #[doc = ""]
+                #[doc = "Excludes soft deleted documents if the collection is declared with `soft_delete`, see `exclude_soft_deleted`."]
+                #[allow(dead_code)]
+                pub async fn [<find_by_ $first $(_and_ $keys)*>](
+                    &self,
+                    $first: &<schema::$coll as schema::[<$coll FieldTypes>]>::$first,
+                    $($keys: &<schema::$coll as schema::[<$coll FieldTypes>]>::$keys,)*
+                ) -> $crate::mongodb::error::Result<std::option::Option<schema::$coll>> {
+                    let mut filter = $crate::mongodb::bson::document::Document::new();
+                    filter.insert($crate::expand_index!(@key $coll $first), $crate::mongodb::bson::to_bson($first)?);
+                    $(
+                        filter.insert($crate::expand_index!(@key $coll $keys), $crate::mongodb::bson::to_bson($keys)?);
+                    )*
+                    self.database
+                        .collection::<$crate::mongodb::bson::document::Document>(<schema::$coll as $crate::MongoCollection>::NAME)
+                        .find_one($crate::exclude_soft_deleted::<schema::$coll>(std::option::Option::Some(filter)), std::option::Option::None)
+                        .await?
+                        .map(|document| $crate::deserialize_document(<schema::$coll as $crate::MongoCollection>::NAME, document))
+                        .transpose()
+                }
+            }
+        }
+    };
+}
+
+/// Expands one index declaration into a [`mongodb::IndexModel`].
+///
+/// Needed internally, but has no big use on its own.
+/// Thus hidden from documentation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_index {
+    // keys are converted to the case of the collection, which is only known as `MongoCollection::RENAME_ALL`
+    // keys of the `indexes` collection parameter are converted at compile time, the case is known there
+    (@key [$case:tt] $field:ident) => {
+        $crate::expand_key!($field => $case)
+    };
+    (@key $coll:ident $field:ident) => {
+        match <schema::$coll as $crate::MongoCollection>::RENAME_ALL {
+            "snake_case" | "none" => $crate::serde_case!($field => "snake_case"),
+            "PascalCase" => $crate::serde_case!($field => "PascalCase"),
+            "kebab-case" => $crate::serde_case!($field => "kebab-case"),
+            "lowercase" => $crate::serde_case!($field => "lowercase"),
+            "UPPERCASE" => $crate::serde_case!($field => "UPPERCASE"),
+            "SCREAMING_SNAKE_CASE" => $crate::serde_case!($field => "SCREAMING_SNAKE_CASE"),
+            "SCREAMING-KEBAB-CASE" => $crate::serde_case!($field => "SCREAMING-KEBAB-CASE"),
+            _ => $crate::serde_case!($field => "camelCase"),
+        }
+    };
+    // parse keys, each key is a field name optionally followed by `asc` / `desc`
+    (
+        @keys $coll:tt [$($keys:tt)*]
+        $field:ident desc, $($rest:tt)+
+    ) => {
+        $crate::expand_index! {
+            @keys $coll [$($keys)* ($crate::expand_index!(@key $coll $field), -1)]
+            $($rest)+
+        }
+    };
+    (
+        @keys $coll:tt [$($keys:tt)*]
+        $field:ident asc, $($rest:tt)+
+    ) => {
+        $crate::expand_index! {
+            @keys $coll [$($keys)* ($crate::expand_index!(@key $coll $field), 1)]
+            $($rest)+
+        }
+    };
+    (
+        @keys $coll:tt [$($keys:tt)*]
+        $field:ident, $($rest:tt)+
+    ) => {
+        $crate::expand_index! {
+            @keys $coll [$($keys)* ($crate::expand_index!(@key $coll $field), 1)]
+            $($rest)+
+        }
+    };
+    (
+        @keys $coll:tt [$($keys:tt)*]
+        $field:ident desc $($rest:tt)*
+    ) => {
+        $crate::expand_index! {
+            @options $coll [$($keys)* ($crate::expand_index!(@key $coll $field), -1)] []
+            $($rest)*
+        }
+    };
+    (
+        @keys $coll:tt [$($keys:tt)*]
+        $field:ident asc $($rest:tt)*
+    ) => {
+        $crate::expand_index! {
+            @options $coll [$($keys)* ($crate::expand_index!(@key $coll $field), 1)] []
+            $($rest)*
+        }
+    };
+    (
+        @keys $coll:tt [$($keys:tt)*]
+        $field:ident $($rest:tt)*
+    ) => {
+        $crate::expand_index! {
+            @options $coll [$($keys)* ($crate::expand_index!(@key $coll $field), 1)] []
+            $($rest)*
+        }
+    };
+    // parse options that follow the keys
+    (
+        @options $coll:tt [$($keys:tt)*] [$($options:tt)*]
+        unique $($rest:tt)*
+    ) => {
+        $crate::expand_index! {
+            @options $coll [$($keys)*] [$($options)* .unique(true)]
+            $($rest)*
+        }
+    };
+    (
+        @options $coll:tt [$($keys:tt)*] [$($options:tt)*]
+        ttl $seconds:literal $($rest:tt)*
+    ) => {
+        $crate::expand_index! {
+            @options $coll [$($keys)*] [$($options)* .expire_after(std::time::Duration::from_secs($seconds))]
+            $($rest)*
+        }
+    };
+    (
+        @options $coll:tt [$($keys:tt)*] [$($options:tt)*]
+        name $name:literal $($rest:tt)*
+    ) => {
+        $crate::expand_index! {
+            @options $coll [$($keys)*] [$($options)* .name(std::string::String::from($name))]
+            $($rest)*
+        }
+    };
+    (
+        @options $coll:tt [$($keys:tt)*] [$($options:tt)*]
+        hidden $($rest:tt)*
+    ) => {
+        $crate::expand_index! {
+            @options $coll [$($keys)*] [$($options)* .hidden(true)]
+            $($rest)*
+        }
+    };
+    (
+        @options $coll:tt [$($keys:tt)*] [$($options:tt)*]
+        background $($rest:tt)*
+    ) => {
+        $crate::expand_index! {
+            @options $coll [$($keys)*] [$($options)* .background(true)]
+            $($rest)*
+        }
+    };
+    (
+        @options $coll:tt [$($keys:tt)*] [$($options:tt)*]
+        partial { $($filter:tt)+ } $($rest:tt)*
+    ) => {
+        $crate::expand_index! {
+            @options $coll [$($keys)*] [$($options)* .partial_filter_expression({
+                let mut filter = $crate::mongodb::bson::document::Document::new();
+                $crate::expand_index!(@filter $coll filter $($filter)+);
+                filter
+            })]
+            $($rest)*
+        }
+    };
+    // parse the fields of a partial filter one by one, `null` is no expression, thus it needs to be matched before `$value:expr`
+    (
+        @filter $coll:tt $filter:ident
+        $field:ident: null $(, $($rest:tt)*)?
+    ) => {
+        $filter.insert(
+            $crate::expand_index!(@key $coll $field),
+            $crate::mongodb::bson::doc! { "$type": "null" },
+        );
+        $crate::expand_index!(@filter $coll $filter $($($rest)*)?);
+    };
+    (
+        @filter $coll:tt $filter:ident
+        $field:ident: $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $filter.insert($crate::expand_index!(@key $coll $field), $value);
+        $crate::expand_index!(@filter $coll $filter $($($rest)*)?);
+    };
+    (@filter $coll:tt $filter:ident) => {};
+    // everything is parsed, build the model
+    (
+        @options $coll:tt [$(($key:expr, $direction:expr))+] [$($options:tt)*]
+    ) => {
+        {
+            let mut keys = $crate::mongodb::bson::document::Document::new();
+            $(
+                keys.insert($key, $direction as i32);
+            )+
+            $crate::mongodb::IndexModel::builder()
+                .keys(keys)
+                .options($crate::mongodb::options::IndexOptions::builder()$($options)*.build())
+                .build()
+        }
+    };
+}
+
+/// Expands the type / the initialization of one collection handle on the main database client, as well as the writes that depend on the handles being read-only.
+///
+/// Needed internally, but has no big use on its own.
+/// Thus hidden from documentation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_collection_handle {
+    (@type collection; $coll_type:ty) => {
+        $crate::mongodb::Collection<$coll_type>
+    };
+    (@type read_only; $coll_type:ty) => {
+        $crate::ReadOnlyCollection<$coll_type>
+    };
+    (@new collection; $collection:expr) => {
+        $collection
+    };
+    (@new read_only; $collection:expr) => {
+        $crate::ReadOnlyCollection::from($collection)
+    };
+    (@write collection; $($tokens:tt)*) => {
+        $($tokens)*
+    };
+    (@write read_only; $($tokens:tt)*) => {};
+    (@drop_database collection; $database:expr) => {
+        $database.drop(std::option::Option::None).await
+    };
+    (@drop_database read_only; $database:expr) => {
+        $crate::mongodb::error::Result::Err($crate::mongodb::error::Error::custom(std::format!(
+            "cannot drop the database `{}` through a read-only handler",
+            $database.name()
+        )))
+    };
+}
+
+/// Expands the helpers of collections with an `_id` field on the repository and the database handler, unless the collection is declared with `_id: none`.
+///
+/// The collection parameters are searched for `_id`, the type itself is taken from [`MongoCollectionId::Id`] since custom types may only be in scope of the `schema` module.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_id_helpers {
+    ($target:ident $args:tt [_id: none $(, $($params:tt)*)?] $coll_name:ident) => {};
+    ($target:ident $args:tt [$param:ident $(: $($value:tt)::+ $($word:ident $(= $($word_value:tt)::+)?)?)? $(, $($params:tt)*)?] $coll_name:ident) => {
+        $crate::expand_id_helpers! { $target $args [$($($params)*)?] $coll_name }
+    };
+    (repository [$handle:ident $audit:tt] [] $coll_name:ident) => {
+        $crate::paste::paste! {
+            $crate::expand_collection_handle! {
+                @write $handle;
+                #[doc = "Replaces the stored `" $coll_name "` document with the same `_id` by `document`, using the given `options`."]
+                #[doc = ""]
+                #[doc = "Set `upsert` or a write concern in `options` for single operations without reconfiguring the collection."]
+                #[allow(dead_code)]
+                pub async fn replace_with(
+                    &self,
+                    document: &schema::$coll_name,
+                    options: $crate::mongodb::options::ReplaceOptions,
+                ) -> $crate::mongodb::error::Result<$crate::mongodb::results::UpdateResult> {
+                    $crate::validate_before_write(document)?;
+                    let id = match &document._id {
+                        std::option::Option::Some(id) => $crate::mongodb::bson::to_bson(id)?,
+                        std::option::Option::None => {
+                            return $crate::mongodb::error::Result::Err(
+                                <$crate::mongodb::bson::ser::Error as $crate::serde::ser::Error>::custom(
+                                    "cannot replace a document without `_id`",
+                                )
+                                .into(),
+                            )
+                        }
+                    };
+                    let mut replacement = $crate::mongodb::bson::to_raw_document_buf(document)?.to_document()?;
+                    <schema::$coll_name as $crate::MongoCollection>::normalize_document(&mut replacement);
+                    let result = self
+                        .collection
+                        .clone_with_type::<$crate::mongodb::bson::document::Document>()
+                        .replace_one($crate::mongodb::bson::doc! { "_id": id.clone() }, replacement, options)
+                        .await?;
+                    $crate::expand_audit! {
+                        $audit self.database, "replace", <schema::$coll_name as $crate::MongoCollection>::NAME, id
+                    }
+                    $crate::mongodb::error::Result::Ok(result)
+                }
+
+                #[doc = "Deletes the `" $coll_name "` document with the given `_id`."]
+                #[doc = ""]
+                #[doc = "Removes the document even if the collection is declared with `soft_delete`, use `soft_delete_by_id` to keep it."]
+                #[allow(dead_code)]
+                pub async fn delete_by_id(
+                    &self,
+                    id: &<schema::$coll_name as $crate::MongoCollectionId>::Id,
+                ) -> $crate::mongodb::error::Result<$crate::mongodb::results::DeleteResult> {
+                    let id = $crate::mongodb::bson::to_bson(id)?;
+                    let result = self
+                        .collection
+                        .delete_one($crate::mongodb::bson::doc! { "_id": id.clone() }, std::option::Option::None)
+                        .await?;
+                    $crate::expand_audit! {
+                        $audit self.database, "delete", <schema::$coll_name as $crate::MongoCollection>::NAME, id
+                    }
+                    $crate::mongodb::error::Result::Ok(result)
+                }
+
+                #[doc = "Saves the fields of `document` that differ from the stored `" $coll_name "` document with the same `_id`."]
+                #[doc = ""]
+                #[doc = "Reads the stored document first and issues a single update that `$set`s the changed fields and `$unset`s the fields missing in `document`, see `changes_update`."]
+                #[doc = "Returns `None` without writing if nothing changed, fails if there is no stored document with the same `_id`."]
+                #[allow(dead_code)]
+                pub async fn save_changes(
+                    &self,
+                    document: &schema::$coll_name,
+                ) -> $crate::mongodb::error::Result<std::option::Option<$crate::mongodb::results::UpdateResult>> {
+                    $crate::validate_before_write(document)?;
+                    let mut serialized = $crate::mongodb::bson::to_raw_document_buf(document)?.to_document()?;
+                    <schema::$coll_name as $crate::MongoCollection>::normalize_document(&mut serialized);
+                    let id = match serialized.get("_id") {
+                        std::option::Option::Some(id) => id.clone(),
+                        std::option::Option::None => {
+                            return $crate::mongodb::error::Result::Err(
+                                <$crate::mongodb::bson::ser::Error as $crate::serde::ser::Error>::custom(
+                                    "cannot save changes of a document without `_id`",
+                                )
+                                .into(),
+                            )
+                        }
+                    };
+                    let filter = $crate::mongodb::bson::doc! { "_id": id.clone() };
+                    let collection = self.collection
+                        .clone_with_type::<$crate::mongodb::bson::document::Document>();
+                    let stored = match collection.find_one(filter.clone(), std::option::Option::None).await? {
+                        std::option::Option::Some(stored) => stored,
+                        std::option::Option::None => {
+                            return $crate::mongodb::error::Result::Err($crate::mongodb::error::Error::custom(std::format!(
+                                "cannot save changes, there is no `{}` document with `_id` {}",
+                                <schema::$coll_name as $crate::MongoCollection>::NAME,
+                                id
+                            )))
+                        }
+                    };
+                    let update = match $crate::changes_update(&stored, &serialized) {
+                        std::option::Option::Some(update) => update,
+                        std::option::Option::None => return $crate::mongodb::error::Result::Ok(std::option::Option::None),
+                    };
+                    let result = collection
+                        .update_one(filter, update, std::option::Option::None)
+                        .await?;
+                    $crate::expand_audit! {
+                        $audit self.database, "update", <schema::$coll_name as $crate::MongoCollection>::NAME, id
+                    }
+                    $crate::mongodb::error::Result::Ok(std::option::Option::Some(result))
+                }
+
+                #[doc = "Inserts or replaces all `documents` in the `" $coll_name "` collection by their `_id` using batched unordered `update` commands, see `upsert_many`."]
+                #[doc = ""]
+                #[doc = "The written documents are recorded in the audit log even if others failed, see `mongo_db!` for details."]
+                #[allow(dead_code)]
+                pub async fn upsert(
+                    &self,
+                    documents: std::vec::Vec<schema::$coll_name>,
+                ) -> $crate::mongodb::error::Result<$crate::UpsertManyResult> {
+                    let mut ids = std::vec::Vec::with_capacity(documents.len());
+                    let mut replacements = std::vec::Vec::with_capacity(documents.len());
+                    for document in &documents {
+                        $crate::validate_before_write(document)?;
+                        let mut replacement = $crate::mongodb::bson::to_raw_document_buf(document)?.to_document()?;
+                        <schema::$coll_name as $crate::MongoCollection>::normalize_document(&mut replacement);
+                        match replacement.get("_id") {
+                            std::option::Option::Some(id) => ids.push(id.clone()),
+                            std::option::Option::None => {
+                                return $crate::mongodb::error::Result::Err($crate::mongodb::error::Error::custom(
+                                    std::string::String::from("cannot upsert a document without `_id`"),
+                                ))
+                            }
+                        }
+                        replacements.push(replacement);
+                    }
+                    let (written, result) = $crate::upsert_many(
+                        &self.database,
+                        <schema::$coll_name as $crate::MongoCollection>::NAME,
+                        replacements,
+                    )
+                    .await;
+                    for index in written {
+                        $crate::expand_audit! {
+                            $audit self.database, "upsert", <schema::$coll_name as $crate::MongoCollection>::NAME, ids[index].clone()
+                        }
+                    }
+                    result
+                }
+            }
+        }
+    };
+    (with_id { $($tokens:tt)* } [] $coll_name:ident) => {
+        $($tokens)*
+    };
+    (handler [$handle:ident] [] $coll_name:ident) => {
+        $crate::paste_snake_case! {
+            $crate::paste::paste! {
+                #[doc = "Finds the `" $coll_name "` document with the given `_id`, see `" $coll_name "Repository::find_by_id`."]
+                #[allow(dead_code)]
+                pub async fn [<find_ $coll_name:snake:lower _by_id>](
+                    &self,
+                    id: &<schema::$coll_name as $crate::MongoCollectionId>::Id,
+                ) -> $crate::mongodb::error::Result<std::option::Option<schema::$coll_name>> {
+                    self.[<$coll_name:snake:lower>]().find_by_id(id).await
+                }
+
+                $crate::expand_collection_handle! {
+                    @write $handle;
+                    #[doc = "Replaces the stored `" $coll_name "` document with the same `_id` using the given `options`, see `" $coll_name "Repository::replace_with`."]
+                    #[allow(dead_code)]
+                    pub async fn [<replace_ $coll_name:snake:lower _with>](
+                        &self,
+                        document: &schema::$coll_name,
+                        options: $crate::mongodb::options::ReplaceOptions,
+                    ) -> $crate::mongodb::error::Result<$crate::mongodb::results::UpdateResult> {
+                        self.[<$coll_name:snake:lower>]().replace_with(document, options).await
+                    }
+
+                    #[doc = "Deletes the `" $coll_name "` document with the given `_id`, see `" $coll_name "Repository::delete_by_id`."]
+                    #[allow(dead_code)]
+                    pub async fn [<delete_ $coll_name:snake:lower _by_id>](
+                        &self,
+                        id: &<schema::$coll_name as $crate::MongoCollectionId>::Id,
+                    ) -> $crate::mongodb::error::Result<$crate::mongodb::results::DeleteResult> {
+                        self.[<$coll_name:snake:lower>]().delete_by_id(id).await
+                    }
+
+                    #[doc = "Saves the fields of `document` that differ from the stored `" $coll_name "` document, see `" $coll_name "Repository::save_changes`."]
+                    #[allow(dead_code)]
+                    pub async fn [<save_changes_ $coll_name:snake:lower>](
+                        &self,
+                        document: &schema::$coll_name,
+                    ) -> $crate::mongodb::error::Result<std::option::Option<$crate::mongodb::results::UpdateResult>> {
+                        self.[<$coll_name:snake:lower>]().save_changes(document).await
+                    }
+
+                    #[doc = "Inserts or replaces all `documents` in the `" $coll_name "` collection, see `" $coll_name "Repository::upsert`."]
+                    #[allow(dead_code)]
+                    pub async fn [<upsert_ $coll_name:snake:lower>](
+                        &self,
+                        documents: std::vec::Vec<schema::$coll_name>,
+                    ) -> $crate::mongodb::error::Result<$crate::UpsertManyResult> {
+                        self.[<$coll_name:snake:lower>]().upsert(documents).await
+                    }
+                }
+            }
+        }
+    };
+}
+
+/// Inserts an audit record into the database's audit collection if one is declared with the `audit` database parameter.
+///
+/// Expands to a statement that returns early on errors, thus it can only be used inside of functions returning a mongodb [`Result`](mongodb::error::Result).
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_audit {
+    ((audit) $database:expr, $operation:literal, $coll_name:expr, $id:expr) => {};
+    ((audit $audit_coll:ident) $database:expr, $operation:literal, $coll_name:expr, $id:expr) => {
+        $database
+            .collection::<$crate::mongodb::bson::document::Document>($crate::case!($audit_coll => Camel))
+            .insert_one(
+                $crate::mongodb::bson::doc! {
+                    "operation": $operation,
+                    "collection": $coll_name,
+                    "timestamp": $crate::mongodb::bson::DateTime::now(),
+                    "documentId": $id,
+                },
+                std::option::Option::None,
+            )
+            .await?;
+    };
+}
+
+/// Expands the main database client.
+///
+/// Needed internally, but has no big use on its own.
+/// Thus hidden from documentation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! expand_main_client {
+    // entry point, initialize all slots
+    (
+        [$($params:tt)*]
+        $($rest:tt)*
+    ) => {
+        $crate::expand_main_client! {
+            @parse [$($params)*]
+            (handle collection)
+            (audit)
+            (shutdown)
+            (env)
+            $($rest)*
+        }
+    };
+    (
+        @parse [read_only $(, $($params:tt)*)?]
+        (handle $old:ident)
+        $($rest:tt)*
+    ) => {
+        $crate::expand_main_client! {
+            @parse [$($($params)*)?]
+            (handle read_only)
+            $($rest)*
+        }
+    };
+    (
+        @parse [audit: $audit_coll:ident $(, $($params:tt)*)?]
+        $handle:tt
+        (audit $($old:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::expand_main_client! {
+            @parse [$($($params)*)?]
+            $handle
+            (audit $audit_coll)
+            $($rest)*
+        }
+    };
+    (
+        @parse [shutdown_on_drop $(, $($params:tt)*)?]
+        $handle:tt
+        $audit:tt
+        (shutdown $($old:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::expand_main_client! {
+            @parse [$($($params)*)?]
+            $handle
+            $audit
+            (shutdown shutdown_on_drop)
+            $($rest)*
+        }
+    };
+    (
+        @parse [env: $env_var:literal $(, $($params:tt)*)?]
+        $handle:tt
+        $audit:tt
+        $shutdown:tt
+        (env $($old:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::expand_main_client! {
+            @parse [$($($params)*)?]
+            $handle
+            $audit
+            $shutdown
+            (env $env_var)
+            $($rest)*
+        }
+    };
+    (
+        @parse [$param:ident $($params:tt)*]
+        $($rest:tt)*
+    ) => {
+        std::compile_error!(std::concat!("Unknown database parameter `", std::stringify!($param), "`"));
+    };
+    // all parameters parsed
+    (
+        @parse []
+        (handle $handle:ident)
+        $audit:tt
+        $shutdown:tt
+        (env $($env_var:literal)?)
+        $(#[$additional_db_attr:meta])*
+        $db_name:ident {
+            $(
+                $(#[$additional_coll_attr:meta])*
+                $coll_name:ident [$($coll_params:tt)*] {
+                    $(
+                        $(#[$additional_field_attr:meta])*
+                        $field:ident: $field_type:ty
+                    ),*$(,)?
+                }
+            ),+
+        }
+        $(@view $view_name:ident;)*
+        $(@indexes {
+            $(
+                $index_coll_name:ident: $([$($index:tt)+])+
+            );+$(;)?
+        })?
+        $(-{
+            $($impl:tt)+
+        })?
+    ) => {
+        $crate::paste_snake_case! {
+            $crate::paste::paste! {
+                #[doc = "Client to interact with the `" $db_name "` database."]
+                $(#[$additional_db_attr])*
+                pub struct $db_name {
+                    pub client: $crate::mongodb::Client,
+                    pub database: $crate::mongodb::Database,
+                    $(
+                        #[doc = "Handle to the `" $coll_name "` collection"]
+                        pub [<$coll_name:snake:lower _coll>]: $crate::expand_collection_handle!(@type $handle; schema::$coll_name)
+                    ),+
+                    $(
+                        ,
+                        #[doc = "Read-only handle to the `" $view_name "` view"]
+                        pub [<$view_name:snake:lower _view>]: $crate::ReadOnlyCollection<schema::$view_name>
+                    )*
+                }
+
+                #[$crate::async_trait::async_trait]
+                impl $crate::MongoClient for $db_name {
+                    const NAME: &'static str = $crate::case!($db_name => Camel);
+                    const COLLECTION_NAMES: &'static [&'static str] = &[
+                        $(<schema::$coll_name as $crate::MongoCollection>::NAME),+
+                    ];
+                    $(const ENV_VAR: &'static str = $env_var;)?
+                    type Handle<T> = $crate::expand_collection_handle!(@type $handle; T);
+
+                    async fn new(connection_str: &str) -> $crate::mongodb::error::Result<Self> {
+                        let options = match $crate::mongodb::options::ClientOptions::parse(connection_str).await {
+                            $crate::mongodb::error::Result::Ok(options) => options,
+                            $crate::mongodb::error::Result::Err(e) => return $crate::mongodb::error::Result::Err(e),
+                        };
+                        <Self as $crate::MongoClient>::new_with_options(options).await
+                    }
+
+                    fn new_with_client(client: $crate::mongodb::Client) -> $crate::mongodb::error::Result<Self> {
+                        let database = client.database(<Self as $crate::MongoClient>::NAME);
+                        $(
+                            let [<$coll_name:snake:lower _coll>] = $crate::expand_collection_handle!(
+                                @new $handle;
+                                database.collection(<schema::$coll_name as $crate::MongoCollection>::NAME)
+                            );
+                        )+
+                        $(
+                            let [<$view_name:snake:lower _view>] = $crate::ReadOnlyCollection::from(
+                                database.collection(<schema::$view_name as $crate::MongoView>::NAME)
+                            );
+                        )*
+                        $crate::mongodb::error::Result::Ok(Self {
+                            client,
+                            database,
+                            $([<$coll_name:snake:lower _coll>],)+
+                            $([<$view_name:snake:lower _view>],)*
+                        })
+                    }
+
+                    async fn reconnect(&mut self, connection_str: &str) -> $crate::mongodb::error::Result<()> {
+                        let client = match $crate::mongodb::Client::with_uri_str(connection_str).await {
+                            $crate::mongodb::error::Result::Ok(client) => client,
+                            $crate::mongodb::error::Result::Err(e) => return $crate::mongodb::error::Result::Err(e),
+                        };
+                        let database = client.database(<Self as $crate::MongoClient>::NAME);
+                        $(
+                            self.[<$coll_name:snake:lower _coll>] = $crate::expand_collection_handle!(
+                                @new $handle;
+                                database.collection(<schema::$coll_name as $crate::MongoCollection>::NAME)
+                            );
+                        )+
+                        $(
+                            self.[<$view_name:snake:lower _view>] = $crate::ReadOnlyCollection::from(
+                                database.collection(<schema::$view_name as $crate::MongoView>::NAME)
+                            );
+                        )*
+                        self.database = database;
+                        self.client = client;
+                        $crate::mongodb::error::Result::Ok(())
+                    }
+
+                    async fn drop_database(&self) -> $crate::mongodb::error::Result<()> {
+                        $crate::expand_collection_handle!(@drop_database $handle; self.database)
+                    }
+
+                    fn index_models() -> std::vec::Vec<(&'static str, $crate::mongodb::IndexModel)> {
+                        #[allow(unused_mut)]
+                        let mut models = std::vec![
+                            $($($(
+                                (
+                                    <schema::$index_coll_name as $crate::MongoCollection>::NAME,
+                                    $crate::expand_index!(@keys $index_coll_name [] $($index)+)
+                                )
+                            ),+),+)?
+                        ];
+                        $(
+                            models.extend(
+                                <schema::$coll_name as $crate::MongoCollection>::index_models()
+                                    .into_iter()
+                                    .map(|model| (<schema::$coll_name as $crate::MongoCollection>::NAME, model)),
+                            );
+                        )+
+                        models
+                    }
+
+                    fn view_options() -> std::vec::Vec<(&'static str, $crate::mongodb::options::CreateCollectionOptions)> {
+                        std::vec![
+                            $(
+                                (
+                                    <schema::$view_name as $crate::MongoView>::NAME,
+                                    $crate::mongodb::options::CreateCollectionOptions::builder()
+                                        .view_on(std::string::String::from(<schema::$view_name as $crate::MongoView>::VIEW_ON))
+                                        .pipeline(<schema::$view_name as $crate::MongoView>::pipeline())
+                                        .build(),
+                                )
+                            ),*
+                        ]
+                    }
+
+                    fn database(&self) -> &$crate::mongodb::Database {
+                        &self.database
+                    }
+                    fn client(&self) -> &$crate::mongodb::Client {
+                        &self.client
+                    }
+                }
+                $(
+                    #[doc = "Repository that bundles all generated operations on the [`" $coll_name "`](schema::" $coll_name ") collection."]
+                    #[doc = ""]
+                    #[doc = "Obtained from the method of [`" $db_name "`] that is named after the collection in `snake_case`, it is cheap to create and to clone."]
+                    #[derive(Clone)]
+                    pub struct [<$coll_name Repository>] {
+                        database: $crate::mongodb::Database,
+                        collection: $crate::expand_collection_handle!(@type $handle; schema::$coll_name),
+                    }
+
+                    impl [<$coll_name Repository>] {
+                        $crate::expand_id_helpers! { repository [$handle $audit] [$($coll_params)*] $coll_name }
+                        $crate::expand_sequence_id! { [$handle] [$($coll_params)*] $coll_name }
+                        $crate::expand_soft_delete_writes! { [$handle $audit] [$($coll_params)*] $coll_name }
+                        $crate::expand_schema_version_writes! { [$handle $audit] $coll_name [$($field)*] }
+                        $crate::expand_assign_id! { [$handle] [$($coll_params)*] (id) (gen) () $coll_name }
+
+                        #[doc = "Returns a reference to the database object."]
+                        #[allow(dead_code)]
+                        pub fn database(&self) -> &$crate::mongodb::Database {
+                            &self.database
+                        }
+
+                        #[doc = "Returns a reference to the handle of the `" $coll_name "` collection."]
+                        #[allow(dead_code)]
+                        pub fn collection(&self) -> &$crate::expand_collection_handle!(@type $handle; schema::$coll_name) {
+                            &self.collection
+                        }
+
+                        #[doc = "Finds all documents in the `" $coll_name "` collection matching `filter`."]
+                        #[doc = ""]
+                        #[doc = "Uses the collection's `MongoCollection::default_find_options` if `options` is `None`."]
+                        #[doc = "Excludes soft deleted documents if the collection is declared with `soft_delete`, see `exclude_soft_deleted`."]
+                        #[allow(dead_code)]
+                        pub async fn find(
+                            &self,
+                            filter: impl std::convert::Into<std::option::Option<$crate::mongodb::bson::document::Document>>,
+                            options: impl std::convert::Into<std::option::Option<$crate::mongodb::options::FindOptions>>,
+                        ) -> $crate::mongodb::error::Result<$crate::mongodb::Cursor<schema::$coll_name>> {
+                            let options = options
+                                .into()
+                                .or_else(<schema::$coll_name as $crate::MongoCollection>::default_find_options);
+                            self.collection
+                                .find($crate::exclude_soft_deleted::<schema::$coll_name>(filter.into()), options)
+                                .await
+                        }
+
+                        #[doc = "Returns the most recent document in the `" $coll_name "` collection, ie. the first one sorted by `MongoCollection::order_by`."]
+                        #[doc = ""]
+                        #[doc = "Falls back to reverse natural order if the collection is declared without `order_by`, which only matches the insertion order for capped collections."]
+                        #[doc = "Excludes soft deleted documents if the collection is declared with `soft_delete`, see `exclude_soft_deleted`."]
+                        #[allow(dead_code)]
+                        pub async fn latest(
+                            &self,
+                        ) -> $crate::mongodb::error::Result<std::option::Option<schema::$coll_name>> {
+                            let sort = <schema::$coll_name as $crate::MongoCollection>::order_by()
+                                .unwrap_or_else(|| $crate::mongodb::bson::doc! { "$natural": -1 });
+                            self.database
+                                .collection::<$crate::mongodb::bson::document::Document>(<schema::$coll_name as $crate::MongoCollection>::NAME)
+                                .find_one(
+                                    $crate::exclude_soft_deleted::<schema::$coll_name>(std::option::Option::None),
+                                    $crate::mongodb::options::FindOneOptions::builder().sort(sort).build(),
+                                )
+                                .await?
+                                .map(|document| $crate::deserialize_document(<schema::$coll_name as $crate::MongoCollection>::NAME, document))
+                                .transpose()
+                        }
+
+                        #[doc = "Checks whether the `" $coll_name "` collection contains no documents."]
+                        #[doc = ""]
+                        #[doc = "Looks for a single `_id` instead of counting, which is cheaper for large collections."]
+                        #[doc = "Excludes soft deleted documents if the collection is declared with `soft_delete`, see `exclude_soft_deleted`."]
+                        #[allow(dead_code)]
+                        pub async fn is_empty(
+                            &self,
+                        ) -> $crate::mongodb::error::Result<bool> {
+                            std::result::Result::Ok(self
+                                .database
+                                .collection::<$crate::mongodb::bson::document::Document>(<schema::$coll_name as $crate::MongoCollection>::NAME)
+                                .find_one(
+                                    $crate::exclude_soft_deleted::<schema::$coll_name>(std::option::Option::None),
+                                    $crate::mongodb::options::FindOneOptions::builder()
+                                        .projection($crate::mongodb::bson::doc! { "_id": 1 })
+                                        .build(),
+                                )
+                                .await?
+                                .is_none())
+                        }
+
+                        #[doc = "Streams all documents of the `" $coll_name "` collection, fetching `batch_size` documents per round trip."]
+                        #[doc = ""]
+                        #[doc = "Uses the collection's `MongoCollection::default_find_options` with `batch_size` applied on top."]
+                        #[allow(dead_code)]
+                        pub async fn export(
+                            &self,
+                            batch_size: u32,
+                        ) -> $crate::mongodb::error::Result<$crate::mongodb::Cursor<schema::$coll_name>> {
+                            let mut options = <schema::$coll_name as $crate::MongoCollection>::default_find_options()
+                                .unwrap_or_default();
+                            options.batch_size = std::option::Option::Some(batch_size);
+                            self.collection
+                                .find($crate::exclude_soft_deleted::<schema::$coll_name>(std::option::Option::None), options)
+                                .await
+                        }
+
+                        #[doc = "Counts the documents in the `" $coll_name "` collection matching `filter` with full control over `options`."]
+                        #[doc = ""]
+                        #[doc = "Set `CountOptions::hint` to force an index if the query planner picks a bad one."]
+                        #[doc = "Excludes soft deleted documents if the collection is declared with `soft_delete`, see `exclude_soft_deleted`."]
+                        #[allow(dead_code)]
+                        pub async fn count_with(
+                            &self,
+                            filter: $crate::mongodb::bson::document::Document,
+                            options: $crate::mongodb::options::CountOptions,
+                        ) -> $crate::mongodb::error::Result<u64> {
+                            self.collection
+                                .count_documents($crate::exclude_soft_deleted::<schema::$coll_name>(std::option::Option::Some(filter)), options)
+                                .await
+                        }
+
+                        #[doc = "Runs the aggregation `pipeline` on the `" $coll_name "` collection, eg. a `Pipeline`."]
+                        #[doc = ""]
+                        #[doc = "The results are returned as plain documents, since stages like `$group` or `$project` change their shape."]
+                        #[allow(dead_code)]
+                        pub async fn aggregate(
+                            &self,
+                            pipeline: impl std::iter::IntoIterator<Item = $crate::mongodb::bson::document::Document>,
+                            options: impl std::convert::Into<std::option::Option<$crate::mongodb::options::AggregateOptions>>,
+                        ) -> $crate::mongodb::error::Result<$crate::mongodb::Cursor<$crate::mongodb::bson::document::Document>> {
+                            self.collection.aggregate(pipeline, options).await
+                        }
+
+                        #[doc = "Runs the `collStats` command on the `" $coll_name "` collection, see `MongoClient::collection_stats`."]
+                        #[allow(dead_code)]
+                        pub async fn stats(
+                            &self,
+                        ) -> $crate::mongodb::error::Result<$crate::mongodb::bson::document::Document> {
+                            self.database
+                                .run_command(
+                                    $crate::mongodb::bson::doc! { "collStats": <schema::$coll_name as $crate::MongoCollection>::NAME },
+                                    std::option::Option::None,
+                                )
+                                .await
+                        }
+
+                        #[doc = "Returns the namespace of the `" $coll_name "` collection as `{database}.{collection}`, see `MongoClient::namespace`."]
+                        #[allow(dead_code)]
+                        pub fn namespace(&self) -> std::string::String {
+                            self.collection.namespace().to_string()
+                        }
+
+                        #[doc = "Checks whether the `" $coll_name "` collection exists, see `MongoClient::collection_exists`."]
+                        #[allow(dead_code)]
+                        pub async fn exists(
+                            &self,
+                        ) -> $crate::mongodb::error::Result<bool> {
+                            let name = <schema::$coll_name as $crate::MongoCollection>::NAME;
+                            std::result::Result::Ok(self
+                                .database
+                                .list_collection_names($crate::mongodb::bson::doc! { "name": name })
+                                .await?
+                                .iter()
+                                .any(|collection_name| collection_name == name))
+                        }
+
+                        $crate::expand_collection_handle! {
+                            @write $handle;
+                            #[doc = "Inserts `document` into the `" $coll_name "` collection."]
+                            #[doc = ""]
+                            #[doc = "Assigns an `_id` first if `document` has none, see `assign_id`, the `_id` is returned in `InsertOneResult::inserted_id`."]
+                            #[doc = "Fields declared with `#[normalize(...)]` are normalized before writing, see `MongoCollection::normalize`."]
+                            #[allow(dead_code)]
+                            pub async fn insert(
+                                &self,
+                                mut document: schema::$coll_name,
+                            ) -> $crate::mongodb::error::Result<$crate::mongodb::results::InsertOneResult> {
+                                $crate::expand_id_helpers! { with_id { self.assign_id(&mut document).await?; } [$($coll_params)*] $coll_name }
+                                $crate::MongoCollection::normalize(&mut document);
+                                $crate::validate_before_write(&document)?;
+                                let result = self.collection.insert_one(document, std::option::Option::None).await?;
+                                $crate::expand_audit! {
+                                    $audit self.database, "insert", <schema::$coll_name as $crate::MongoCollection>::NAME, result.inserted_id.clone()
+                                }
+                                $crate::mongodb::error::Result::Ok(result)
+                            }
+
+                            #[doc = "Returns the `" $coll_name "` document matching `filter`, inserting `default` first if there is none."]
+                            #[doc = ""]
+                            #[doc = "Uses a single `findAndModify` command with `upsert` and `$setOnInsert`, thus concurrent callers never insert twice."]
+                            #[doc = "Only an inserted document is recorded in the audit log, as `insert` with its `_id`."]
+                            #[doc = "Fields of `filter` that test for equality are stored in the inserted document too."]
+                            #[doc = "Assigns an `_id` to `default` first if neither `default` nor `filter` contains one, see `assign_id`."]
+                            #[allow(dead_code)]
+                            pub async fn get_or_insert(
+                                &self,
+                                filter: $crate::mongodb::bson::document::Document,
+                                default: schema::$coll_name,
+                            ) -> $crate::mongodb::error::Result<schema::$coll_name> {
+                                $crate::expand_id_helpers! {
+                                    with_id {
+                                        let mut default = default;
+                                        if !filter.contains_key("_id") {
+                                            self.assign_id(&mut default).await?;
+                                        }
+                                    }
+                                    [$($coll_params)*] $coll_name
+                                }
+                                $crate::validate_before_write(&default)?;
+                                let mut insert = $crate::mongodb::bson::to_raw_document_buf(&default)?.to_document()?;
+                                <schema::$coll_name as $crate::MongoCollection>::normalize_document(&mut insert);
+                                // the raw command reports whether the document was inserted, `find_one_and_update` does not
+                                let response = self
+                                    .database
+                                    .run_command(
+                                        $crate::mongodb::bson::doc! {
+                                            "findAndModify": <schema::$coll_name as $crate::MongoCollection>::NAME,
+                                            "query": filter,
+                                            "update": { "$setOnInsert": insert },
+                                            "upsert": true,
+                                            "new": true,
+                                        },
+                                        std::option::Option::None,
+                                    )
+                                    .await?;
+                                let document = match response.get_document("value") {
+                                    std::result::Result::Ok(document) => document.clone(),
+                                    std::result::Result::Err(_) => {
+                                        return $crate::mongodb::error::Result::Err($crate::mongodb::error::Error::custom(
+                                            std::format!(
+                                                "upsert into `{}` returned no document",
+                                                <schema::$coll_name as $crate::MongoCollection>::NAME
+                                            ),
+                                        ))
+                                    }
+                                };
+                                if let std::option::Option::Some(id) = response
+                                    .get_document("lastErrorObject")
+                                    .ok()
+                                    .and_then(|last_error| last_error.get("upserted"))
+                                {
+                                    $crate::expand_audit! {
+                                        $audit self.database, "insert", <schema::$coll_name as $crate::MongoCollection>::NAME, id.clone()
+                                    }
+                                }
+                                $crate::deserialize_document(<schema::$coll_name as $crate::MongoCollection>::NAME, document)
+                            }
+
+                            #[doc = "Deletes all documents in the `" $coll_name "` collection matching `filter`."]
+                            #[doc = ""]
+                            #[doc = "An empty `filter` deletes all documents of the collection."]
+                            #[allow(dead_code)]
+                            pub async fn delete_many(
+                                &self,
+                                filter: $crate::mongodb::bson::document::Document,
+                            ) -> $crate::mongodb::error::Result<$crate::mongodb::results::DeleteResult> {
+                                let result = self.collection.delete_many(filter.clone(), std::option::Option::None).await?;
+                                $crate::expand_audit! {
+                                    $audit self.database, "deleteMany", <schema::$coll_name as $crate::MongoCollection>::NAME, filter
+                                }
+                                $crate::mongodb::error::Result::Ok(result)
+                            }
+
+                            #[doc = "Applies `update` to all documents in the `" $coll_name "` collection matching `filter`."]
+                            #[doc = ""]
+                            #[doc = "`update` is passed to the server unmodified, thus it needs update operators like `$set`."]
+                            #[allow(dead_code)]
+                            pub async fn update_many(
+                                &self,
+                                filter: $crate::mongodb::bson::document::Document,
+                                update: $crate::mongodb::bson::document::Document,
+                            ) -> $crate::mongodb::error::Result<$crate::mongodb::results::UpdateResult> {
+                                let result = self.collection.update_many(filter.clone(), update, std::option::Option::None).await?;
+                                $crate::expand_audit! {
+                                    $audit self.database, "updateMany", <schema::$coll_name as $crate::MongoCollection>::NAME, filter
+                                }
+                                $crate::mongodb::error::Result::Ok(result)
+                            }
+                        }
+
+                        $crate::expand_change_streams! {
+                            #[doc = "Opens a change stream on the `" $coll_name "` collection."]
+                            #[allow(dead_code)]
+                            pub async fn watch(
+                                &self,
+                                pipeline: impl std::iter::IntoIterator<Item = $crate::mongodb::bson::document::Document>,
+                                options: impl std::convert::Into<std::option::Option<$crate::mongodb::options::ChangeStreamOptions>>,
+                            ) -> $crate::mongodb::error::Result<
+                                $crate::mongodb::change_stream::ChangeStream<
+                                    $crate::mongodb::change_stream::event::ChangeStreamEvent<schema::$coll_name>
+                                >
+                            > {
+                                self.collection.watch(pipeline, options).await
+                            }
+
+                            #[doc = "Opens a change stream on the `" $coll_name "` collection that starts after `resume_after`."]
+                            #[doc = ""]
+                            #[doc = "Starts at the current time if `resume_after` is `None`."]
+                            #[doc = "Persist `ChangeStream::resume_token` after processing each event to continue where a previous consumer stopped."]
+                            #[allow(dead_code)]
+                            pub async fn watch_from(
+                                &self,
+                                resume_after: std::option::Option<$crate::mongodb::change_stream::event::ResumeToken>,
+                            ) -> $crate::mongodb::error::Result<
+                                $crate::mongodb::change_stream::ChangeStream<
+                                    $crate::mongodb::change_stream::event::ChangeStreamEvent<schema::$coll_name>
+                                >
+                            > {
+                                let options = $crate::mongodb::options::ChangeStreamOptions::builder()
+                                    .resume_after(resume_after)
+                                    .build();
+                                self.collection
+                                    .watch(std::iter::empty(), options)
+                                    .await
+                            }
+                        }
+                    }
+                )+
+                $($($(
+                    $crate::expand_unique_finder! { $index_coll_name [] $($index)+ }
+                )+)+)?
+                $(
+                    $crate::expand_unique_finder! { @params $coll_name [$($coll_params)*] }
+                )+
+
+                impl $db_name {
+                    $(
+                        #[doc = "Returns the repository of the `" $coll_name "` collection."]
+                        #[allow(dead_code)]
+                        pub fn [<$coll_name:snake:lower>](&self) -> [<$coll_name Repository>] {
+                            [<$coll_name Repository>] {
+                                database: self.database.clone(),
+                                collection: self.[<$coll_name:snake:lower _coll>].clone(),
+                            }
+                        }
+
+                        #[doc = "Returns a clone of the handle to the `" $coll_name "` collection, eg. to move it into a spawned task."]
+                        #[doc = ""]
+                        #[doc = "Cloning a handle is cheap, all clones share the underlying client."]
+                        #[allow(dead_code)]
+                        pub fn [<$coll_name:snake:lower _handle>](&self) -> $crate::expand_collection_handle!(@type $handle; schema::$coll_name) {
+                            self.[<$coll_name:snake:lower _coll>].clone()
+                        }
+
+                        #[doc = "Finds all documents in the `" $coll_name "` collection matching `filter`, see `" $coll_name "Repository::find`."]
+                        #[allow(dead_code)]
+                        pub async fn [<find_ $coll_name:snake:lower>](
+                            &self,
+                            filter: impl std::convert::Into<std::option::Option<$crate::mongodb::bson::document::Document>>,
+                            options: impl std::convert::Into<std::option::Option<$crate::mongodb::options::FindOptions>>,
+                        ) -> $crate::mongodb::error::Result<$crate::mongodb::Cursor<schema::$coll_name>> {
+                            self.[<$coll_name:snake:lower>]().find(filter, options).await
+                        }
+
+                        #[doc = "Finds the first document in the `" $coll_name "` collection matching `filter` and deserializes it into `T`, see `MongoClient::find_one_as`."]
+                        #[doc = ""]
+                        #[doc = "Excludes soft deleted documents if the collection is declared with `soft_delete`, see `exclude_soft_deleted`."]
+                        #[allow(dead_code)]
+                        pub async fn [<find_one_ $coll_name:snake:lower _as>]<T>(
+                            &self,
+                            filter: impl std::convert::Into<std::option::Option<$crate::mongodb::bson::document::Document>>,
+                        ) -> $crate::mongodb::error::Result<std::option::Option<T>>
+                        where
+                            T: $crate::serde::de::DeserializeOwned,
+                        {
+                            <Self as $crate::MongoClient>::find_one_as(
+                                self,
+                                <schema::$coll_name as $crate::MongoCollection>::NAME,
+                                $crate::exclude_soft_deleted::<schema::$coll_name>(filter.into()).unwrap_or_default(),
+                            )
+                            .await
+                        }
+
+                        $crate::expand_id_helpers! { handler [$handle] [$($coll_params)*] $coll_name }
+
+                        #[doc = "Returns the most recent document in the `" $coll_name "` collection, see `" $coll_name "Repository::latest`."]
+                        #[allow(dead_code)]
+                        pub async fn [<latest_ $coll_name:snake:lower>](
+                            &self,
+                        ) -> $crate::mongodb::error::Result<std::option::Option<schema::$coll_name>> {
+                            self.[<$coll_name:snake:lower>]().latest().await
+                        }
+
+                        #[doc = "Checks whether the `" $coll_name "` collection contains no documents, see `" $coll_name "Repository::is_empty`."]
+                        #[allow(dead_code)]
+                        pub async fn [<$coll_name:snake:lower _is_empty>](
+                            &self,
+                        ) -> $crate::mongodb::error::Result<bool> {
+                            self.[<$coll_name:snake:lower>]().is_empty().await
+                        }
+
+                        #[doc = "Streams all documents of the `" $coll_name "` collection, see `" $coll_name "Repository::export`."]
+                        #[allow(dead_code)]
+                        pub async fn [<export_ $coll_name:snake:lower>](
+                            &self,
+                            batch_size: u32,
+                        ) -> $crate::mongodb::error::Result<$crate::mongodb::Cursor<schema::$coll_name>> {
+                            self.[<$coll_name:snake:lower>]().export(batch_size).await
+                        }
+
+                        #[doc = "Counts the documents in the `" $coll_name "` collection matching `filter` with the given `options`, see `" $coll_name "Repository::count_with`."]
+                        #[allow(dead_code)]
+                        pub async fn [<count_ $coll_name:snake:lower _with>](
+                            &self,
+                            filter: $crate::mongodb::bson::document::Document,
+                            options: $crate::mongodb::options::CountOptions,
+                        ) -> $crate::mongodb::error::Result<u64> {
+                            self.[<$coll_name:snake:lower>]().count_with(filter, options).await
+                        }
+
+                        #[doc = "Runs the aggregation `pipeline` on the `" $coll_name "` collection, see `" $coll_name "Repository::aggregate`."]
+                        #[allow(dead_code)]
+                        pub async fn [<aggregate_ $coll_name:snake:lower>](
+                            &self,
+                            pipeline: impl std::iter::IntoIterator<Item = $crate::mongodb::bson::document::Document>,
+                            options: impl std::convert::Into<std::option::Option<$crate::mongodb::options::AggregateOptions>>,
+                        ) -> $crate::mongodb::error::Result<$crate::mongodb::Cursor<$crate::mongodb::bson::document::Document>> {
+                            self.[<$coll_name:snake:lower>]().aggregate(pipeline, options).await
+                        }
+
+                        #[doc = "Runs the `collStats` command on the `" $coll_name "` collection, see `MongoClient::collection_stats`."]
+                        #[allow(dead_code)]
+                        pub async fn [<$coll_name:snake:lower _stats>](
+                            &self,
+                        ) -> $crate::mongodb::error::Result<$crate::mongodb::bson::document::Document> {
+                            <Self as $crate::MongoClient>::collection_stats(
+                                self,
+                                <schema::$coll_name as $crate::MongoCollection>::NAME,
+                            )
+                            .await
+                        }
+
+                        #[doc = "Returns the namespace of the `" $coll_name "` collection, see `MongoClient::namespace`."]
+                        #[allow(dead_code)]
+                        pub fn [<$coll_name:snake:lower _namespace>](&self) -> std::string::String {
+                            <Self as $crate::MongoClient>::namespace(
+                                self,
+                                <schema::$coll_name as $crate::MongoCollection>::NAME,
+                            )
+                        }
+
+                        #[doc = "Checks whether the `" $coll_name "` collection exists, see `MongoClient::collection_exists`."]
+                        #[allow(dead_code)]
+                        pub async fn [<$coll_name:snake:lower _exists>](
+                            &self,
+                        ) -> $crate::mongodb::error::Result<bool> {
+                            <Self as $crate::MongoClient>::collection_exists(
+                                self,
+                                <schema::$coll_name as $crate::MongoCollection>::NAME,
+                            )
+                            .await
+                        }
+
+                        $crate::expand_collection_handle! {
+                            @write $handle;
+                            #[doc = "Inserts `document` into the `" $coll_name "` collection, see `" $coll_name "Repository::insert`."]
+                            #[allow(dead_code)]
+                            pub async fn [<insert_ $coll_name:snake:lower>](
+                                &self,
+                                document: schema::$coll_name,
+                            ) -> $crate::mongodb::error::Result<$crate::mongodb::results::InsertOneResult> {
+                                self.[<$coll_name:snake:lower>]().insert(document).await
+                            }
+
+                            #[doc = "Creates all indexes of the `" $coll_name "` collection, see `MongoClient::index_models`."]
+                            #[doc = ""]
+                            #[doc = "Indexes that exist already with the same options are left unchanged."]
+                            #[allow(dead_code)]
+                            pub async fn [<create_ $coll_name:snake:lower _indexes>](&self) -> $crate::mongodb::error::Result<()> {
+                                let name = <schema::$coll_name as $crate::MongoCollection>::NAME;
+                                let models: std::vec::Vec<$crate::mongodb::IndexModel> = <Self as $crate::MongoClient>::index_models()
+                                    .into_iter()
+                                    .filter(|(collection_name, _)| *collection_name == name)
+                                    .map(|(_, model)| model)
+                                    .collect();
+                                if !models.is_empty() {
+                                    self.database
+                                        .collection::<$crate::mongodb::bson::document::Document>(name)
+                                        .create_indexes(models, std::option::Option::None)
+                                        .await?;
+                                }
+                                $crate::mongodb::error::Result::Ok(())
+                            }
+
+                            #[doc = "Drops the `" $coll_name "` collection with all its documents and indexes."]
+                            #[doc = ""]
+                            #[doc = "Succeeds if the collection does not exist."]
+                            #[allow(dead_code)]
+                            pub async fn [<drop_ $coll_name:snake:lower>](&self) -> $crate::mongodb::error::Result<()> {
+                                self.database
+                                    .collection::<$crate::mongodb::bson::document::Document>(<schema::$coll_name as $crate::MongoCollection>::NAME)
+                                    .drop(std::option::Option::None)
+                                    .await?;
+                                $crate::expand_audit! {
+                                    $audit self.database, "drop", <schema::$coll_name as $crate::MongoCollection>::NAME, $crate::mongodb::bson::Bson::Null
+                                }
+                                $crate::mongodb::error::Result::Ok(())
+                            }
+
+                            #[doc = "Returns the `" $coll_name "` document matching `filter`, inserting `default` first if there is none, see `" $coll_name "Repository::get_or_insert`."]
+                            #[allow(dead_code)]
+                            pub async fn [<get_or_insert_ $coll_name:snake:lower>](
+                                &self,
+                                filter: $crate::mongodb::bson::document::Document,
+                                default: schema::$coll_name,
+                            ) -> $crate::mongodb::error::Result<schema::$coll_name> {
+                                self.[<$coll_name:snake:lower>]().get_or_insert(filter, default).await
+                            }
+
+                            #[doc = "Deletes all documents in the `" $coll_name "` collection matching `filter`, see `" $coll_name "Repository::delete_many`."]
+                            #[doc = ""]
+                            #[doc = "An empty `filter` deletes all documents of the collection."]
+                            #[allow(dead_code)]
+                            pub async fn [<delete_ $coll_name:snake:lower>](
+                                &self,
+                                filter: $crate::mongodb::bson::document::Document,
+                            ) -> $crate::mongodb::error::Result<$crate::mongodb::results::DeleteResult> {
+                                self.[<$coll_name:snake:lower>]().delete_many(filter).await
+                            }
+
+                            #[doc = "Applies `update` to all documents in the `" $coll_name "` collection matching `filter`, see `" $coll_name "Repository::update_many`."]
+                            #[allow(dead_code)]
+                            pub async fn [<update_ $coll_name:snake:lower>](
+                                &self,
+                                filter: $crate::mongodb::bson::document::Document,
+                                update: $crate::mongodb::bson::document::Document,
+                            ) -> $crate::mongodb::error::Result<$crate::mongodb::results::UpdateResult> {
+                                self.[<$coll_name:snake:lower>]().update_many(filter, update).await
+                            }
+                        }
+
+                        $crate::expand_change_streams! {
+                            #[doc = "Opens a change stream on the `" $coll_name "` collection, see `" $coll_name "Repository::watch`."]
+                            #[allow(dead_code)]
+                            pub async fn [<watch_ $coll_name:snake:lower>](
+                                &self,
+                                pipeline: impl std::iter::IntoIterator<Item = $crate::mongodb::bson::document::Document>,
+                                options: impl std::convert::Into<std::option::Option<$crate::mongodb::options::ChangeStreamOptions>>,
+                            ) -> $crate::mongodb::error::Result<
+                                $crate::mongodb::change_stream::ChangeStream<
+                                    $crate::mongodb::change_stream::event::ChangeStreamEvent<schema::$coll_name>
+                                >
+                            > {
+                                self.[<$coll_name:snake:lower>]().watch(pipeline, options).await
+                            }
+
+                            #[doc = "Opens a change stream on the `" $coll_name "` collection that starts after `resume_after`, see `" $coll_name "Repository::watch_from`."]
+                            #[allow(dead_code)]
+                            pub async fn [<watch_ $coll_name:snake:lower _from>](
+                                &self,
+                                resume_after: std::option::Option<$crate::mongodb::change_stream::event::ResumeToken>,
+                            ) -> $crate::mongodb::error::Result<
+                                $crate::mongodb::change_stream::ChangeStream<
+                                    $crate::mongodb::change_stream::event::ChangeStreamEvent<schema::$coll_name>
+                                >
+                            > {
+                                self.[<$coll_name:snake:lower>]().watch_from(resume_after).await
+                            }
+                        }
+                    )+
+                }
+                $crate::expand_shutdown_on_drop! { $shutdown $db_name }
+                $(
+                    impl $db_name {
+                        $($impl)+
+                    }
+                )?
+            }
+        }
+    };
+}
+
+/// Model a mongodb database.
+///
+/// This macro creates structs / functions / constants / modules that represent a mongoDB database.
+/// Being a macro (which is expanded at compile time) there is no run time performance penalty when using this macro.
+///
+/// For a detailled syntax demonstration see [Examples](#examples).
+///
+/// # Structure
+///
+/// This macro wraps everything in a module called `mongo`.
+///
+/// The main database handler has the following attributes:
+/// - Its name represents the database's name (eg. a database named `MyDatabase` has a struct `mongo::MyDatabase`).
+/// - It implements the [`MongoClient`] trait.
+/// - It contains handles to all given collections inside the database.
+///   These handles have the format `{collection_name}_coll` where `{collection_name}` represents the collection's name in `snake_case`.
+///   Acronyms are kept together and digits stick to the preceding word, eg. `HTTPCache` has a handle `http_cache_coll` and `My2ndCollection` has a handle `my2nd_collection_coll`.
+/// - It also contains a [`client`](mongodb::Client) and a [`database`](mongodb::Database) field for you to use.
+/// - [`MongoClient::COLLECTION_NAMES`] lists the names of all collections in declaration order, eg. for generic setup and teardown code, views are not included.
+/// - [`MongoClient::reconnect`] replaces the client, the database and all collection handles in place, eg. after rotating credentials.
+/// - It has a method `latest_{collection_name}` for each collection that returns the most recent document, see [Latest documents](#latest-documents).
+/// - It has a method `aggregate_{collection_name}` for each collection that runs an aggregation pipeline, see [`Pipeline`].
+/// - It has a method `{collection_name}_stats` for each collection that returns the collection's statistics using [`MongoClient::collection_stats`].
+/// - It has a method `{collection_name}_namespace` for each collection that returns the collection's namespace `{database}.{collection}` using [`MongoClient::namespace`].
+/// - It has a method `{collection_name}_is_empty` for each collection that checks whether the collection contains no documents, which is cheaper than counting them.
+/// - It has a method `insert_{collection_name}` for each collection that inserts a single document and returns the inserted `_id`, documents without `_id` get one from the collection's generator or counter first.
+/// - It has a method `create_{collection_name}_indexes` for each collection that creates the indexes of this collection, see [Indexes next to the collection](#indexes-next-to-the-collection).
+/// - It has a method `drop_{collection_name}` for each collection that drops the collection, eg. to tear down integration tests, [`MongoClient::drop_database`] drops the whole database.
+/// - It has a method `find_one_{collection_name}_as` for each collection that finds a single document and deserializes it into another type, eg. a read model with fewer fields, see [`MongoClient::find_one_as`].
+/// - It has a method `find_{collection_name}_by_id` for each collection with an `_id` field that finds a single document by its `_id`.
+/// - It has a method `replace_{collection_name}_with` for each collection with an `_id` field that replaces a single document by its `_id` with per call [`ReplaceOptions`](mongodb::options::ReplaceOptions), eg. to upsert or to set a write concern.
+/// - It has a method `delete_{collection_name}_by_id` for each collection with an `_id` field that deletes a single document by its `_id`.
+/// - It has a method `{collection_name}_exists` for each collection that checks whether the collection exists using [`MongoClient::collection_exists`].
+/// - It has a method `{collection_name}` for each collection that returns the collection's repository, see [Repositories](#repositories).
+/// - It has a method `{collection_name}_handle` for each collection that returns an owned clone of the collection handle, eg. to move it into a spawned task.
+/// - It contains a read-only handle `{view_name}_view` for each view, see [Views](#views).
+///
+/// All collections are wrapped in an additional public module named `schema`.
+///
+/// Each collection has its own struct which stores all specified fields.
+/// All collection structs implement [`Serialize`](serde::Serialize), [`Deserialize`](serde::Deserialize) and [`MongoCollection`].
+/// They also implement [`TryFrom<Document>`](std::convert::TryFrom) to convert raw documents, eg. from aggregations, without importing bson functions.
+/// Rows of other databases can be converted with their `from_map`, see [Migrating rows](#migrating-rows).
+/// Single fields of raw documents can be read with their `read_field`, see [Reading single fields of large documents](#reading-single-fields-of-large-documents).
+/// Validator field attributes are checked by [`MongoCollection::validate`], see [Validation](#validation).
+///
+/// By default a field `_id` gets added to each collection automatically:
+///     `pub _id: Option<DefaultId>` (see [`DefaultId`] for more info).
+/// This field needs to exist for you to be able to obtain an `_id` field from the database.
+/// When serializing, `_id` gets skipped if it is [`None`].
+/// When deserializing, `_id` is read from an ObjectId, a hex string or an extended JSON object `{ "$oid": ... }`, eg. when importing data, see [`object_id`].
+/// Collections with an `_id` field implement [`MongoCollectionId`], which names the type of the `_id`.
+/// All fields except `_id` get renamed to `camelCase` when serializing (converting `_id` to `camelCase` results in `id`), unless the collection declares another case, see [Other cases of field names](#other-cases-of-field-names).
+/// This does not affect the types of the fields: variants of an enum field keep their names unless the enum has its own `#[serde(rename_all = "...")]`.
+///
+/// _Note_: All structs' names in `camelCase` can be accessed via the [`MongoClient`] / [`MongoCollection`] trait.
+///
+/// # Examples
+///
+/// ## General Examples
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection, DefaultId};
+/// use serde_json::ser;
+///
+/// mongo_db! {
+///     // database name
+///     SomeDatabase {
+///         // additional attributes for the collection
+///         #[derive(Debug, Clone)]
+///         // collection name
+///         SomeCollection {
+///             // collection fields
+///             first_name: String,
+///         }
+///     }
+/// }
+///
+/// let mut some_document = mongo::schema::SomeCollection {
+///     _id: None,
+///     first_name: String::from("alice")
+/// };
+///
+/// // When serializing, `_id` is skipped only if it is `None`.
+/// // Note the key conversion to `camelCase`.
+/// assert_eq!(
+///     ser::to_string(&some_document).unwrap(),
+///     String::from("{\"firstName\":\"alice\"}")
+/// );
+///
+/// // update `_id` field to include in serialization.
+/// let oid = DefaultId::parse_str("0123456789ABCDEF01234567").unwrap();
+/// some_document._id = Some(oid);
+/// assert_eq!(
+///     ser::to_string(&some_document).unwrap(),
+///     String::from("{\"_id\":{\"$oid\":\"0123456789abcdef01234567\"},\"firstName\":\"alice\"}")
+/// );
+///
+/// // constants store the collection / database names in `camelCase` + collection version
+/// assert_eq!("someCollection", mongo::schema::SomeCollection::NAME);
+/// assert_eq!(1, mongo::schema::SomeCollection::SCHEMA_VERSION);
+/// assert_eq!("someDatabase", mongo::SomeDatabase::NAME);
+/// ```
+///
+/// Multiple collections need to be separated by `;`, a trailing `;` is optional:
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoCollection, MongoClient};
+///
+/// mongo_db! {
+///     #[derive(Debug, Clone)]
+///     MyDatabase {
+///         #[derive(Debug, Clone)]
+///         MyFirstCollection {
+///             first_name: String,
+///             last_name: String,
+///             age: u8,
+///         };
+///         #[derive(Debug)]
+///         AnotherCollection {
+///             some_field: String
+///         };
+///     }
+/// }
+///
+/// // all constants that were defined
+/// assert_eq!("myDatabase", mongo::MyDatabase::NAME);
+/// assert_eq!("myFirstCollection", mongo::schema::MyFirstCollection::NAME);
+/// assert_eq!(1, mongo::schema::MyFirstCollection::SCHEMA_VERSION);
+/// assert_eq!("anotherCollection", mongo::schema::AnotherCollection::NAME);
+/// assert_eq!(1, mongo::schema::AnotherCollection::SCHEMA_VERSION);
+///
+/// // initializer function and general usage
+/// // note that `tokio_test::block_on` is just a test function to run `async` code in doc tests
+///
+/// let mongo = tokio_test::block_on(mongo::MyDatabase::new("mongodb://example.com"))
+///     .expect("Could not create mongoDB client");
+///
+/// let bob = mongo::schema::MyFirstCollection {
+///     _id: None,
+///     first_name: String::from("Bob"),
+///     last_name: String::from("Bob's last name"),
+///     age: 255,
+/// };
+///
+/// // This should fail beause there is no actual mongoDB service running at the specified
+/// // connection.
+/// assert!(tokio_test::block_on(
+///     mongo.my_first_collection_coll.insert_one(bob, None)
+/// ).is_err());
+/// ```
+///
+/// ## Casing of names
+///
+/// Two different casing engines are involved when converting names to `camelCase`:
+/// - Fields are renamed by serde's `#[serde(rename_all = "camelCase")]`.
+///   All keys this macro builds from field names, eg. index keys, partial filters and the filters of `find_by_{field_name}`, are converted by [`serde_case!`] which applies the exact same rule.
+///   Thus an index always covers the key the field is actually stored as.
+/// - Names of collections, views and databases are types, which serde does not rename.
+///   They are converted by [`case!`], which uses [`convert_case`](https://docs.rs/convert_case).
+///
+/// Both engines agree on common field names, eg. `user_id` => `userId`, `area51_code` => `area51Code` and `r#type` => `type`.
+/// They differ on words that are not separated by underscores:
+///
+/// | Name            | serde (fields)   | [`case!`] (collections) |
+/// |-----------------|------------------|-------------------------|
+/// | `user_URL`      | `userURL`        | `userUrl`               |
+/// | `md5sum`        | `md5sum`         | `md5Sum`                |
+/// | `HTTPRequest`   | -                | `httpRequest`           |
+/// | `MyURLs`        | -                | `myUrLs`                |
+///
+/// Since fields are always converted by serde's rules, only the names of collections are affected.
+/// Check [`MongoCollection::NAME`] when naming a collection with acronyms or digits, eg. to match an existing collection.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection};
+/// use mongodb::bson::doc;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         #[derive(Debug)]
+///         HTTPRequest {
+///             md5sum: String,
+///         };
+///
+///         @indexes {
+///             HTTPRequest: [md5sum unique];
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::HTTPRequest;
+///
+/// assert_eq!(HTTPRequest::NAME, "httpRequest");
+///
+/// let request = HTTPRequest { _id: None, md5sum: String::from("d41d8cd98f00b204e9800998ecf8427e") };
+/// let document = mongodb::bson::to_document(&request).unwrap();
+/// assert!(document.contains_key("md5sum"));
+///
+/// let models = mongo::SomeDatabase::index_models();
+/// assert_eq!(models[0].1.keys, doc! { "md5sum": 1 });
+///
+/// assert_eq!(HTTPRequest::field_mapping(), vec![("_id", "_id"), ("md5sum", "md5sum")]);
+/// ```
+///
+/// The generated `field_mapping()` of each collection lists all fields with the keys they are stored as,
+/// which allows to pin the stored schema in a test and catch accidental renames.
+/// A `rename` is taken into account in combined attributes too, eg. `#[serde(default, rename = "x")]`, fields with `#[serde(flatten)]`, `#[serde(skip)]` or `#[serde(skip_serializing)]` are left out.
+///
+/// ### Other cases of field names
+///
+/// Collections of existing databases often store their fields in another case.
+/// The `rename_all: {case}` parameter stores the fields of a collection in the given case instead of `camelCase`,
+/// where `{case}` is one of `camel`, `snake`, `pascal`, `kebab`, `lower`, `upper`, `screaming_snake` and `screaming_kebab`.
+///
+/// All keys built from field names follow the collection's case, eg. index keys, `order_by` and the keys of `field_key`.
+/// The case is returned by [`MongoCollection::RENAME_ALL`].
+/// Fields of a [shared base](#shared-base-fields) keep the case of their base struct,
+/// and the `deletedAt` field of [soft deletes](#soft-deletes) is always stored as `deletedAt`.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection};
+/// use mongodb::bson::doc;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         LegacyUser<rename_all: snake, order_by: [last_login desc]> {
+///             first_name: String,
+///             last_login: i64,
+///         };
+///
+///         @indexes {
+///             LegacyUser: [first_name unique];
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::LegacyUser;
+///
+/// assert_eq!(LegacyUser::RENAME_ALL, "snake_case");
+///
+/// let user = LegacyUser::builder().first_name(String::from("Alice")).last_login(0).build();
+/// let document = mongodb::bson::to_document(&user).unwrap();
+/// assert_eq!(document, doc! { "first_name": "Alice", "last_login": 0_i64 });
+///
+/// assert_eq!(LegacyUser::order_by(), Some(doc! { "last_login": -1 }));
+/// assert_eq!(mongo::SomeDatabase::index_models()[0].1.keys, doc! { "first_name": 1 });
+/// ```
+///
+/// `rename_all: none` leaves out the `#[serde(rename_all = "...")]` attribute, fields are then stored exactly as they are written
+/// and [`MongoCollection::RENAME_ALL`] returns `"none"`.
+///
+/// ## Manipulating / Removing `_id`
+///
+/// You can specify any type (that implements [`Serialize`](serde::Serialize) and [`Deserialize`](serde::Deserialize)) to be used inside the `_id` [`Option`] by specifying it in `<` / `>` after the collection name:
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         SomeCollection<_id: u128> {
+///             first_name: String,
+///         }
+///     }
+/// }
+///
+/// // _id is now `u128` instead of `DefaultId`
+/// let some_document = mongo::schema::SomeCollection {
+///     _id: Some(255),
+///     first_name: String::from("Bob")
+/// };
+/// ```
+///
+/// It is also possible to disable the generation of an `_id` field all together by using `<_id: none>`.
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         SomeCollection<_id: none> {
+///             #[serde(skip_serializing_if = "Option::is_none")]
+///             email_address: Option<String>,
+///             first_name: String,
+///         }
+///     }
+/// }
+///
+/// // no `_id` exists, this example assumes that users are addressed via their email address
+/// let some_document = mongo::schema::SomeCollection {
+///     email_address: Some(String::from("bob@example.com")),
+///     first_name: String::from("Bob")
+/// };
+/// ```
+///
+/// These features are unique for each collection:
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, DefaultId};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         SomeCollection<_id: u128> {
+///             first_name: String,
+///         };
+///         Another {
+///             some_field: u32,
+///         };
+///         AndYetAnother<_id: none> {
+///             email: String,
+///             name: String,
+///         }
+///     }
+/// }
+///
+/// // `_id` type changed to `u128`
+/// let some_document = mongo::schema::SomeCollection {
+///     _id: Some(255),
+///     first_name: String::from("Bob")
+/// };
+/// // `_id` type default, eg. `DefaultId`
+/// let oid = DefaultId::parse_str("0123456789ABCDEF01234567").unwrap();
+/// let another_document = mongo::schema::Another {
+///     _id: Some(oid),
+///     some_field: 1,
+/// };
+/// // `_id` field disabled
+/// let and_yet_another_document = mongo::schema::AndYetAnother {
+///     name: String::from("Bob"),
+///     email: String::from("bob@example.com")
+/// };
+/// ```
+///
+/// Each collection that does not have a parameter of `id: none` implements a function `id(&self)` that returns a reference to its ID:
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, DefaultId};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         SomeCollection<_id: u128> {};
+///         Another {};
+///     }
+/// }
+///
+/// // `id` returns `&Option<u128>`
+/// let some_collection = mongo::schema::SomeCollection {
+///     _id: Some(255),
+/// };
+/// assert_eq!(
+///     *some_collection.id(),
+///     Some(255)
+/// );
+///
+/// // `id` returns `&Option<DefaultId>`
+/// let oid = DefaultId::parse_str("0123456789ABCDEF01234567").unwrap();
+/// let another = mongo::schema::Another {
+///     _id: Some(oid.clone()),
+/// };
+/// assert_eq!(
+///     *another.id(),
+///     Some(oid)
+/// );
+/// ```
+///
+/// Add the `no_accessor` parameter to keep the `_id` field but skip generating `id(&self)`, eg. to define your own `id` method:
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         SomeCollection<_id: u128, no_accessor> {}-{
+///             pub fn id(&self) -> u128 {
+///                 self._id.unwrap_or_default()
+///             }
+///         }
+///     }
+/// }
+///
+/// let some_collection = mongo::schema::SomeCollection {
+///     _id: Some(255),
+/// };
+/// assert_eq!(some_collection.id(), 255);
+/// ```
+///
+/// ### Generating `_id` on the client
+///
+/// For `_id` types that can be generated client-side, a generator function can be specified behind the type with `gen`, eg. `<_id: String gen = generate_id>`.
+/// The function is called with no arguments and must return the `_id` type.
+/// Paths are resolved inside the `schema` module, so either import the function there or use an absolute path.
+///
+/// Each collection with a generator implements a method `ensure_id(&mut self)` that calls the generator only if `_id` is [`None`]:
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         {
+///             pub fn generate_id() -> String {
+///                 String::from("01ARZ3NDEKTSV4RRFFQ69G5FAV")
+///             }
+///         }
+///         SomeCollection<_id: String gen = generate_id> {
+///             first_name: String,
+///         }
+///     }
+/// }
+///
+/// let mut some_document = mongo::schema::SomeCollection {
+///     _id: None,
+///     first_name: String::from("Bob")
+/// };
+/// assert_eq!(some_document.ensure_id(), "01ARZ3NDEKTSV4RRFFQ69G5FAV");
+///
+/// // an existing `_id` is never overwritten
+/// let mut another_document = mongo::schema::SomeCollection {
+///     _id: Some(String::from("bob")),
+///     first_name: String::from("Bob")
+/// };
+/// assert_eq!(another_document.ensure_id(), "bob");
+/// ```
+///
+/// The generator can also be given as separate `id_gen` parameter, `<_id: String, id_gen: generate_id>` is equivalent to the example above.
+///
+/// Collections with the default `_id` type get `ensure_id` too, it generates a new [`DefaultId`] (see [`ObjectId::new`](mongodb::bson::oid::ObjectId::new)).
+/// This lets the client choose the `_id` before inserting a document:
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         SomeCollection {
+///             first_name: String,
+///         }
+///     }
+/// }
+///
+/// let mut some_document = mongo::schema::SomeCollection {
+///     _id: None,
+///     first_name: String::from("Bob")
+/// };
+/// let id = *some_document.ensure_id();
+/// assert_eq!(some_document._id, Some(id));
+/// ```
+///
+/// ### Sequential integer ids
+///
+/// Collections with an `_id` of type `i32` or `i64` can be declared with the `sequence` parameter to get readable, increasing ids, see [`COUNTERS_COLLECTION`].
+/// The repository of such a collection implements `async fn next_id(&self)` that increments the collection's counter with `$inc` in a single atomic `findOneAndUpdate`.
+/// Concurrent callers thus always get distinct ids, the counters collection and the counter document are created on first use.
+/// This trades an extra round trip per inserted document for readable ids.
+///
+/// Since it writes the counter, `next_id` is not generated for database handlers declared with `<read_only>`.
+///
+/// Note that the counter is independent of the stored documents: inserting documents with other ids does not advance it, and failed inserts leave gaps.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Invoice<_id: i64, sequence> {
+///             amount: u32,
+///         }
+///     }
+/// }
+///
+/// async fn create_invoice(mongo: &mongo::SomeDatabase, amount: u32) -> mongodb::error::Result<i64> {
+///     // `insert` takes the `_id` from the counter, see `assign_id`
+///     let invoice = mongo::schema::Invoice::builder().amount(amount).build();
+///     let result = mongo.invoice().insert(invoice).await?;
+///     Ok(result.inserted_id.as_i64().unwrap_or_default())
+/// }
+/// ```
+///
+/// `sequence` requires an integer `_id`:
+///
+/// ```rust,compile_fail
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Invoice<sequence> {
+///             amount: u32,
+///         }
+///     }
+/// }
+/// ```
+///
+/// ## Versioning of your schema
+///
+/// Your database schema version is managed via [`MongoCollection::SCHEMA_VERSION`].
+///
+/// This can be modified like so:
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoCollection};
+/// use serde_json::ser;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         // no schema version defaults to const `DEFAULT_SCHEMA_VERSION`
+///         Items {
+///             name: String,
+///         };
+///         // schema version of 200
+///         Queue<version: 200> {
+///             item: i32,
+///         };
+///         // schema version of 4
+///         SomeCollection<version: 4, _id: none> {
+///             first_name: String,
+///         };
+///         // schema version of 5
+///         FourthCollection<_id: String, version: 5> {};
+///     }
+/// }
+///
+/// // default schema version is 1
+/// assert_eq!(1, mongodb_ext::DEFAULT_SCHEMA_VERSION);
+///
+/// assert_eq!(mongo::schema::Items::SCHEMA_VERSION, 1);
+/// assert_eq!(mongo::schema::Queue::SCHEMA_VERSION, 200);
+/// assert_eq!(mongo::schema::SomeCollection::SCHEMA_VERSION, 4);
+/// assert_eq!(mongo::schema::FourthCollection::SCHEMA_VERSION, 5);
+/// ```
+///
+/// A human-readable version can be declared with `version_tag` in addition to the numeric version.
+/// It is available as [`MongoCollection::SCHEMA_VERSION_TAG`], which is empty if no tag is declared.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoCollection};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Items<version: 3, version_tag: "v2.1"> {
+///             name: String,
+///         };
+///         Queue {
+///             item: i32,
+///         }
+///     }
+/// }
+///
+/// assert_eq!(mongo::schema::Items::SCHEMA_VERSION, 3);
+/// assert_eq!(mongo::schema::Items::SCHEMA_VERSION_TAG, "v2.1");
+/// assert_eq!(mongo::schema::Queue::SCHEMA_VERSION_TAG, "");
+/// ```
+///
+/// ### Stamping stored versions
+///
+/// Collections that store their schema version in a field named `schema_version` track it: their repository has a method `bump_schema_version(from)`.
+/// It sets `schemaVersion` of all documents stored with version `from` to [`MongoCollection::SCHEMA_VERSION`] using a single `update_many`, which is the last step of most migrations.
+/// Like the other write helpers it is recorded in the [audit log](#audit-log), as `"bump_schema_version"` with the filter, and not generated for database handlers declared with `<read_only>`.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient};
+/// use mongodb::bson::doc;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Users<version: 2> {
+///             schema_version: i32,
+///             full_name: String,
+///         }
+///     }
+/// }
+///
+/// async fn migrate_to_v2(mongo: &mongo::SomeDatabase) -> mongodb::error::Result<()> {
+///     // transform the documents of version 1 first ...
+///     mongo
+///         .update_users(doc! { "schemaVersion": 1 }, doc! { "$rename": { "name": "fullName" } })
+///         .await?;
+///     // ... then stamp the new version
+///     mongo.users().bump_schema_version(1).await?;
+///     Ok(())
+/// }
+/// ```
+///
+/// ### Fields added in later versions
+///
+/// Fields added in a later schema version are missing in documents stored before.
+/// Marking them with `#[versioned_field(since = {version})]` records that contract in the schema:
+/// - Missing values are filled with [`Default::default`] when deserializing, thus older documents can still be read.
+/// - The field is required by the builder and always written, thus new documents are stored with the current version's fields.
+/// - Compilation fails if `{version}` is greater than the collection's [`MongoCollection::SCHEMA_VERSION`], eg. if the version was not raised together with the new field.
+///
+/// The field's type has to implement [`Default`].
+/// Base structs and views have no schema version, thus they cannot declare versioned fields.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoCollection};
+/// use mongodb::bson::{doc, from_document};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         #[derive(Debug)]
+///         Users<version: 3> {
+///             name: String,
+///             #[versioned_field(since = 3)]
+///             email: String,
+///         }
+///     }
+/// }
+///
+/// let user: mongo::schema::Users = from_document(doc! { "name": "alice" }).unwrap();
+/// assert_eq!(user.email, "");
+/// ```
+///
+/// ```rust,compile_fail
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         // the collection's version was not raised
+///         Users<version: 2> {
+///             name: String,
+///             #[versioned_field(since = 3)]
+///             email: String,
+///         }
+///     }
+/// }
+/// ```
+///
+/// ## Default find options
+///
+/// The database handler has a method `find_{collection_name}` for each collection that forwards to [`Collection::find`](mongodb::Collection::find).
+///
+/// Collections that always need the same read behavior can declare default [`FindOptions`](mongodb::options::FindOptions) with the `default_find` parameter.
+/// Each option is the name of a [`FindOptions`](mongodb::options::FindOptions) field followed by its value.
+/// The default options are returned by [`MongoCollection::default_find_options`] and used by `find_{collection_name}` unless other options are given.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection};
+/// use mongodb::bson::doc;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         {
+///             use mongodb::bson::{doc, DateTime};
+///         }
+///         Events<default_find: { sort: doc! { "createdAt": -1 }, limit: 100 }> {
+///             created_at: DateTime,
+///         };
+///         Users {
+///             name: String,
+///         }
+///     }
+/// }
+///
+/// let options = mongo::schema::Events::default_find_options().unwrap();
+/// assert_eq!(options.sort, Some(doc! { "createdAt": -1 }));
+/// assert_eq!(options.limit, Some(100));
+///
+/// assert!(mongo::schema::Users::default_find_options().is_none());
+///
+/// let mongo = tokio_test::block_on(mongo::SomeDatabase::new("mongodb://example.com"))
+///     .expect("Could not create mongoDB client");
+///
+/// // uses the default options
+/// let _newest_events = mongo.find_events(None, None);
+/// // uses the given options instead
+/// let _oldest_events = mongo.find_events(
+///     None,
+///     mongodb::options::FindOptions::builder().sort(doc! { "createdAt": 1 }).build(),
+/// );
+/// ```
+///
+/// ### Exporting
+///
+/// For bulk reads the database handler has a method `export_{collection_name}(batch_size)` that returns a [`Cursor`](mongodb::Cursor) over all documents of the collection.
+/// The cursor implements [`Stream`](https://docs.rs/futures/latest/futures/stream/trait.Stream.html) and fetches `batch_size` documents per round trip to the server, larger batches need fewer round trips but more memory.
+/// The collection's default find options still apply, only their `batch_size` is replaced.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Users {
+///             name: String,
+///         }
+///     }
+/// }
+///
+/// async fn export(mongo: &mongo::SomeDatabase) -> mongodb::error::Result<()> {
+///     let mut users = mongo.export_users(1000).await?;
+///     while users.advance().await? {
+///         let _user: mongo::schema::Users = users.deserialize_current()?;
+///     }
+///     Ok(())
+/// }
+/// ```
+///
+/// ### Counting with options
+///
+/// The database handler has a method `count_{collection_name}_with(filter, options)` that counts the matching documents with the given [`CountOptions`](mongodb::options::CountOptions).
+/// Use it eg. to force an index with [`hint`](mongodb::options::CountOptions::hint) if the query planner picks a bad one.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient};
+/// use mongodb::{bson::doc, options::{CountOptions, Hint}};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Users {
+///             name: String,
+///             active: bool,
+///         }
+///     }
+/// }
+///
+/// async fn count_active(mongo: &mongo::SomeDatabase) -> mongodb::error::Result<u64> {
+///     let options = CountOptions::builder()
+///         .hint(Hint::Name(String::from("active_1")))
+///         .build();
+///     mongo.count_users_with(doc! { "active": true }, options).await
+/// }
+/// ```
+///
+/// ### Aggregating
+///
+/// The database handler has a method `aggregate_{collection_name}(pipeline, options)` that runs an aggregation pipeline on the collection.
+/// The pipeline can be a `Vec<Document>` or a [`Pipeline`], which names each stage by a method and keeps them in order.
+/// Results are plain [`Document`](mongodb::bson::Document)s, since most stages change the shape of the documents.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient, Pipeline};
+/// use mongodb::bson::{doc, Document};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Users {
+///             country: String,
+///             active: bool,
+///         }
+///     }
+/// }
+///
+/// async fn users_per_country(mongo: &mongo::SomeDatabase) -> mongodb::error::Result<Vec<Document>> {
+///     let pipeline = Pipeline::new()
+///         .match_(doc! { "active": true })
+///         .group(doc! { "_id": "$country", "users": { "$sum": 1 } })
+///         .sort(doc! { "users": -1 });
+///     let mut cursor = mongo.aggregate_users(pipeline, None).await?;
+///     let mut countries = Vec::new();
+///     while cursor.advance().await? {
+///         countries.push(cursor.deserialize_current()?);
+///     }
+///     Ok(countries)
+/// }
+/// ```
+///
+/// ### Latest documents
+///
+/// The database handler has a method `latest_{collection_name}` that returns the most recent document of the collection.
+/// The order is declared with the `order_by` parameter, eg. `order_by: [created_at desc]`, and available as [`MongoCollection::order_by`].
+/// Keys are field names that get converted to `camelCase` like the fields themselves, followed by `asc` (default) or `desc`.
+/// Compound sort keys are separated by commas, eg. `order_by: [created_at desc, _id desc]`, a single key can be given without brackets, eg. `order_by: created_at desc`.
+///
+/// Collections without `order_by` fall back to reverse natural order (`{ "$natural": -1 }`).
+/// The server does not guarantee that natural order is the insertion order except for capped collections, so declare `order_by` whenever "latest" matters.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection};
+/// use mongodb::bson::doc;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Events<order_by: [created_at desc, _id desc]> {
+///             kind: String,
+///             created_at: mongodb::bson::DateTime,
+///         }
+///     }
+/// }
+///
+/// assert_eq!(
+///     mongo::schema::Events::order_by(),
+///     Some(doc! { "createdAt": -1, "_id": -1 })
+/// );
+///
+/// async fn last_event(mongo: &mongo::SomeDatabase) -> mongodb::error::Result<Option<String>> {
+///     Ok(mongo.latest_events().await?.map(|event| event.kind))
+/// }
+/// ```
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoCollection};
+/// use mongodb::bson::doc;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Events<order_by: created_at desc> {
+///             created_at: mongodb::bson::DateTime,
+///         }
+///     }
+/// }
+///
+/// assert_eq!(mongo::schema::Events::order_by(), Some(doc! { "createdAt": -1 }));
+/// ```
+///
+/// ## Saving changes
+///
+/// The database handler has a method `save_changes_{collection_name}` for each collection with an `_id` field that updates a stored document to match the given one.
+/// It reads the stored document with the same `_id` first and only sends the changed top level fields in a single update, see [`changes_update`].
+/// Fields missing in the serialized document (eg. [`None`] values of fields declared with `#[null_as(omit)]`) are removed from the stored document with `$unset`.
+/// Note that this removes stored fields the collection does not declare as well.
+/// Nothing is written if no field changed, in this case [`None`] is returned.
+///
+/// This costs an additional read per call, use [`Collection::update_one`](mongodb::Collection::update_one) directly if you know which fields changed.
+/// Saving a document whose `_id` is not set or without a stored document with the same `_id` results in an error.
+/// Collections declared with `<_id: none>` and read-only database handlers do not have this method.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         User {
+///             name: String,
+///             login_count: u32,
+///         }
+///     }
+/// }
+///
+/// let mongo = tokio_test::block_on(mongo::SomeDatabase::new("mongodb://example.com"))
+///     .expect("Could not create mongoDB client");
+///
+/// let mut user = mongo::schema::User::builder()
+///     ._id(Some(mongodb::bson::oid::ObjectId::new()))
+///     .name(String::from("alice"))
+///     .login_count(1)
+///     .build();
+///
+/// user.login_count += 1;
+/// // sends `{ "$set": { "loginCount": 2 } }` if only the login count changed
+/// let _result = mongo.save_changes_user(&user);
+/// ```
+///
+/// ## Upserting many documents
+///
+/// The database handler has a method `upsert_{collection_name}` for each collection with an `_id` field that inserts or replaces a batch of documents by their `_id`.
+/// The documents are sent to the server in unordered `update` commands, which is a lot faster than upserting them one by one.
+/// Each command holds at most [`MAX_WRITE_BATCH_SIZE`] documents and [`MAX_WRITE_BATCH_BYTES`], larger inputs are split into several commands (see [`upsert_many`]).
+///
+/// Every document needs an `_id`, otherwise an error containing a [`String`] message is returned before anything is sent.
+/// The counts and the inserted `_id`s of all commands are summed up in an [`UpsertManyResult`].
+/// If some documents could not be written or a write concern failed, the others are written anyway and an error containing an [`UpsertManyError`] is returned.
+/// Use [`Error::get_custom`](mongodb::error::Error::get_custom) to get the message or the [`UpsertManyError`] out of these errors.
+/// Each written document is recorded in the [audit log](#audit-log) as `"upsert"`, even if writing others failed.
+/// Collections declared with `<_id: none>` and read-only database handlers do not have this method.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         User<_id: String> {
+///             name: String,
+///         }
+///     }
+/// }
+///
+/// let mongo = tokio_test::block_on(mongo::SomeDatabase::new("mongodb://example.com"))
+///     .expect("Could not create mongoDB client");
+///
+/// let users = vec![
+///     mongo::schema::User { _id: Some(String::from("alice")), name: String::from("Alice") },
+///     mongo::schema::User { _id: Some(String::from("bob")), name: String::from("Bob") },
+/// ];
+/// let _result = mongo.upsert_user(users);
+///
+/// // documents without `_id` are rejected before anything is sent
+/// let error = tokio_test::block_on(mongo.upsert_user(vec![
+///     mongo::schema::User { _id: None, name: String::from("Carol") },
+/// ]))
+/// .unwrap_err();
+/// assert_eq!(
+///     error.get_custom::<String>().map(String::as_str),
+///     Some("cannot upsert a document without `_id`")
+/// );
+/// ```
+///
+/// Nothing is sent for an empty batch:
+///
+/// ```rust
+/// # use mongodb_ext::{mongo_db, MongoClient, UpsertManyResult};
+/// # mongo_db! {
+/// #     SomeDatabase {
+/// #         User<_id: String> {
+/// #             name: String,
+/// #         }
+/// #     }
+/// # }
+/// # let mongo = tokio_test::block_on(mongo::SomeDatabase::new("mongodb://example.com"))
+/// #     .expect("Could not create mongoDB client");
+/// let result = tokio_test::block_on(mongo.upsert_user(Vec::new())).unwrap();
+/// assert_eq!(result, UpsertManyResult::default());
+/// ```
+///
+/// ## Getting or inserting a document
+///
+/// The database handler has a method `get_or_insert_{collection_name}(filter, default)` for each collection that returns the document matching `filter`.
+/// If there is none, `default` is inserted and returned.
+/// Both happen atomically in a single `findAndModify` command with `upsert` and `$setOnInsert`, thus concurrent callers do not insert duplicates (as long as a unique index covers `filter`).
+///
+/// Fields of `filter` that test for equality are stored in the inserted document too.
+/// If a document is inserted, the audit log records it as `"insert"` with its `_id`, getting an existing document is not recorded.
+/// Read-only database handlers do not have this method.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient};
+/// use mongodb::bson::doc;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Settings {
+///             user_name: String,
+///             dark_mode: bool,
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::Settings;
+///
+/// async fn settings_of(mongo: &mongo::SomeDatabase, user_name: &str) -> mongodb::error::Result<Settings> {
+///     let default = Settings::builder()
+///         .user_name(user_name.to_string())
+///         .dark_mode(false)
+///         .build();
+///     mongo
+///         .get_or_insert_settings(doc! { "userName": user_name }, default)
+///         .await
+/// }
+/// ```
+///
+/// ## Deserialization errors
+///
+/// Errors of generated helpers that return a single document, eg. `get_or_insert_{collection_name}`, `find_{collection_name}_by_id`, `find_by_{field_name}` or `latest`, name the collection and the `_id` of the offending document, see [`deserialize_document`].
+/// Helpers that return a [`Cursor`](mongodb::Cursor), eg. `find_{collection_name}`, leave deserialization to the cursor, thus use [`deserialize_document`] on a cursor of raw documents if you need the context there.
+///
+/// ```rust
+/// use mongodb_ext::{deserialize_document, mongo_db, MongoCollection};
+/// use mongodb::bson::doc;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         #[derive(Debug)]
+///         Users<_id: i32> {
+///             name: String,
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::Users;
+///
+/// let error = deserialize_document::<Users>(Users::NAME, doc! { "_id": 42 }).unwrap_err();
+/// assert!(error
+///     .to_string()
+///     .contains("failed to deserialize document with `_id` 42 of `users`: missing field `name`"));
+/// ```
+///
+/// ## Reading single fields of large documents
+///
+/// Deserializing a document near the 16MB limit into its collection struct copies all of its fields.
+/// If only a few fields are needed, read the documents as [`RawDocumentBuf`](mongodb::bson::RawDocumentBuf)s instead and deserialize single fields with the generated `read_field(raw, field)` of the collection struct.
+/// It takes the name of the field as declared and looks up the key it is stored as, see `field_key(field)`, thus `#[serde(rename = "...")]` and `_id` are taken into account.
+/// Only the value of that field is deserialized, see [`read_raw_field`].
+///
+/// Flattened and skipped fields are not stored as a top-level key, thus they cannot be read this way.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient};
+/// use mongodb::bson::{doc, RawDocumentBuf};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         #[derive(Debug)]
+///         Reports {
+///             title: String,
+///             #[serde(rename = "data")]
+///             payload: Vec<u8>,
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::Reports;
+///
+/// assert_eq!(Reports::field_key("payload"), Some("data"));
+///
+/// async fn report_titles(mongo: &mongo::SomeDatabase) -> mongodb::error::Result<Vec<String>> {
+///     let mut cursor = mongo
+///         .reports_coll
+///         .clone_with_type::<RawDocumentBuf>()
+///         .find(doc! {}, None)
+///         .await?;
+///     let mut titles = Vec::new();
+///     while cursor.advance().await? {
+///         if let Some(title) = Reports::read_field(cursor.current(), "title")? {
+///             titles.push(title);
+///         }
+///     }
+///     Ok(titles)
+/// }
+/// ```
+///
+/// ## Migrating rows
+///
+/// Each collection has a function `from_map` that builds a document from a `HashMap<String, Bson>`, eg. a row read from a SQL table.
+/// Columns named like a field in `snake_case` are stored under the field's key, ie. converted to `camelCase` or renamed by `#[serde(rename = "...")]`.
+/// All other columns are kept as they are, eg. `_id` or keys of flattened fields.
+/// Columns that are missing for a required field fail with a [`bson::de::Error`](mongodb::bson::de::Error).
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+/// use mongodb::bson::Bson;
+/// use std::collections::HashMap;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Users<_id: i64> {
+///             first_name: String,
+///             #[serde(rename = "mail")]
+///             email: Option<String>,
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::Users;
+///
+/// let mut row = HashMap::new();
+/// row.insert("_id".to_string(), Bson::Int64(7));
+/// row.insert("first_name".to_string(), Bson::from("Alice"));
+/// row.insert("email".to_string(), Bson::Null);
+///
+/// let user = Users::from_map(row).unwrap();
+/// assert_eq!(user._id, Some(7));
+/// assert_eq!(user.first_name, "Alice");
+/// assert_eq!(user.email, None);
+/// ```
+///
+/// ## Updating and deleting many documents
+///
+/// The database handler has a method `update_{collection_name}(filter, update)` for each collection that applies `update` to all documents matching `filter` using [`Collection::update_many`](mongodb::Collection::update_many).
+/// The update document is passed through unmodified, thus you control the update operators (eg. `$set`, `$inc`).
+///
+/// The database handler also has a method `delete_{collection_name}(filter)` for each collection that deletes all documents matching `filter` using [`Collection::delete_many`](mongodb::Collection::delete_many).
+/// **An empty filter deletes all documents of the collection.**
+///
+/// Read-only database handlers do not have these methods.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient};
+/// use mongodb::bson::doc;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Session {
+///             user_name: String,
+///             expired: bool,
+///         }
+///     }
+/// }
+///
+/// async fn expire_sessions(mongo: &mongo::SomeDatabase, user_name: &str) -> mongodb::error::Result<u64> {
+///     let result = mongo
+///         .update_session(doc! { "userName": user_name }, doc! { "$set": { "expired": true } })
+///         .await?;
+///     Ok(result.modified_count)
+/// }
+///
+/// async fn remove_expired(mongo: &mongo::SomeDatabase) -> mongodb::error::Result<u64> {
+///     let result = mongo.delete_session(doc! { "expired": true }).await?;
+///     Ok(result.deleted_count)
+/// }
+/// ```
+///
+/// ## Repositories
+///
+/// Each collection has a repository struct `{CollectionName}Repository` in the `mongo` module that bundles all generated operations on that collection.
+/// The database handler's method named after the collection in `snake_case` returns it, eg. `users()` for a collection `Users`.
+///
+/// Repositories contain the same helpers as the database handler without the collection's name, eg. `find` instead of `find_users`, and give access to the collection handle via `collection()`.
+/// The helpers on the database handler forward to the repository.
+/// Repositories own clones of the database and the collection handle, thus they can be passed around independently of the database handler.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Users {
+///             name: String,
+///         }
+///     }
+/// }
+///
+/// use mongo::{schema::Users, UsersRepository};
+///
+/// async fn rename(users: &UsersRepository, mut user: Users, name: &str) -> mongodb::error::Result<()> {
+///     user.name = name.to_string();
+///     users.save_changes(&user).await?;
+///     Ok(())
+/// }
+///
+/// let mongo = tokio_test::block_on(mongo::SomeDatabase::new("mongodb://example.com"))
+///     .expect("Could not create mongoDB client");
+/// let users: UsersRepository = mongo.users();
+/// assert_eq!(users.collection().name(), "users");
+/// ```
+///
+/// ### Alternate keys
+///
+/// Fields marked with `#[alternate_key]` are unique business keys next to `_id`, eg. a customer number.
+/// Each alternate key gets a unique index, see [`MongoCollection::index_models`], and a method `find_by_{field_name}` on the collection's repository.
+/// `#[unique]` does the same, use whichever name reads better for the field.
+/// [`MongoClient::create_indexes`] creates these indexes together with all others, [`MongoClient::sync_indexes`] creates only the unique indexes of all collections.
+/// Index keys and filters use the field name in `camelCase`, just like the `@indexes` block.
+///
+/// A field is declared unique only once: an alternate key can not be the only key of a unique index in the `@indexes` block or the `indexes` parameter as well.
+/// Both would generate `find_by_{field_name}`, thus such a declaration fails to compile (see the second example).
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection};
+/// use mongodb::bson::doc;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         #[derive(Debug)]
+///         Customers {
+///             #[alternate_key]
+///             customer_id: String,
+///             name: String,
+///         }
+///     }
+/// }
+///
+/// let models = mongo::SomeDatabase::index_models();
+/// assert_eq!(models[0].1.keys, doc! { "customerId": 1 });
+///
+/// async fn find_customer(mongo: &mongo::SomeDatabase) -> mongodb::error::Result<Option<mongo::schema::Customers>> {
+///     mongo.customers().find_by_customer_id(&String::from("C-42")).await
+/// }
+///
+/// async fn setup(mongo: &mongo::SomeDatabase) -> mongodb::error::Result<()> {
+///     mongo.sync_indexes().await
+/// }
+/// ```
+///
+/// ```rust,compile_fail
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Customers {
+///             #[unique]
+///             customer_id: String,
+///         };
+///
+///         // `customer_id` is unique already
+///         @indexes {
+///             Customers: [customer_id unique];
+///         }
+///     }
+/// }
+/// ```
+///
+/// ### Projections into partial structs
+///
+/// The `partial` parameter generates a struct `Partial{CollectionName}` in `schema` that holds all fields of the collection as [`Option`], including `_id`.
+/// Its repository then has a method `find_projected(filter, fields)` that only reads the given `fields` from the server and returns the partial structs.
+/// Fields that are not read are [`None`].
+///
+/// `fields` are the serialized field names, eg. `firstName`, the server always includes `_id`.
+/// The partial struct derives the same attributes as the collection.
+/// Of the field attributes, only `#[serde(rename = "...")]`, `#[serde(flatten)]`, `#[binary]`, `#[date_as_iso]`, `#[as_string]` and `#[as_i64]` are applied to the partial struct.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient};
+/// use mongodb::bson::doc;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         #[derive(Debug)]
+///         Users<partial> {
+///             first_name: String,
+///             last_name: String,
+///             age: u8,
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::PartialUsers;
+///
+/// let partial: PartialUsers = mongodb::bson::from_document(doc! { "firstName": "Alice" }).unwrap();
+/// assert_eq!(partial.first_name.as_deref(), Some("Alice"));
+/// assert_eq!(partial.age, None);
+/// assert_eq!(partial._id, None);
+///
+/// async fn first_names(mongo: &mongo::SomeDatabase) -> mongodb::error::Result<Vec<PartialUsers>> {
+///     mongo.users().find_projected(doc! { "age": { "$gte": 18 } }, &["firstName"]).await
+/// }
+/// ```
+///
+/// ### Soft deletes
+///
+/// The `soft_delete` parameter adds a field `deleted_at: Option<DateTime>` to the collection and sets [`MongoCollection::SOFT_DELETE`].
+/// Instead of removing documents, their `deletedAt` is set to the time of deletion, thus they can be restored later on.
+/// The field defaults to [`None`] in the builder and when deserializing, it is stored as `null` then.
+///
+/// The collection's repository gets two methods that take the `_id` of a document:
+/// - `soft_delete_by_id` sets `deletedAt` to now.
+/// - `restore_by_id` sets `deletedAt` to `null` again.
+///
+/// Both are write helpers: they are recorded as `"soft_delete"` and `"restore"` in the [audit log](#audit-log) and not generated for database handlers declared with `<read_only>`.
+///
+/// Documents are addressed by `_id`, thus `soft_delete` cannot be used together with `_id: none`.
+///
+/// Soft deleted documents are excluded by the generated find and count helpers, ie. `find`, `export` and `count_with` of the repository and `find_{collection_name}`, `export_{collection_name}` and `count_{collection_name}_with` of the database handler.
+/// They add `deletedAt: null` to the filter using [`exclude_soft_deleted`], unless the filter contains `deletedAt` already.
+/// To include soft deleted documents, use the repository's
+/// - `find_with_deleted`, which finds all documents matching the filter like `find` did without `soft_delete`.
+/// - `count_with_deleted`, which counts all documents matching the filter like `count_with` did without `soft_delete`.
+///
+/// Filtering by `deletedAt` explicitly, eg. `doc! { "deletedAt": { "$ne": null } }`, selects soft deleted documents with the default helpers too.
+/// Other helpers, eg. `find_by_{field_name}`, aggregations and the collection handles themselves, are not filtered.
+/// Declare a unique index with a partial filter `deleted_at: null` to enforce uniqueness among the documents that are not deleted, see [Unique among active documents](#unique-among-active-documents).
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection};
+/// use mongodb::{bson::{doc, oid::ObjectId}, options::CountOptions, results::UpdateResult};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         #[derive(Debug)]
+///         Users<soft_delete> {
+///             email: String,
+///         };
+///
+///         @indexes {
+///             Users: [email unique partial { deleted_at: null }];
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::Users;
+///
+/// assert!(Users::SOFT_DELETE);
+/// let user = Users::builder().email(String::from("alice@example.com")).build();
+/// assert_eq!(user.deleted_at, None);
+///
+/// async fn delete_user(mongo: &mongo::SomeDatabase, id: &ObjectId) -> mongodb::error::Result<UpdateResult> {
+///     mongo.users().soft_delete_by_id(id).await
+/// }
+///
+/// async fn count_users(mongo: &mongo::SomeDatabase) -> mongodb::error::Result<(u64, u64)> {
+///     let active = mongo.count_users_with(doc! {}, CountOptions::default()).await?;
+///     let all = mongo.users().count_with_deleted(doc! {}, CountOptions::default()).await?;
+///     Ok((active, all))
+/// }
+/// ```
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, exclude_soft_deleted};
+/// use mongodb::bson::doc;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         #[derive(Debug)]
+///         Users<soft_delete> {
+///             email: String,
+///         };
+///         Sessions {
+///             token: String,
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::{Sessions, Users};
+///
+/// assert_eq!(
+///     exclude_soft_deleted::<Users>(Some(doc! { "email": "alice@example.com" })),
+///     Some(doc! { "email": "alice@example.com", "deletedAt": null })
+/// );
+/// // explicit filters on `deletedAt` are kept
+/// assert_eq!(
+///     exclude_soft_deleted::<Users>(Some(doc! { "deletedAt": { "$ne": null } })),
+///     Some(doc! { "deletedAt": { "$ne": null } })
+/// );
+/// // collections without `soft_delete` are not filtered
+/// assert_eq!(exclude_soft_deleted::<Sessions>(None), None);
+/// ```
+///
+/// ## Round trip tests
+///
+/// The `round_trip` parameter generates a test `{collection_name}_round_trip` in `schema` that serializes [`Default::default`] of the collection into a [`Document`](mongodb::bson::Document), deserializes it again and asserts that nothing changed.
+/// It runs with your crate's own tests (it is only compiled with `#[cfg(test)]`) and catches mistakes in the serde attributes of your schema, eg. a skipped field without default or a rename that does not match an alias.
+///
+/// The collection needs to implement [`Default`], [`PartialEq`] and [`Debug`](std::fmt::Debug), thus the test is opt-in.
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         #[derive(Debug, PartialEq)]
+///         Users<all_default, round_trip> {
+///             name: String,
+///             #[serde(rename = "mail")]
+///             email: Option<String>,
+///         }
+///     }
+/// }
+/// ```
+///
+/// ## Display
+///
+/// The `display` parameter implements [`Display`](std::fmt::Display) for a collection, which is handy for readable log lines.
+/// `display: {field_name}` displays the given field, which needs to implement [`Display`](std::fmt::Display) itself.
+/// `display` without a field name displays the `_id` field, or `None` if it is not set, thus it cannot be used together with `_id: none`.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, DefaultId};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         User<display: name> {
+///             name: String,
+///             password_hash: String,
+///         };
+///         Order<display> {
+///             total: u64,
+///         }
+///     }
+/// }
+///
+/// let user = mongo::schema::User::builder()
+///     .name(String::from("alice"))
+///     .password_hash(String::from("secret"))
+///     .build();
+/// assert_eq!(user.to_string(), "alice");
+///
+/// let mut order = mongo::schema::Order::builder().total(1).build();
+/// assert_eq!(order.to_string(), "None");
+/// order._id = Some(DefaultId::parse_str("0123456789ABCDEF01234567").unwrap());
+/// assert_eq!(order.to_string(), "0123456789abcdef01234567");
+/// ```
+///
+/// ```rust,compile_fail
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         // there is no `_id` to display
+///         Event<display, _id: none> {
+///             name: String,
+///         }
+///     }
+/// }
+/// ```
+///
+/// ## Ordering in memory
+///
+/// The `order_key: {field_name}` parameter implements [`Ord`] for a collection by comparing the given field, eg. to sort fetched documents or to push them into a [`BinaryHeap`](std::collections::BinaryHeap).
+/// The field can be given by its name or by its stored name in `camelCase`, its type needs to implement [`Ord`] itself.
+///
+/// [`PartialEq`], [`Eq`] and [`PartialOrd`] are implemented by comparing the field as well, thus two documents with the same key are equal.
+/// Do not derive [`PartialEq`] for such collections.
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         {
+///             use mongodb::bson::DateTime;
+///         }
+///         Event<order_key: createdAt> {
+///             name: String,
+///             created_at: DateTime,
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::Event;
+///
+/// let event = |name: &str, millis| {
+///     Event::builder()
+///         .name(String::from(name))
+///         .created_at(mongodb::bson::DateTime::from_millis(millis))
+///         .build()
+/// };
+/// let mut events = vec![event("second", 2), event("third", 3), event("first", 1)];
+/// events.sort();
+/// let names: Vec<&str> = events.iter().map(|event| event.name.as_str()).collect();
+/// assert_eq!(names, ["first", "second", "third"]);
+///
+/// let mut heap: std::collections::BinaryHeap<Event> = events.into_iter().collect();
+/// assert_eq!(heap.pop().unwrap().name, "third");
+/// ```
+///
+/// ## OpenAPI schemas
+///
+/// With the _"utoipa"_ feature enabled, the `to_schema` parameter derives [`ToSchema`](utoipa::ToSchema) for a collection, next to the derives of this macro.
+/// The derived code refers to `utoipa` directly, thus the crate calling [`mongo_db!`] needs `utoipa` (version 5) as a dependency too.
+///
+/// Every field type needs a schema, which bson types like [`DateTime`](mongodb::bson::DateTime) do not have.
+/// Set the schema of such fields with utoipa's `#[schema(value_type = ...)]` attribute.
+/// Fields added by this macro, ie. the default `_id` and the `deleted_at` field of `soft_delete`, are documented as objects.
+/// Flattened base structs need their own `#[derive(utoipa::ToSchema)]`.
+///
+/// `to_schema` is opt-in per collection, so other collections can keep fields without a schema.
+///
+/// ```rust
+/// #[cfg(feature = "utoipa")]
+/// mod api {
+///     use mongodb_ext::mongo_db;
+///
+///     mongo_db! {
+///         MyDatabase {
+///             Users<to_schema> {
+///                 name: String,
+///                 #[schema(value_type = String)]
+///                 created_at: mongodb::bson::DateTime,
+///             };
+///             // no `to_schema`, thus no `ToSchema`
+///             Sessions {
+///                 created_at: mongodb::bson::DateTime,
+///             }
+///         }
+///     }
+/// }
+///
+/// #[cfg(feature = "utoipa")]
+/// {
+///     use utoipa::ToSchema;
+///
+///     assert_eq!(api::mongo::schema::Users::name(), "Users");
+/// }
+/// ```
+///
+/// ## Conditional attributes
+///
+/// Attributes of a collection are placed after the derives of the macro, which are always `#[derive(Deserialize, Serialize, TypedBuilder)]`.
+/// Thus conditional attributes like `#[cfg_attr(test, derive(PartialEq))]` compose with them, eg. to derive traits only in tests.
+/// They are passed on to `Partial{CollectionName}` as well, see [Projections into partial structs](#projections-into-partial-structs).
+///
+/// Note that
+/// - `Deserialize`, `Serialize` and `TypedBuilder` must not be derived again, not even conditionally.
+/// - this crate's field attributes, eg. `#[sensitive]`, are consumed by the macro before `cfg_attr` is evaluated, thus they cannot be wrapped in `cfg_attr`.
+///   Attributes of other crates, eg. `#[cfg_attr(test, serde(default))]`, can.
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         #[derive(Debug, Clone)]
+///         #[cfg_attr(test, derive(PartialEq))]
+///         Users {
+///             name: String,
+///         }
+///     }
+/// }
+///
+/// let user = mongo::schema::Users { _id: None, name: String::from("alice") };
+/// assert_eq!(user.clone().name, user.name);
+/// ```
+///
+/// ## Rejecting unknown fields
+///
+/// By default unknown fields are ignored when deserializing, which keeps old binaries working with newer documents.
+/// The `deny_unknown_fields` parameter adds `#[serde(deny_unknown_fields)]` to a collection to catch typos and schema drift instead.
+///
+/// The generated `_id` field is a known field, thus documents containing `_id` still deserialize.
+/// Note that mongoDB always stores an `_id`, so fetching documents into a collection declared with `<_id: none, deny_unknown_fields>` fails.
+/// Also note that [serde does not support](https://serde.rs/container-attrs.html#deny_unknown_fields) this attribute in combination with `#[serde(flatten)]`.
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+/// use mongodb::bson::{de::from_document, doc};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         #[derive(Debug)]
+///         SomeCollection<deny_unknown_fields> {
+///             first_name: String,
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::SomeCollection;
+///
+/// assert!(from_document::<SomeCollection>(doc! {
+///     "_id": mongodb::bson::oid::ObjectId::new(),
+///     "firstName": "Bob"
+/// }).is_ok());
+///
+/// // note the typo
+/// assert!(from_document::<SomeCollection>(doc! {
+///     "fistName": "Bob"
+/// }).is_err());
+/// ```
+///
+/// ## Null handling
+///
+/// Queries distinguish between a field that is `null` and a field that is absent: `{ field: { $exists: false } }` only matches the latter.
+/// The field attributes `#[null_as(omit)]` and `#[null_as(null)]` control how a field of type [`Option`] is written if it is [`None`]:
+///
+/// - `#[null_as(omit)]` leaves the field out of the document.
+/// - `#[null_as(null)]` writes BSON `null`, which is what serde does without any attribute. Use it to make that choice explicit.
+///
+/// Without either attribute the current behavior is kept: a generated `_id` that is [`None`] is always omitted, all other fields are written as `null`.
+/// Both attributes are consumed by [`mongo_db!`], all other field attributes are passed through unchanged.
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+/// use mongodb::bson::{doc, ser::to_document};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         SomeCollection {
+///             #[null_as(omit)]
+///             nickname: Option<String>,
+///             #[null_as(null)]
+///             deleted_at: Option<i64>,
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::SomeCollection;
+///
+/// let document = to_document(&SomeCollection::builder().nickname(None).deleted_at(None).build()).unwrap();
+/// assert_eq!(document, doc! { "deletedAt": null });
+/// ```
+///
+/// The `omit_none` collection parameter marks every field of type [`Option`] with `#[null_as(omit)]`, which keeps stored documents lean.
+/// Fields are recognized by the spelling of their type, since macros cannot resolve types.
+/// Supported are `Option<T>`, `std::option::Option<T>` and `core::option::Option<T>`, optionally with a leading `::`.
+/// Type aliases of [`Option`] are not recognized, mark such fields with `#[null_as(omit)]` yourself.
+///
+/// Fields with a `null_as` or `skip_serializing_if` attribute keep it, thus `#[null_as(null)]` opts a field out.
+/// The `deleted_at` field of `soft_delete` is always written as `null`, so unique indexes can filter on it, see [Unique among active documents](#unique-among-active-documents).
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+/// use mongodb::bson::{doc, ser::to_document};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         SomeCollection<_id: none, omit_none> {
+///             nickname: Option<String>,
+///             email: std::option::Option<String>,
+///             #[null_as(null)]
+///             deleted_at: Option<i64>,
+///             name: String,
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::SomeCollection;
+///
+/// let document = SomeCollection::builder()
+///     .nickname(None)
+///     .email(None)
+///     .deleted_at(None)
+///     .name("Alice".to_string())
+///     .build();
+/// assert_eq!(to_document(&document).unwrap(), doc! { "deletedAt": null, "name": "Alice" });
+/// ```
+///
+/// ## Sensitive fields
+///
+/// Fields marked with `#[sensitive]` are redacted in the collection's [`Debug`](std::fmt::Debug) output, they print as `"<redacted>"`.
+/// This keeps personal data out of logs that print whole documents.
+///
+/// Collections with sensitive fields implement [`Debug`](std::fmt::Debug) themselves, thus they must not derive it.
+/// All other fields print as if it was derived.
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Users<_id: none> {
+///             name: String,
+///             #[sensitive]
+///             email: String,
+///         }
+///     }
+/// }
+///
+/// let user = mongo::schema::Users {
+///     name: String::from("alice"),
+///     email: String::from("alice@example.com"),
+/// };
+/// assert_eq!(
+///     format!("{:?}", user),
+///     r#"Users { name: "alice", email: "<redacted>" }"#
+/// );
+/// ```
+///
+/// ## Computed fields
+///
+/// Fields that are computed by an aggregation, eg. in a `$project` stage, but never stored can be marked with `#[computed]`.
+/// Computed fields are never serialized, thus neither the generated helpers nor [`Collection`](mongodb::Collection) methods like `insert_one` persist them.
+/// They are deserialized if present and fall back to [`Default::default`] otherwise, eg. when reading stored documents with a plain `find`.
+/// The builder does not require them either.
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+/// use mongodb::bson::{de::from_document, doc, ser::to_document};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         Users<_id: none> {
+///             first_name: String,
+///             last_name: String,
+///             #[computed]
+///             full_name: String,
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::Users;
+///
+/// let user = Users::builder().first_name("Ada".into()).last_name("Lovelace".into()).build();
+/// assert_eq!(to_document(&user).unwrap(), doc! { "firstName": "Ada", "lastName": "Lovelace" });
+///
+/// // eg. the output of `{ $addFields: { fullName: { $concat: ["$firstName", " ", "$lastName"] } } }`
+/// let projected: Users = from_document(doc! {
+///     "firstName": "Ada",
+///     "lastName": "Lovelace",
+///     "fullName": "Ada Lovelace",
+/// }).unwrap();
+/// assert_eq!(projected.full_name, "Ada Lovelace");
+/// ```
+///
+/// ## Dates as ISO strings
+///
+/// [`DateTime`](mongodb::bson::DateTime) fields serialize as extended JSON, eg. `{"$date": {"$numberLong": "0"}}`, which JSON consumers rarely expect.
+/// Fields of type [`DateTime`](mongodb::bson::DateTime) or `Option<DateTime>` with the field attribute `#[date_as_iso]` serialize as ISO-8601 strings in human-readable formats like JSON instead.
+/// The driver does not use a human-readable format, thus these fields are still stored as BSON dates.
+///
+/// Both ISO-8601 strings and BSON dates are accepted when deserializing, see [`iso_date`] for details.
+/// Like with any serde `with` attribute, add `#[serde(default)]` to optional fields if missing fields should deserialize as [`None`].
+///
+/// _Note_: [`to_document`](mongodb::bson::to_document) is human-readable, use [`to_raw_document_buf`](mongodb::bson::to_raw_document_buf) to get BSON dates.
+/// The helpers generated on the database handler do that already.
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+/// use mongodb::bson::{rawdoc, to_raw_document_buf, DateTime};
+/// use serde_json::json;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         {
+///             use mongodb::bson::DateTime;
+///         }
+///         Events<_id: none> {
+///             #[date_as_iso]
+///             created_at: DateTime,
+///         }
+///     }
+/// }
+///
+/// let event = mongo::schema::Events {
+///     created_at: DateTime::from_millis(0),
+/// };
+///
+/// assert_eq!(
+///     serde_json::to_value(&event).unwrap(),
+///     json!({ "createdAt": "1970-01-01T00:00:00Z" })
+/// );
+/// assert_eq!(
+///     to_raw_document_buf(&event).unwrap(),
+///     rawdoc! { "createdAt": DateTime::from_millis(0) }
+/// );
+/// ```
+///
+/// ## Defaulting missing fields
+///
+/// Deserializing a document fails if a field is missing, unless the field has a default, eg. `#[serde(default)]`.
+/// The `all_default` parameter derives [`Default`] for a collection and adds `#[serde(default)]` to the collection struct, thus every missing field is taken from [`Default::default`].
+/// This suits schemas that only ever get new fields, as documents written before a field was added still deserialize.
+///
+/// All field types need to implement [`Default`], per-field defaults like `#[serde(default = "...")]` still take precedence.
+/// Do not derive [`Default`] for such a collection yourself, implement it manually instead of using `all_default` if you need custom defaults for the whole struct.
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+/// use mongodb::bson::{de::from_document, doc};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         {
+///             pub fn default_limit() -> u32 { 10 }
+///         }
+///         #[derive(Debug, PartialEq)]
+///         SomeCollection<all_default> {
+///             name: String,
+///             tags: Vec<String>,
+///             #[serde(default = "default_limit")]
+///             limit: u32,
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::SomeCollection;
+///
+/// let sparse: SomeCollection = from_document(doc! { "name": "Bob" }).unwrap();
+/// assert_eq!(sparse.name, "Bob");
+/// assert!(sparse.tags.is_empty());
+/// assert_eq!(sparse.limit, 10);
+/// ```
+///
+/// ## Binary data
+///
+/// serde serializes `Vec<u8>` as an array of numbers, which wastes space and is rarely what other clients expect.
+/// Fields of type `Vec<u8>` or `Option<Vec<u8>>` with the field attribute `#[binary]` are serialized as BSON binary of subtype [`Generic`](binary::BinarySubtype::Generic) instead.
+/// Both binaries and arrays of numbers are accepted when deserializing, thus existing documents stay readable.
+///
+/// Use [`Binary`](binary::Binary) as field type if the subtype matters, eg. for UUIDs.
+///
+/// ```rust
+/// use mongodb_ext::{binary::{Binary, BinarySubtype}, mongo_db};
+/// use mongodb::bson::{de::from_document, doc, ser::to_document};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         {
+///             use mongodb_ext::binary::Binary;
+///         }
+///         Files<_id: none> {
+///             #[binary]
+///             content: Vec<u8>,
+///             checksum: Binary,
+///         }
+///     }
+/// }
+///
+/// let checksum = Binary { subtype: BinarySubtype::Md5, bytes: vec![0; 16] };
+/// let file = mongo::schema::Files { content: vec![1, 2, 3], checksum: checksum.clone() };
+///
+/// let document = to_document(&file).unwrap();
+/// assert_eq!(
+///     document,
+///     doc! {
+///         "content": Binary { subtype: BinarySubtype::Generic, bytes: vec![1, 2, 3] },
+///         "checksum": checksum,
+///     }
+/// );
+/// assert_eq!(from_document::<mongo::schema::Files>(document).unwrap().content, vec![1, 2, 3]);
+/// ```
+///
+/// ## Numbers as strings and timestamps as integers
+///
+/// Fields with the field attribute `#[as_string]` are serialized as strings, which keeps the precision of large numbers for clients that read them as floating point numbers, eg. JavaScript.
+/// It applies to all primitive number types (eg. `u64`, `i128`, `f64`) and [`Option`]s of them, see [`as_string`].
+///
+/// Fields with the field attribute `#[as_i64]` are serialized as 64-bit integers.
+/// It applies to [`DateTime`](mongodb::bson::DateTime) (stored as milliseconds since the Unix epoch) and [`Option`]s of it, see [`as_i64`].
+///
+/// Both attributes accept the values they replace when deserializing (numbers for `#[as_string]`, dates for `#[as_i64]`), thus existing documents stay readable.
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+/// use mongodb::bson::{de::from_document, doc, ser::to_document, DateTime};
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         {
+///             use mongodb::bson::DateTime;
+///         }
+///         Transfers<_id: none> {
+///             #[as_string]
+///             amount: u64,
+///             #[as_i64]
+///             booked_at: DateTime,
+///         }
+///     }
+/// }
+///
+/// let transfer = mongo::schema::Transfers {
+///     amount: u64::MAX,
+///     booked_at: DateTime::from_millis(1_600_000_000_000),
+/// };
+///
+/// let document = to_document(&transfer).unwrap();
+/// assert_eq!(
+///     document,
+///     doc! { "amount": "18446744073709551615", "bookedAt": 1_600_000_000_000_i64 }
+/// );
+/// assert_eq!(from_document::<mongo::schema::Transfers>(document).unwrap().amount, u64::MAX);
+///
+/// // numbers and dates written before the attributes were added are still readable
+/// let legacy: mongo::schema::Transfers = from_document(doc! {
+///     "amount": 5_i64,
+///     "bookedAt": DateTime::from_millis(1_600_000_000_000),
+/// })
+/// .unwrap();
+/// assert_eq!(legacy.amount, 5);
+/// ```
+///
+/// ## Shared base fields
+///
+/// Fields shared by multiple collections can be declared once in a base struct with `@base {BaseName} { ... }` in front of the collections.
+/// Base structs accept attributes after `@base` and field attributes just like collections do, their fields are renamed to `camelCase` too.
+///
+/// Collections declared with the `flatten: {BaseName}` parameter get an additional field `base` that is flattened into the collection's documents using `#[serde(flatten)]`.
+/// Note that `deny_unknown_fields` cannot be used in combination with `flatten`.
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+/// use serde_json::ser;
+///
+/// mongo_db! {
+///     SomeDatabase {
+///         @base #[derive(Debug, Clone)]
+///         Tenant {
+///             tenant_id: String,
+///         };
+///
+///         #[derive(Debug)]
+///         Invoice<flatten: Tenant> {
+///             total_amount: u64,
+///         };
+///         #[derive(Debug)]
+///         Customer<flatten: Tenant, _id: none> {
+///             name: String,
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::{Customer, Tenant};
+///
+/// let customer = Customer::builder()
+///     .base(Tenant { tenant_id: String::from("acme") })
+///     .name(String::from("Bob"))
+///     .build();
+///
+/// assert_eq!(
+///     ser::to_string(&customer).unwrap(),
+///     String::from("{\"tenantId\":\"acme\",\"name\":\"Bob\"}")
+/// );
+/// ```
+///
+/// ## Serializing from [`json!`](serde_json::json) and [`doc!`](mongodb::bson::doc) macros
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+/// use serde_json::{json, Value};
+/// use mongodb::{bson::{doc, Document}, bson};
+///
+/// mongo_db! {
+///     #[derive(Debug, Clone)]
+///     DatabaseOfItems {
+///         #[derive(Debug, Clone, PartialEq)]
+///         Items {
+///             counter: u16,
+///             name: String
+///         };
+///     }
+/// }
+///
+/// // Note that `_id` is not specified here
+/// let my_item: Value = json! ({
+///     "counter": 0,
+///     "name": "my_special_item"
+/// });
+///
+/// let my_collection_entry: mongo::schema::Items =
+///     serde_json::from_value(my_item)
+///     .expect("Could not convert json Value to collection document");
+///
+/// assert_eq!(
+///     my_collection_entry,
+///     mongo::schema::Items {
+///         _id: None,
+///         counter: 0,
+///         name: String::from("my_special_item")
+///     }
+/// );
+///
+/// // Note that `_id` is not specified here
+/// let my_item: Document = doc! {
+///     "counter": 0,
+///     "name": "my_special_item"
+/// };
+///
+/// let my_collection_entry: mongo::schema::Items = bson::de::from_document(my_item)
+///     .expect("Could not convert mongodb bson Document to collection document");
+///
+/// assert_eq!(
+///     my_collection_entry,
+///     mongo::schema::Items {
+///         _id: None,
+///         counter: 0,
+///         name: String::from("my_special_item")
+///     }
+/// );
+/// ```
+///
+/// ## Adding your own code
+///
+/// Additional code for the `mongo` and `schema` modules can be specified in curly braces (`{` / `}`).
+///
+/// ```rust
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     // specify code to be in `mongo` here:
+///     {
+///         pub fn this_is_a_function_in_mongo() -> bool { true }
+///     }
+///     SomeDatabase {
+///         // specify code to be in `schema` here:
+///         {
+///             pub fn this_is_a_function_in_schema() -> bool { true }
+///             use std::collections::HashMap;
+///             // type aliases can be used as field types
+///             pub type Counters = HashMap<String, u32>;
+///         }
+///         SomeCollection {
+///             dict: HashMap<String, u32>,
+///             counters: Counters,
+///         }
+///     }
+/// }
+///
+/// assert!(mongo::this_is_a_function_in_mongo());
+/// assert!(mongo::schema::this_is_a_function_in_schema());
+/// ```
+///
+/// ### Code positioning
+///
+/// `Impl`ementations can be easily added by using the preset feature:
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, DefaultId};
+///
+/// mongo_db! {
+///     // specify globally needed code in `mongo` here:
+///     {
+///         use std::collections::HashMap;
+///     }
+///     SomeDatabase {
+///         // specify globally needed code in `schema` here:
+///         {
+///             use {
+///                 std::collections::HashMap,
+///                 mongodb::bson::oid::ObjectId
+///             };
+///         }
+///
+///         // specify collection-dependent code in an additional block below the
+///         // collection connected with a `-`:
+///         SomeCollection {
+///             dict: HashMap<String, u32>,
+///         }-{
+///             pub fn some_collection_function() -> bool { true }
+///         };
+///         #[derive(Debug, PartialEq)]
+///         AnotherCollection {}-{
+///             pub fn from_id(id: ObjectId) -> Self { Self { _id: Some(id) } }
+///         }
+///     }-{
+///         // specify implementations on the database handler here:
+///         pub fn give_bool() -> bool { true }
+///     }
+/// }
+///
+/// assert!(mongo::SomeDatabase::give_bool());
+/// assert!(mongo::schema::SomeCollection::some_collection_function());
+///
+/// let oid = DefaultId::parse_str("0123456789ABCDEF01234567").unwrap();
+/// assert_eq!(
+///     mongo::schema::AnotherCollection::from_id(oid.clone()),
+///     mongo::schema::AnotherCollection {
+///         _id: Some(oid),
+///     },
+/// );
+/// ```
+///
+/// ## [`TypedBuilder`](typed_builder::TypedBuilder)
+///
+/// Each schema implements [`TypedBuilder`](typed_builder::TypedBuilder) which lets you create a collection more easily.
+///
+/// If `_id` is not set to `none`, the `_id` field will have a `builder` attribute set to `default`.
+/// This enables you to skip specifying `_id` as [`None`].
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection};
+///
+/// mongo_db! {
+///     MyDatabase {
+///         #[derive(Debug, PartialEq)]
+///         MyCollection<version: 2, _id: u128> {
+///             name: String,
+///             counter: u32,
+///             schema_version: i32
+///         }
+///     }
+/// }
+///
+/// use mongo::schema::MyCollection;
+///
+/// assert_eq!(
+///     // constructing using the builder
+///     // note that no field `_id` is specified, thus `None` is used
+///     MyCollection::builder()
+///         .name("Alice".to_string())
+///         .counter(1)
+///         .schema_version(MyCollection::SCHEMA_VERSION)
+///         .build(),
+///     // constructing normally
+///     MyCollection {
+///         _id: None,
+///         name: "Alice".to_string(),
+///         counter: 1,
+///         schema_version: MyCollection::SCHEMA_VERSION
+///     }
+/// );
+/// ```
 ///
-/// # Structure
+/// Combining the schema version with the typed builder can be very useful:
 ///
-/// This macro wraps everything in a module called `mongo`.
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection};
 ///
-/// The main database handler has the following attributes:
-/// - Its name represents the database's name (eg. a database named `MyDatabase` has a struct `mongo::MyDatabase`).
-/// - It implements the [`MongoClient`] trait.
-/// - It contains handles to all given collections inside the database.
-///     These handles have the format `{collection_name}_coll` where `{collection_name}` represents the collection's name in `snake_case`.
-/// - It also contains a [`client`](mongodb::Client) and a [`database`](mongodb::Database) field for you to use.
+/// mongo_db! {
+///     MyDatabase {
+///         {
+///             use mongodb_ext::MongoCollection;
+///         }
+///         #[derive(Debug, PartialEq)]
+///         MyCollection<version: 2, _id: u128> {
+///             name: String,
+///             counter: u32,
+///             #[builder(default = <MyCollection as MongoCollection>::SCHEMA_VERSION)]
+///             schema_version: i32
+///         }
+///     }
+/// }
 ///
-/// All collections are wrapped in an additional public module named `schema`.
+/// use mongo::schema::MyCollection;
 ///
-/// Each collection has its own struct which stores all specified fields.
-/// All collection structs implement [`Serialize`](serde::Serialize), [`Deserialize`](serde::Deserialize) and [`MongoCollection`].
+/// assert_eq!(
+///     // specifying no version takes version constant by default
+///     MyCollection::builder()
+///         .name("Alice".to_string())
+///         .counter(255)
+///         .build(),
+///     MyCollection {
+///         _id: None,
+///         name: "Alice".to_string(),
+///         counter: 255,
+///         schema_version: 2
+///     }
+/// );
+/// ```
 ///
-/// By default a field `_id` gets added to each collection automatically:
-///     `pub _id: Option<DefaultId>` (see [`DefaultId`] for more info).
-/// This field needs to exist for you to be able to obtain an `_id` field from the database.
-/// When serializing, `_id` gets skipped if it is [`None`].
-/// All fields except `_id` get renamed to `camelCase` when serializing (converting `_id` to `camelCase` results in `id`).
+/// ## Validation
 ///
-/// _Note_: All structs' names in `camelCase` can be accessed via the [`MongoClient`] / [`MongoCollection`] trait.
+/// Fields of collections can be restricted with validator attributes:
+/// - `#[min_len(n)]` / `#[max_len(n)]` check the length of strings (in characters) and collections (in elements).
+/// - `#[min(value)]` / `#[max(value)]` check numbers.
+/// - `#[array(max_items = n, unique_items)]` checks the elements of a [`Vec`], both arguments are optional:
+///   `max_items` limits the number of elements and `unique_items` rejects equal elements.
 ///
-/// # Examples
+/// Fields of type [`Option`] are only checked if they are [`Some`].
+/// The checks are run by [`MongoCollection::validate`], which returns a [`ValidationError`] for the first violation.
+/// The generated write helpers (`insert`, `upsert`, `get_or_insert`, `save_changes` and `replace_with`) run them before anything is sent, see [`validate_before_write`].
+/// Documents written directly through the collection handles are not checked.
+/// Validator attributes can not be used in base structs and views.
 ///
-/// ## General Examples
+/// With the `validated` parameter the checks run at construction time already:
+/// the builder of the collection has a method `try_build` returning `Result<Self, ValidationError>` instead of `build`.
+/// This is a breaking opt-in, existing calls of `build` on the collection's builder no longer compile once `validated` is added (see the second example).
 ///
 /// ```rust
-/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection, DefaultId};
-/// use serde_json::ser;
+/// use mongodb_ext::{mongo_db, MongoCollection, ValidationError};
 ///
 /// mongo_db! {
-///     // database name
-///     SomeDatabase {
-///         // additional attributes for the collection
-///         #[derive(Debug, Clone)]
-///         // collection name
-///         SomeCollection {
-///             // collection fields
-///             first_name: String,
+///     MyDatabase {
+///         #[derive(Debug)]
+///         Users<validated> {
+///             #[min_len(1)]
+///             #[max_len(32)]
+///             name: String,
+///             #[max(150)]
+///             age: Option<u8>,
+///             #[array(max_items = 10, unique_items)]
+///             tags: Vec<String>,
 ///         }
 ///     }
 /// }
 ///
-/// let mut some_document = mongo::schema::SomeCollection {
-///     _id: None,
-///     first_name: String::from("alice")
-/// };
+/// use mongo::schema::Users;
 ///
-/// // When serializing, `_id` is skipped only if it is `None`.
-/// // Note the key conversion to `camelCase`.
-/// assert_eq!(
-///     ser::to_string(&some_document).unwrap(),
-///     String::from("{\"firstName\":\"alice\"}")
-/// );
+/// let user = Users::builder()
+///     .name("Alice".to_string())
+///     .age(Some(30))
+///     .tags(vec!["admin".to_string()])
+///     .try_build()
+///     .unwrap();
+/// assert!(user.validate().is_ok());
 ///
-/// // update `_id` field to include in serialization.
-/// let oid = DefaultId::parse_str("0123456789ABCDEF01234567").unwrap();
-/// some_document._id = Some(oid);
-/// assert_eq!(
-///     ser::to_string(&some_document).unwrap(),
-///     String::from("{\"_id\":{\"$oid\":\"0123456789abcdef01234567\"},\"firstName\":\"alice\"}")
-/// );
+/// let error = Users::builder()
+///     .name(String::new())
+///     .age(None)
+///     .tags(Vec::new())
+///     .try_build()
+///     .unwrap_err();
+/// assert_eq!(error, ValidationError::new("users", "name", "length must be at least 1"));
+/// assert_eq!(error.to_string(), "invalid field `name` of `users`: length must be at least 1");
+/// ```
 ///
-/// // constants store the collection / database names in `camelCase` + collection version
-/// assert_eq!("someCollection", mongo::schema::SomeCollection::NAME);
-/// assert_eq!(1, mongo::schema::SomeCollection::SCHEMA_VERSION);
-/// assert_eq!("someDatabase", mongo::SomeDatabase::NAME);
+/// ```rust,compile_fail
+/// use mongodb_ext::mongo_db;
+///
+/// mongo_db! {
+///     MyDatabase {
+///         Users<validated> {
+///             #[min_len(1)]
+///             name: String,
+///         }
+///     }
+/// }
+///
+/// // `build` is replaced by `try_build`
+/// let _user = mongo::schema::Users::builder().name("Alice".to_string()).build();
 /// ```
 ///
-/// Multiple collections need to be separated by `;`, a trailing `;` is optional:
+/// ## Normalizing fields
+///
+/// Text fields of collections can be normalized with `#[normalize(...)]`, eg. to look up emails case-insensitively without a collation:
+/// - `lowercase` converts the field to lowercase.
+/// - `trim` removes leading and trailing whitespace.
+///
+/// Both can be combined, eg. `#[normalize(trim, lowercase)]`, and apply to fields of type [`String`] and `Option<String>`.
+/// They are applied by [`MongoCollection::normalize`], which the generated `insert` helpers call before writing.
+/// The other generated write helpers (`upsert`, `get_or_insert`, `save_changes` and `replace_with`) normalize a serialized copy of the document with [`MongoCollection::normalize_document`] instead, thus the document passed to them is left unchanged.
+/// Declare a unique index on the normalized field, eg. with `#[alternate_key]`, to reject duplicates.
 ///
 /// ```rust
-/// use mongodb_ext::{mongo_db, MongoCollection, MongoClient};
+/// use mongodb_ext::{mongo_db, MongoCollection};
 ///
 /// mongo_db! {
-///     #[derive(Debug, Clone)]
 ///     MyDatabase {
-///         #[derive(Debug, Clone)]
-///         MyFirstCollection {
-///             first_name: String,
-///             last_name: String,
-///             age: u8,
-///         };
-///         #[derive(Debug)]
-///         AnotherCollection {
-///             some_field: String
-///         };
+///         Users {
+///             #[normalize(trim, lowercase)]
+///             #[alternate_key]
+///             email: String,
+///         }
 ///     }
 /// }
 ///
-/// // all constants that were defined
-/// assert_eq!("myDatabase", mongo::MyDatabase::NAME);
-/// assert_eq!("myFirstCollection", mongo::schema::MyFirstCollection::NAME);
-/// assert_eq!(1, mongo::schema::MyFirstCollection::SCHEMA_VERSION);
-/// assert_eq!("anotherCollection", mongo::schema::AnotherCollection::NAME);
-/// assert_eq!(1, mongo::schema::AnotherCollection::SCHEMA_VERSION);
+/// let mut user = mongo::schema::Users::builder()
+///     .email(String::from(" Alice@Example.com"))
+///     .build();
+/// user.normalize();
+/// assert_eq!(user.email, "alice@example.com");
+/// ```
 ///
-/// // initializer function and general usage
-/// // note that `tokio_test::block_on` is just a test function to run `async` code in doc tests
+/// ## Read-only database handlers
 ///
-/// let mongo = tokio_test::block_on(mongo::MyDatabase::new("mongodb://example.com"))
+/// Declaring a database with `<read_only>` wraps all collection handles in a [`ReadOnlyCollection`] instead of a [`Collection`](mongodb::Collection).
+/// These handles only expose read operations (`find`, `find_one`, `count_documents`, `aggregate`, ...), which enforces read-only access at the type level.
+///
+/// ```rust
+/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection, ReadOnlyCollection};
+///
+/// mongo_db! {
+///     ReportingDatabase<read_only> {
+///         Orders {
+///             total: u64,
+///         }
+///     }
+/// }
+///
+/// let mongo = tokio_test::block_on(mongo::ReportingDatabase::new("mongodb://example.com"))
 ///     .expect("Could not create mongoDB client");
 ///
-/// let bob = mongo::schema::MyFirstCollection {
-///     _id: None,
-///     first_name: String::from("Bob"),
-///     last_name: String::from("Bob's last name"),
-///     age: 255,
-/// };
+/// let orders: &ReadOnlyCollection<mongo::schema::Orders> = &mongo.orders_coll;
+/// assert_eq!(orders.name(), mongo::schema::Orders::NAME);
+/// ```
 ///
-/// // This should fail beause there is no actual mongoDB service running at the specified
-/// // connection.
-/// assert!(tokio_test::block_on(
-///     mongo.my_first_collection_coll.insert_one(bob, None)
-/// ).is_err());
+/// Write operations are not available:
+///
+/// ```rust,compile_fail
+/// use mongodb_ext::{mongo_db, MongoClient};
+///
+/// mongo_db! {
+///     ReportingDatabase<read_only> {
+///         Orders {
+///             total: u64,
+///         }
+///     }
+/// }
+///
+/// let mongo = tokio_test::block_on(mongo::ReportingDatabase::new("mongodb://example.com"))
+///     .expect("Could not create mongoDB client");
+///
+/// let order = mongo::schema::Orders { _id: None, total: 1 };
+/// tokio_test::block_on(mongo.orders_coll.insert_one(order, None));
 /// ```
 ///
-/// ## Manipulating / Removing `_id`
+/// ## Audit log
 ///
-/// You can specify any type (that implements [`Serialize`](serde::Serialize) and [`Deserialize`](serde::Deserialize)) to be used inside the `_id` [`Option`] by specifying it in `<` / `>` after the collection name:
+/// Declaring a database with `<audit: {collection_name}>` records every write done by the generated write helpers (eg. `save_changes_{collection_name}`) in the given collection.
+/// The collection's name is converted to `camelCase` just like all other names.
+/// After each successful write a document with these fields is inserted:
+/// - `operation`: the kind of write, eg. `"update"`.
+/// - `collection`: the name of the written collection.
+/// - `timestamp`: the current time as [`DateTime`](mongodb::bson::DateTime).
+/// - `documentId`: the `_id` of the written document, or the filter for writes of many documents (eg. `delete_{collection_name}`).
+///
+/// Dropping a collection with `drop_{collection_name}` is recorded too, as `"drop"` with a `null` `documentId`.
+/// [`MongoClient::drop_database`] is not recorded, since it drops the audit collection along with all earlier records.
+///
+/// The audit collection does not need to be declared, but declaring it gives you a typed handle to read the records (see example).
+///
+/// Note that every audited write costs an additional round trip to the server.
+/// The audit record is inserted after the write, thus an error while inserting it is returned even though the write itself succeeded.
+/// Writes made directly through the collection handles are not recorded.
 ///
 /// ```rust
-/// use mongodb_ext::mongo_db;
+/// use mongodb_ext::{mongo_db, MongoClient};
 ///
 /// mongo_db! {
-///     SomeDatabase {
-///         SomeCollection<_id: u128> {
-///             first_name: String,
+///     SomeDatabase<audit: AuditLog> {
+///         {
+///             use mongodb::bson::{Bson, DateTime};
+///         }
+///         User {
+///             name: String,
+///         };
+///         AuditLog {
+///             operation: String,
+///             collection: String,
+///             timestamp: DateTime,
+///             document_id: Bson,
 ///         }
 ///     }
 /// }
 ///
-/// // _id is now `u128` instead of `DefaultId`
-/// let some_document = mongo::schema::SomeCollection {
-///     _id: Some(255),
-///     first_name: String::from("Bob")
-/// };
+/// let mongo = tokio_test::block_on(mongo::SomeDatabase::new("mongodb://example.com"))
+///     .expect("Could not create mongoDB client");
+///
+/// let user = mongo::schema::User::builder()
+///     ._id(Some(mongodb::bson::oid::ObjectId::new()))
+///     .name(String::from("alice"))
+///     .build();
+///
+/// // also inserts a record into `auditLog` if `user` changed
+/// let _result = mongo.save_changes_user(&user);
 /// ```
 ///
-/// It is also possible to disable the generation of an `_id` field all together by using `<_id: none>`.
+/// ## Shutting down on drop
+///
+/// With the _"shutdown-on-drop"_ feature enabled, declaring a database with `<shutdown_on_drop>` implements [`Drop`] for the database handler, which shuts down the client using [`Client::shutdown`](mongodb::Client::shutdown).
+/// This closes all connections promptly, which is useful for short-lived tools that exit right after using the database.
+///
+/// Since there is no asynchronous drop, the shutdown depends on where the handler is dropped:
+/// - Inside of a multi-threaded tokio runtime the current thread is blocked until the shutdown is finished.
+/// - Inside of a current-thread tokio runtime the shutdown is spawned onto the runtime and not waited for, since blocking would dead-lock.
+/// - Outside of a tokio runtime a temporary runtime is created to run the shutdown.
+///
+/// Note that the shutdown waits for all cursors, sessions and GridFS streams of the client to be dropped, so drop them before the handler.
+/// The client is shut down for all clones of it as well, eg. a client passed to [`MongoClient::new_with_client`] of another handler.
+/// Also, fields cannot be moved out of types implementing [`Drop`], which rules out eg. `let client = handler.client;`.
 ///
 /// ```rust
-/// use mongodb_ext::mongo_db;
+/// #[cfg(feature = "shutdown-on-drop")]
+/// mod tool {
+///     use mongodb_ext::mongo_db;
 ///
-/// mongo_db! {
-///     SomeDatabase {
-///         SomeCollection<_id: none> {
-///             #[serde(skip_serializing_if = "Option::is_none")]
-///             email_address: Option<String>,
-///             first_name: String,
+///     mongo_db! {
+///         SomeDatabase<shutdown_on_drop> {
+///             User {
+///                 name: String,
+///             }
 ///         }
 ///     }
 /// }
 ///
-/// // no `_id` exists, this example assumes that users are addressed via their email address
-/// let some_document = mongo::schema::SomeCollection {
-///     email_address: Some(String::from("bob@example.com")),
-///     first_name: String::from("Bob")
-/// };
+/// #[cfg(feature = "shutdown-on-drop")]
+/// {
+///     use mongodb_ext::MongoClient;
+///
+///     let mongo = tokio_test::block_on(tool::mongo::SomeDatabase::new("mongodb://example.com"))
+///         .expect("Could not create mongoDB client");
+///
+///     // shuts down the client
+///     drop(mongo);
+/// }
 /// ```
 ///
-/// These features are unique for each collection:
+/// ## Connection string from the environment
+///
+/// [`MongoClient::from_env`] reads the connection string from the environment variable `MONGODB_URI` and creates the database handler using it.
+/// Declaring a database with `<env: "{variable}">` reads the given variable instead, it is available as [`MongoClient::ENV_VAR`].
+/// An error is returned if the variable is not set.
 ///
 /// ```rust
-/// use mongodb_ext::{mongo_db, DefaultId};
+/// use mongodb_ext::{mongo_db, MongoClient};
 ///
 /// mongo_db! {
-///     SomeDatabase {
-///         SomeCollection<_id: u128> {
-///             first_name: String,
-///         };
-///         Another {
-///             some_field: u32,
-///         };
-///         AndYetAnother<_id: none> {
-///             email: String,
+///     SomeDatabase<env: "SOME_DATABASE_URI"> {
+///         User {
 ///             name: String,
 ///         }
 ///     }
 /// }
 ///
-/// // `_id` type changed to `u128`
-/// let some_document = mongo::schema::SomeCollection {
-///     _id: Some(255),
-///     first_name: String::from("Bob")
-/// };
-/// // `_id` type default, eg. `DefaultId`
-/// let oid = DefaultId::parse_str("0123456789ABCDEF01234567").unwrap();
-/// let another_document = mongo::schema::Another {
-///     _id: Some(oid),
-///     some_field: 1,
-/// };
-/// // `_id` field disabled
-/// let and_yet_another_document = mongo::schema::AndYetAnother {
-///     name: String::from("Bob"),
-///     email: String::from("bob@example.com")
-/// };
+/// assert_eq!(<mongo::SomeDatabase as MongoClient>::ENV_VAR, "SOME_DATABASE_URI");
+///
+/// // the variable is not set yet
+/// assert!(tokio_test::block_on(mongo::SomeDatabase::from_env()).is_err());
+///
+/// std::env::set_var("SOME_DATABASE_URI", "mongodb://example.com");
+/// let mongo = tokio_test::block_on(mongo::SomeDatabase::from_env())
+///     .expect("Could not create mongoDB client");
 /// ```
 ///
-/// Each collection that does not have a parameter of `id: none` implements a function `id(&self)` that returns a reference to its ID:
+/// ## Indexes
+///
+/// Indexes can be declared in an `@indexes` block at the end of the database.
+/// Each collection lists its indexes in brackets, one index per bracket.
+///
+/// An index consists of one or more comma-separated keys, each optionally followed by `asc` (default) or `desc`.
+/// Keys are converted to `camelCase` just like the fields, see [Casing of names](#casing-of-names).
+/// The keys may be followed by these options:
+/// - `unique`: creates a unique index.
+/// - `ttl {seconds}`: removes documents `{seconds}` seconds after the date stored in the (single) key.
+/// - `partial { {field}: {value}, ... }`: only indexes documents matching the filter.
+///   Fields are converted to `camelCase`, values can be anything that converts into [`Bson`](mongodb::bson::Bson), eg. `doc! { "$gt": 5 }` for query operators.
+///   The value `null` matches documents that store `null` in the field, see [Unique among active documents](#unique-among-active-documents).
+/// - `name "{name}"`: names the index instead of letting the server derive the name from the keys.
+/// - `hidden`: creates a hidden index, which is maintained but not used by the query planner.
+///   Hide an index before dropping it to check that no query depends on it, unhiding it again is instant compared to a rebuild.
+/// - `background`: builds the index in the background on servers older than 4.2, newer servers ignore it.
+///
+/// All declared indexes are returned by [`MongoClient::index_models`] and can be created with [`MongoClient::create_indexes`].
+///
+/// Each `unique` index without a partial filter gets a finder on the collection's repository, see [Repositories](#repositories).
+/// It is named `find_by_{key}`, or `find_by_{key1}_and_{key2}` for compound indexes, and takes a reference to the value of each key in the type of the field.
+/// Thus the keys of a unique index must be fields of the collection, and a field can not be an [alternate key](#alternate-keys) (or `#[unique]`) and the only key of a unique index at once.
 ///
 /// ```rust
-/// use mongodb_ext::{mongo_db, DefaultId};
+/// use mongodb_ext::{mongo_db, MongoClient};
 ///
 /// mongo_db! {
 ///     SomeDatabase {
-///         SomeCollection<_id: u128> {};
-///         Another {};
+///         {
+///             use mongodb::bson::DateTime;
+///         }
+///         User {
+///             email: String,
+///             first_name: String,
+///             last_name: String,
+///         };
+///         Post {
+///             created_at: DateTime,
+///             is_draft: bool,
+///         };
+///
+///         @indexes {
+///             User: [email unique] [last_name, first_name desc];
+///             Post: [created_at desc ttl 86400] [created_at partial { is_draft: false }];
+///         }
 ///     }
 /// }
 ///
-/// // `id` returns `&Option<u128>`
-/// let some_collection = mongo::schema::SomeCollection {
-///     _id: Some(255),
-/// };
+/// let models = mongo::SomeDatabase::index_models();
+/// assert_eq!(models.len(), 4);
+///
+/// let (collection_name, unique_email) = &models[0];
+/// assert_eq!(*collection_name, "user");
+/// assert_eq!(unique_email.keys, mongodb::bson::doc! { "email": 1 });
+/// assert_eq!(unique_email.options.as_ref().unwrap().unique, Some(true));
+///
+/// let (collection_name, names) = &models[1];
+/// assert_eq!(*collection_name, "user");
+/// assert_eq!(names.keys, mongodb::bson::doc! { "lastName": 1, "firstName": -1 });
+///
+/// let (collection_name, expiring) = &models[2];
+/// assert_eq!(*collection_name, "post");
+/// assert_eq!(expiring.keys, mongodb::bson::doc! { "createdAt": -1 });
 /// assert_eq!(
-///     *some_collection.id(),
-///     Some(255)
+///     expiring.options.as_ref().unwrap().expire_after,
+///     Some(std::time::Duration::from_secs(86400))
 /// );
 ///
-/// // `id` returns `&Option<DefaultId>`
-/// let oid = DefaultId::parse_str("0123456789ABCDEF01234567").unwrap();
-/// let another = mongo::schema::Another {
-///     _id: Some(oid.clone()),
-/// };
+/// let (collection_name, published) = &models[3];
+/// assert_eq!(*collection_name, "post");
 /// assert_eq!(
-///     *another.id(),
-///     Some(oid)
+///     published.options.as_ref().unwrap().partial_filter_expression,
+///     Some(mongodb::bson::doc! { "isDraft": false })
 /// );
+///
+/// async fn find_user(mongo: &mongo::SomeDatabase) -> mongodb::error::Result<Option<mongo::schema::User>> {
+///     mongo.user().find_by_email(&String::from("alice@example.com")).await
+/// }
 /// ```
 ///
-/// ## Versioning of your schema
+/// ### Indexes next to the collection
 ///
-/// Your database schema version is managed via [`MongoCollection::SCHEMA_VERSION`].
+/// Indexes can also be declared with the `indexes` collection parameter, which keeps them next to the fields they cover.
+/// It takes the same brackets as the `@indexes` block, wrapped in another pair of brackets.
+/// Unlike in the `@indexes` block, keys use the [case of the collection](#other-cases-of-field-names) at compile time.
+/// These indexes are returned by [`MongoCollection::indexes`] and included in [`MongoClient::index_models`] as well.
 ///
-/// This can be modified like so:
+/// The database handler has a method `create_{collection_name}_indexes` for each collection that creates the indexes of this collection only,
+/// no matter where they are declared.
 ///
 /// ```rust
-/// use mongodb_ext::{mongo_db, MongoCollection};
-/// use serde_json::ser;
+/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection};
+/// use mongodb::bson::doc;
 ///
 /// mongo_db! {
 ///     SomeDatabase {
-///         // no schema version defaults to const `DEFAULT_SCHEMA_VERSION`
-///         Items {
-///             name: String,
-///         };
-///         // schema version of 200
-///         Queue<version: 200> {
-///             item: i32,
-///         };
-///         // schema version of 4
-///         SomeCollection<version: 4, _id: none> {
+///         User<indexes: [[email unique] [last_name, first_name desc]]> {
+///             email: String,
 ///             first_name: String,
-///         };
-///         // schema version of 5
-///         FourthCollection<_id: String, version: 5> {};
+///             last_name: String,
+///         }
 ///     }
 /// }
 ///
-/// // default schema version is 1
-/// assert_eq!(1, mongodb_ext::DEFAULT_SCHEMA_VERSION);
+/// let models = mongo::schema::User::indexes();
+/// assert_eq!(models[0].keys, doc! { "email": 1 });
+/// assert_eq!(models[1].keys, doc! { "lastName": 1, "firstName": -1 });
+/// assert_eq!(mongo::SomeDatabase::index_models().len(), 2);
 ///
-/// assert_eq!(mongo::schema::Items::SCHEMA_VERSION, 1);
-/// assert_eq!(mongo::schema::Queue::SCHEMA_VERSION, 200);
-/// assert_eq!(mongo::schema::SomeCollection::SCHEMA_VERSION, 4);
-/// assert_eq!(mongo::schema::FourthCollection::SCHEMA_VERSION, 5);
+/// async fn setup(mongo: &mongo::SomeDatabase) -> mongodb::error::Result<()> {
+///     mongo.create_user_indexes().await
+/// }
 /// ```
 ///
-/// ## Serializing from [`json!`](serde_json::json) and [`doc!`](mongodb::bson::doc) macros
+/// ### Unique among active documents
+///
+/// Collections with soft deletes usually need keys that are unique among the documents that are not deleted, eg. an email address that can be registered again after its user was deleted.
+/// Combine `unique` with a `partial` filter on the deletion field for that:
+/// only documents matching the filter are indexed, thus only they are checked for uniqueness.
+///
+/// Partial filters cannot select documents that miss a field, thus `null` is translated to `{ "$type": "null" }`, which selects documents that store `null`.
+/// Fields of type [`Option`] store `null` for [`None`], unless they are marked with `#[null_as(omit)]`.
 ///
 /// ```rust
-/// use mongodb_ext::mongo_db;
-/// use serde_json::{json, Value};
-/// use mongodb::{bson::{doc, Document}, bson};
+/// use mongodb_ext::{mongo_db, MongoClient};
+/// use mongodb::bson::doc;
 ///
 /// mongo_db! {
-///     #[derive(Debug, Clone)]
-///     DatabaseOfItems {
-///         #[derive(Debug, Clone, PartialEq)]
-///         Items {
-///             counter: u16,
-///             name: String
+///     SomeDatabase {
+///         {
+///             use mongodb::bson::DateTime;
+///         }
+///         Users {
+///             email: String,
+///             deleted_at: Option<DateTime>,
 ///         };
-///     }
-/// }
 ///
-/// // Note that `_id` is not specified here
-/// let my_item: Value = json! ({
-///     "counter": 0,
-///     "name": "my_special_item"
-/// });
-///
-/// let my_collection_entry: mongo::schema::Items =
-///     serde_json::from_value(my_item)
-///     .expect("Could not convert json Value to collection document");
-///
-/// assert_eq!(
-///     my_collection_entry,
-///     mongo::schema::Items {
-///         _id: None,
-///         counter: 0,
-///         name: String::from("my_special_item")
+///         @indexes {
+///             Users: [email unique partial { deleted_at: null }];
+///         }
 ///     }
-/// );
-///
-/// // Note that `_id` is not specified here
-/// let my_item: Document = doc! {
-///     "counter": 0,
-///     "name": "my_special_item"
-/// };
-///
-/// let my_collection_entry: mongo::schema::Items = bson::de::from_document(my_item)
-///     .expect("Could not convert mongodb bson Document to collection document");
+/// }
 ///
+/// let (_, model) = &mongo::SomeDatabase::index_models()[0];
+/// let options = model.options.as_ref().unwrap();
+/// assert_eq!(options.unique, Some(true));
 /// assert_eq!(
-///     my_collection_entry,
-///     mongo::schema::Items {
-///         _id: None,
-///         counter: 0,
-///         name: String::from("my_special_item")
-///     }
+///     options.partial_filter_expression,
+///     Some(doc! { "deletedAt": { "$type": "null" } })
 /// );
 /// ```
 ///
-/// ## Adding your own code
+/// ### Expiring documents
 ///
-/// Additional code for the `mongo` and `schema` modules can be specified in curly braces (`{` / `}`).
+/// A collection that expires all of its documents can declare its TTL index with a `#[ttl(field = {field}, seconds = {seconds})]` attribute instead of an `@indexes` entry.
+/// Documents are removed `{seconds}` seconds after the date stored in `{field}`, which is converted to `camelCase` just like index keys, thus `created_at` and `createdAt` both refer to the stored `createdAt`.
+/// The field does not need to be declared in the collection, eg. if it is part of a [shared base](#shared-base-fields).
+///
+/// The declared expiry is returned by [`MongoCollection::ttl`] and its index is included in [`MongoClient::index_models`].
 ///
 /// ```rust
-/// use mongodb_ext::mongo_db;
+/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection};
+/// use std::time::Duration;
 ///
 /// mongo_db! {
-///     // specify code to be in `mongo` here:
-///     {
-///         pub fn this_is_a_function_in_mongo() -> bool { true }
-///     }
 ///     SomeDatabase {
-///         // specify code to be in `schema` here:
 ///         {
-///             pub fn this_is_a_function_in_schema() -> bool { true }
-///             use std::collections::HashMap;
-///         }
-///         SomeCollection {
-///             dict: HashMap<String, u32>,
+///             use mongodb::bson::DateTime;
 ///         }
+///         /// Sessions are removed a week after they were created.
+///         #[ttl(field = createdAt, seconds = 604800)]
+///         Session {
+///             token: String,
+///             created_at: DateTime,
+///         };
 ///     }
 /// }
 ///
-/// assert!(mongo::this_is_a_function_in_mongo());
-/// assert!(mongo::schema::this_is_a_function_in_schema());
+/// assert_eq!(
+///     mongo::schema::Session::ttl(),
+///     Some(("createdAt", Duration::from_secs(604800)))
+/// );
+///
+/// let (collection_name, expiring) = &mongo::SomeDatabase::index_models()[0];
+/// assert_eq!(*collection_name, "session");
+/// assert_eq!(expiring.keys, mongodb::bson::doc! { "createdAt": 1 });
+/// assert_eq!(
+///     expiring.options.as_ref().unwrap().expire_after,
+///     Some(Duration::from_secs(604800))
+/// );
 /// ```
 ///
-/// ### Code positioning
+/// ## Views
 ///
-/// `Impl`ementations can be easily added by using the preset feature:
+/// Views backed by an aggregation pipeline can be declared with `@view` after the collections (and before read models and the `@indexes` block).
+/// A view names the collection it is backed by, lists the pipeline's stages in brackets and declares the fields of its documents.
+///
+/// Each view gets a struct in `schema` that only implements [`Deserialize`](serde::Deserialize), since views cannot be written to.
+/// Fields are renamed to `camelCase` like the fields of collections, but no `_id` is added: declare it yourself with `#[serde(rename = "_id")]` if the pipeline keeps it.
+/// The struct implements [`MongoView`], which provides the view's name, the backing collection's name and the pipeline.
+///
+/// The database handler contains a [`ReadOnlyCollection`] handle `{view_name}_view` for each view.
+/// All views are returned by [`MongoClient::view_options`] and can be created with [`MongoClient::create_views`].
 ///
 /// ```rust
-/// use mongodb_ext::{mongo_db, DefaultId};
+/// use mongodb_ext::{mongo_db, MongoClient, MongoView};
 ///
 /// mongo_db! {
-///     // specify globally needed code in `mongo` here:
-///     {
-///         use std::collections::HashMap;
-///     }
 ///     SomeDatabase {
-///         // specify globally needed code in `schema` here:
 ///         {
-///             use {
-///                 std::collections::HashMap,
-///                 mongodb::bson::oid::ObjectId
-///             };
+///             use mongodb::bson::doc;
 ///         }
-///
-///         // specify collection-dependent code in an additional block below the
-///         // collection connected with a `-`:
-///         SomeCollection {
-///             dict: HashMap<String, u32>,
-///         }-{
-///             pub fn some_collection_function() -> bool { true }
+///         User {
+///             name: String,
+///             active: bool,
 ///         };
-///         #[derive(Debug, PartialEq)]
-///         AnotherCollection {}-{
-///             pub fn from_id(id: ObjectId) -> Self { Self { _id: Some(id) } }
+///
+///         @view ActiveUsers on User [
+///             doc! { "$match": { "active": true } },
+///             doc! { "$project": { "_id": 0, "name": 1 } },
+///         ] {
+///             name: String,
 ///         }
-///     }-{
-///         // specify implementations on the database handler here:
-///         pub fn give_bool() -> bool { true }
 ///     }
 /// }
 ///
-/// assert!(mongo::SomeDatabase::give_bool());
-/// assert!(mongo::schema::SomeCollection::some_collection_function());
+/// use mongo::schema::ActiveUsers;
 ///
-/// let oid = DefaultId::parse_str("0123456789ABCDEF01234567").unwrap();
-/// assert_eq!(
-///     mongo::schema::AnotherCollection::from_id(oid.clone()),
-///     mongo::schema::AnotherCollection {
-///         _id: Some(oid),
-///     },
-/// );
+/// assert_eq!(ActiveUsers::NAME, "activeUsers");
+/// assert_eq!(ActiveUsers::VIEW_ON, "user");
+/// assert_eq!(ActiveUsers::pipeline().len(), 2);
+///
+/// let views = mongo::SomeDatabase::view_options();
+/// assert_eq!(views.len(), 1);
+/// assert_eq!(views[0].0, "activeUsers");
+/// assert_eq!(views[0].1.view_on.as_deref(), Some("user"));
 /// ```
 ///
-/// ## [`TypedBuilder`](typed_builder::TypedBuilder)
+/// ## Read models
 ///
-/// Each schema implements [`TypedBuilder`](typed_builder::TypedBuilder) which lets you create a collection more easily.
+/// Read models for the output of aggregations, eg. documents with sub-documents joined by `$lookup`, can be declared with `@readmodel` after the views (and before the `@indexes` block).
+/// Unlike views, they do not exist on the server, they only describe the shape of query results.
 ///
-/// If `_id` is not set to `none`, the `_id` field will have a `builder` attribute set to `default`.
-/// This enables you to skip specifying `_id` as [`None`].
+/// Each read model gets a struct in `schema` that only implements [`Deserialize`](serde::Deserialize) and [`TryFrom<Document>`](std::convert::TryFrom), since read models are never written.
+/// Fields are renamed to `camelCase` like the fields of collections, but no `_id` is added: declare it yourself with `#[serde(rename = "_id")]` if the pipeline keeps it.
+/// Joined sub-documents can use the structs of other collections as field types.
+/// The database handler has no handle or helpers for read models, use them with eg. `aggregate_{collection_name}` and [`Cursor::with_type`](mongodb::Cursor::with_type).
 ///
 /// ```rust
-/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection};
+/// use mongodb_ext::{mongo_db, Pipeline};
+/// use mongodb::bson::doc;
 ///
 /// mongo_db! {
-///     MyDatabase {
-///         #[derive(Debug, PartialEq)]
-///         MyCollection<version: 2, _id: u128> {
+///     SomeDatabase {
+///         User {
 ///             name: String,
-///             counter: u32,
-///             schema_version: i32
+///         };
+///         Order {
+///             user_name: String,
+///             total: u32,
+///         };
+///
+///         @readmodel UserWithOrders {
+///             name: String,
+///             orders: Vec<Order>,
 ///         }
 ///     }
 /// }
 ///
-/// use mongo::schema::MyCollection;
+/// use mongo::schema::UserWithOrders;
 ///
-/// assert_eq!(
-///     // constructing using the builder
-///     // note that no field `_id` is specified, thus `None` is used
-///     MyCollection::builder()
-///         .name("Alice".to_string())
-///         .counter(1)
-///         .schema_version(MyCollection::SCHEMA_VERSION)
-///         .build(),
-///     // constructing normally
-///     MyCollection {
-///         _id: None,
-///         name: "Alice".to_string(),
-///         counter: 1,
-///         schema_version: MyCollection::SCHEMA_VERSION
+/// async fn users_with_orders(mongo: &mongo::SomeDatabase) -> mongodb::error::Result<Vec<UserWithOrders>> {
+///     let pipeline = Pipeline::new().lookup(doc! {
+///         "from": "order",
+///         "localField": "name",
+///         "foreignField": "userName",
+///         "as": "orders",
+///     });
+///     let mut cursor = mongo.aggregate_user(pipeline, None).await?.with_type::<UserWithOrders>();
+///     let mut users = Vec::new();
+///     while cursor.advance().await? {
+///         users.push(cursor.deserialize_current()?);
 ///     }
-/// );
+///     Ok(users)
+/// }
+///
+/// let user = UserWithOrders::try_from(doc! {
+///     "name": "alice",
+///     "orders": [{ "userName": "alice", "total": 42 }],
+/// })
+/// .expect("Could not convert read model document");
+/// assert_eq!(user.orders[0].total, 42);
 /// ```
 ///
-/// Combining the schema version with the typed builder can be very useful:
+/// ## Change streams
+///
+/// With the _"change-streams"_ feature enabled, the database handler has two methods per collection to open a change stream:
+/// - `watch_{collection_name}` forwards to [`Collection::watch`](mongodb::Collection::watch).
+/// - `watch_{collection_name}_from` resumes after the given resume token, or starts at the current time if [`None`] is given.
+///
+/// A consumer that persists the stream's resume token after each processed event can continue where it left off after a restart.
 ///
 /// ```rust
-/// use mongodb_ext::{mongo_db, MongoClient, MongoCollection};
+/// use mongodb_ext::mongo_db;
 ///
 /// mongo_db! {
-///     MyDatabase {
-///         {
-///             use mongodb_ext::MongoCollection;
-///         }
-///         #[derive(Debug, PartialEq)]
-///         MyCollection<version: 2, _id: u128> {
+///     SomeDatabase {
+///         #[derive(Debug)]
+///         Users {
 ///             name: String,
-///             counter: u32,
-///             #[builder(default = <MyCollection as MongoCollection>::SCHEMA_VERSION)]
-///             schema_version: i32
 ///         }
 ///     }
 /// }
 ///
-/// use mongo::schema::MyCollection;
-///
-/// assert_eq!(
-///     // specifying no version takes version constant by default
-///     MyCollection::builder()
-///         .name("Alice".to_string())
-///         .counter(255)
-///         .build(),
-///     MyCollection {
-///         _id: None,
-///         name: "Alice".to_string(),
-///         counter: 255,
-///         schema_version: 2
+/// #[cfg(feature = "change-streams")]
+/// async fn consume_users(
+///     mongo: &mongo::SomeDatabase,
+///     mut persist: impl FnMut(mongodb::change_stream::event::ResumeToken),
+///     last_token: Option<mongodb::change_stream::event::ResumeToken>,
+/// ) -> mongodb::error::Result<()> {
+///     let mut stream = mongo.watch_users_from(last_token).await?;
+///     while stream.is_alive() {
+///         if let Some(event) = stream.next_if_any().await? {
+///             println!("{:?} {:?}", event.operation_type, event.full_document);
+///         }
+///         if let Some(token) = stream.resume_token() {
+///             persist(token);
+///         }
 ///     }
-/// );
+///     Ok(())
+/// }
 /// ```
 #[macro_export]
 macro_rules! mongo_db {
@@ -828,24 +7240,59 @@ macro_rules! mongo_db {
         })?
 
         $(#[$additional_db_attr:meta])*
-        $db_name:ident {
+        $db_name:ident$(<$($db_param_name:ident$(: $($db_param_value:tt)::+)?),+>)? {
 
             $({
                 $($inner_tokens:tt)+
             })?
 
             $(
-                $(#[$additional_coll_attr:meta])*
-                $coll_name:ident$(<$($collection_param_name:ident: $collection_param_value:tt),+>)? {
+                @base $(#[$additional_base_attr:meta])*
+                $base_name:ident {
                     $(
-                        $(#[$additional_field_attr:meta])*
-                        $field:ident: $field_type:ty
+                        $(#[$($additional_base_field_attr:tt)*])*
+                        $base_field:ident: $base_field_type:ty
                     ),*$(,)?
+                }$(;)?
+            )*
+
+            $(
+                $(#[$($additional_coll_attr:tt)*])*
+                $coll_name:ident$(<$($collection_param_name:ident$(: $($collection_param_value:tt)::+ $($collection_param_word:ident $(= $($collection_param_word_value:tt)::+)?)?)?),+>)? {
+                    $($coll_fields:tt)*
                 }
                 $(-{
                     $($inner_impl:tt)+
                 })?
             );+$(;)?
+
+            $(
+                @view $(#[$additional_view_attr:meta])*
+                $view_name:ident on $view_source:ident [
+                    $($view_stage:expr),*$(,)?
+                ] {
+                    $(
+                        $(#[$($additional_view_field_attr:tt)*])*
+                        $view_field:ident: $view_field_type:ty
+                    ),*$(,)?
+                }$(;)?
+            )*
+
+            $(
+                @readmodel $(#[$additional_readmodel_attr:meta])*
+                $readmodel_name:ident {
+                    $(
+                        $(#[$($additional_readmodel_field_attr:tt)*])*
+                        $readmodel_field:ident: $readmodel_field_type:ty
+                    ),*$(,)?
+                }$(;)?
+            )*
+
+            $(@indexes {
+                $(
+                    $index_coll_name:ident: $([$($index:tt)+])+
+                );+$(;)?
+            })?
         }
         $(-{
             $($outer_impl:tt)+
@@ -855,42 +7302,133 @@ macro_rules! mongo_db {
             $($($outer_tokens)*)?
 
             pub mod schema {
+                #[allow(unused_imports)]
+                use $crate::{
+                    as_i64 as __as_i64, as_string as __as_string, binary as __binary, iso_date as __iso_date,
+                    object_id as __object_id,
+                };
+
                 $($($inner_tokens)*)?
 
                 $(
-                    $crate::parse_collection_params! {
+                    $crate::expand_fields! {
+                        (base)
+                        [
+                            #[doc = std::concat!("Fields shared by all collections declared with `flatten: ", std::stringify!($base_name), "`.")]
+                            #[derive($crate::serde::Deserialize, $crate::serde::Serialize, $crate::typed_builder::TypedBuilder)]
+                            #[serde(rename_all = "camelCase")]
+                            $(#[$additional_base_attr])*
+                            pub struct $base_name
+                        ]
+                        []
+                        []
+                        []
+                        []
                         $(
-                            $($collection_param_name: $collection_param_value),+
-                        )?
+                            $(#[$($additional_base_field_attr)*])*
+                            $base_field: $base_field_type,
+                        )*
+                    }
+                )*
+
+                $(
+                    $crate::parse_collection_params! {
+                        @attrs [$(
+                            $($collection_param_name$(: $($collection_param_value)::+ $($collection_param_word $(= $($collection_param_word_value)::+)?)?)?),+
+                        )?] []
 
-                        $(#[$additional_coll_attr])*
+                        $(#[$($additional_coll_attr)*])*
 
                         $coll_name {
-                            $(
-                                $(#[$additional_field_attr])*
-                                $field: $field_type
-                            ),*
+                            $($coll_fields)*
                         }
                         $(-{
                             $($inner_impl)+
                         })?
                     }
                 )+
+
+                $(
+                    $crate::expand_fields! {
+                        (base)
+                        [
+                            #[doc = std::concat!("Represents the `", std::stringify!($view_name), "` view on the [`", std::stringify!($view_source), "`] collection in mongodb.")]
+                            #[derive($crate::serde::Deserialize)]
+                            #[serde(rename_all = "camelCase")]
+                            $(#[$additional_view_attr])*
+                            pub struct $view_name
+                        ]
+                        []
+                        []
+                        []
+                        []
+                        $(
+                            $(#[$($additional_view_field_attr)*])*
+                            $view_field: $view_field_type,
+                        )*
+                    }
+
+                    impl $crate::MongoView for $view_name {
+                        const NAME: &'static str = $crate::case!($view_name => Camel);
+                        const VIEW_ON: &'static str = <$view_source as $crate::MongoCollection>::NAME;
+
+                        fn pipeline() -> std::vec::Vec<$crate::mongodb::bson::document::Document> {
+                            std::vec![$($view_stage),*]
+                        }
+                    }
+                )*
+
+                $(
+                    $crate::expand_fields! {
+                        (base)
+                        [
+                            #[doc = std::concat!("Represents the `", std::stringify!($readmodel_name), "` read model, the output of aggregations in mongodb.")]
+                            #[derive($crate::serde::Deserialize)]
+                            #[serde(rename_all = "camelCase")]
+                            $(#[$additional_readmodel_attr])*
+                            pub struct $readmodel_name
+                        ]
+                        []
+                        []
+                        []
+                        []
+                        $(
+                            $(#[$($additional_readmodel_field_attr)*])*
+                            $readmodel_field: $readmodel_field_type,
+                        )*
+                    }
+
+                    impl std::convert::TryFrom<$crate::mongodb::bson::document::Document> for $readmodel_name {
+                        type Error = $crate::mongodb::bson::de::Error;
+
+                        fn try_from(document: $crate::mongodb::bson::document::Document) -> std::result::Result<Self, Self::Error> {
+                            $crate::mongodb::bson::from_document(document)
+                        }
+                    }
+                )*
             }
 
             $crate::expand_main_client ! {
+                [$(
+                    $($db_param_name$(: $($db_param_value)::+)?),+
+                )?]
                 $(#[$additional_db_attr])*
                 $db_name {
                     $(
-                        $(#[$additional_coll_attr])*
-                        $coll_name<_id: none> {
-                            $(
-                                $(#[$additional_field_attr])*
-                                $field: $field_type
-                            ),*
+                        $(#[$($additional_coll_attr)*])*
+                        $coll_name [$(
+                            $($collection_param_name$(: $($collection_param_value)::+ $($collection_param_word $(= $($collection_param_word_value)::+)?)?)?),+
+                        )?] {
+                            $($coll_fields)*
                         }
                     ),+
                 }
+                $(@view $view_name;)*
+                $(@indexes {
+                    $(
+                        $index_coll_name: $([$($index)+])+
+                    );+
+                })?
                 $(-{
                     $($outer_impl)+
                 })?
@@ -0,0 +1,48 @@
+//! This module contains the transformations used by the `#[normalize(...)]` field attribute of [`mongo_db!`](crate::mongo_db).
+//!
+//! They are applied by the generated `MongoCollection::normalize` to a collection struct, or by `MongoCollection::normalize_document` to a serialized copy of it.
+//! The generated write helpers normalize what they send to the server, the document passed to them is only changed by `insert`, which takes it by value.
+//! Fields of type [`Option`] are left unchanged if they are [`None`].
+
+use mongodb::bson::{document::Document, Bson};
+
+/// Types whose text is transformed by `#[normalize(...)]`.
+pub trait Text {
+    /// Returns the text of `self`, or [`None`] if there is no value to transform.
+    fn text_mut(&mut self) -> Option<&mut String>;
+}
+
+impl Text for String {
+    fn text_mut(&mut self) -> Option<&mut String> {
+        Some(self)
+    }
+}
+
+impl<T: Text> Text for Option<T> {
+    fn text_mut(&mut self) -> Option<&mut String> {
+        self.as_mut().and_then(Text::text_mut)
+    }
+}
+
+/// Converts `value` to lowercase.
+pub fn lowercase<T: Text>(value: &mut T) {
+    if let Some(text) = value.text_mut() {
+        *text = text.to_lowercase();
+    }
+}
+
+/// Removes leading and trailing whitespace of `value`.
+pub fn trim<T: Text>(value: &mut T) {
+    if let Some(text) = value.text_mut() {
+        *text = text.trim().to_owned();
+    }
+}
+
+/// Applies `normalization` to the text stored at `key` in `document`, used to normalize serialized documents.
+///
+/// Leaves `document` unchanged if `key` is [`None`] or not stored as a string.
+pub fn document_field(document: &mut Document, key: Option<&str>, normalization: fn(&mut String)) {
+    if let Some(Bson::String(text)) = key.and_then(|key| document.get_mut(key)) {
+        normalization(text);
+    }
+}
@@ -0,0 +1,71 @@
+//! This module contains the tolerant deserializer used by the default `_id` field of collections declared with [`mongo_db!`](crate::mongo_db).
+//!
+//! Use it directly with `#[serde(deserialize_with = "mongodb_ext::object_id::deserialize")]` on structs not declared with [`mongo_db!`](crate::mongo_db).
+//! Fields of type [`Option`] need `#[serde(default)]` as well to accept missing ids.
+
+use crate::{
+    mongodb::bson::{oid::ObjectId, Bson},
+    serde::de::{Deserialize, Deserializer, Error as DeError},
+};
+
+use std::fmt::Display;
+
+/// Types that can be deserialized by [`deserialize`].
+pub trait TolerantObjectId: Sized {
+    /// Deserializes `Self` from an [`ObjectId`], a hex string or an extended JSON object `{ "$oid": ... }`.
+    fn deserialize_object_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+/// Parses `hex` as [`ObjectId`], ignoring surrounding whitespace and the case of the hex digits.
+fn parse_hex<E: DeError>(hex: &str) -> Result<ObjectId, E> {
+    ObjectId::parse_str(hex.trim()).map_err(|_| {
+        E::custom(format!(
+            "invalid ObjectId `{}`, expected a 24-character hex string",
+            hex
+        ))
+    })
+}
+
+/// Error for BSON values that do not represent an [`ObjectId`].
+fn unexpected<E: DeError>(found: impl Display) -> E {
+    E::custom(format!(
+        "expected an ObjectId, a hex string or {{ \"$oid\": ... }}, found {}",
+        found
+    ))
+}
+
+/// Parses `bson` as [`ObjectId`], returns [`None`] if it is `null`.
+fn parse_bson<E: DeError>(bson: Bson) -> Result<Option<ObjectId>, E> {
+    match bson {
+        Bson::Null => Ok(None),
+        Bson::ObjectId(oid) => Ok(Some(oid)),
+        Bson::String(hex) => parse_hex(&hex).map(Some),
+        Bson::Document(document) => match document.get("$oid") {
+            Some(Bson::String(hex)) if document.len() == 1 => parse_hex(hex).map(Some),
+            _ => Err(unexpected(&document)),
+        },
+        other => Err(unexpected(other)),
+    }
+}
+
+impl TolerantObjectId for ObjectId {
+    fn deserialize_object_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        parse_bson(Bson::deserialize(deserializer)?)?
+            .ok_or_else(|| D::Error::custom("expected an ObjectId, found null"))
+    }
+}
+
+impl TolerantObjectId for Option<ObjectId> {
+    fn deserialize_object_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        parse_bson(Bson::deserialize(deserializer)?)
+    }
+}
+
+/// Deserializes an [`ObjectId`] given as BSON ObjectId, as hex string (eg. `"0123456789abcdef01234567"`) or as extended JSON (eg. `{ "$oid": "0123456789abcdef01234567" }`).
+///
+/// Hex digits may be upper- or lowercase, surrounding whitespace is ignored.
+pub fn deserialize<'de, T: TolerantObjectId, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    T::deserialize_object_id(deserializer)
+}
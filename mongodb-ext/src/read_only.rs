@@ -0,0 +1,131 @@
+//! This module contains the read-only collection handle used by database handlers declared with `<read_only>`.
+
+use crate::{
+    mongodb::{
+        bson::{document::Document, Bson},
+        error::Result as MongoResult,
+        options::{
+            AggregateOptions, CountOptions, DistinctOptions, EstimatedDocumentCountOptions,
+            FindOneOptions, FindOptions,
+        },
+        Collection, Cursor, Namespace,
+    },
+    serde::de::DeserializeOwned,
+};
+
+#[cfg(feature = "change-streams")]
+use crate::mongodb::{
+    change_stream::{event::ChangeStreamEvent, ChangeStream},
+    options::ChangeStreamOptions,
+};
+
+/// Collection handle that only exposes read operations.
+///
+/// Database handlers declared with `<read_only>` in [`mongo_db!`](crate::mongo_db) use this type instead of [`Collection`] for all collection handles.
+/// All methods forward to the [`Collection`] method with the same name.
+///
+/// This type does not give access to the wrapped [`Collection`] by design.
+#[derive(Debug)]
+pub struct ReadOnlyCollection<T> {
+    collection: Collection<T>,
+}
+
+// `derive` would require `T: Clone`, but `Collection<T>` is `Clone` for any `T`.
+impl<T> Clone for ReadOnlyCollection<T> {
+    fn clone(&self) -> Self {
+        Self {
+            collection: self.collection.clone(),
+        }
+    }
+}
+
+impl<T> From<Collection<T>> for ReadOnlyCollection<T> {
+    fn from(collection: Collection<T>) -> Self {
+        Self { collection }
+    }
+}
+
+impl<T> ReadOnlyCollection<T> {
+    /// Returns the name of the collection.
+    pub fn name(&self) -> &str {
+        self.collection.name()
+    }
+
+    /// Returns the namespace of the collection.
+    pub fn namespace(&self) -> Namespace {
+        self.collection.namespace()
+    }
+
+    /// Runs an aggregation operation.
+    ///
+    /// Note that pipelines containing `$out` / `$merge` stages are not prevented from writing.
+    pub async fn aggregate(
+        &self,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<AggregateOptions>>,
+    ) -> MongoResult<Cursor<Document>> {
+        self.collection.aggregate(pipeline, options).await
+    }
+
+    /// Estimates the number of documents in the collection using collection metadata.
+    pub async fn estimated_document_count(
+        &self,
+        options: impl Into<Option<EstimatedDocumentCountOptions>>,
+    ) -> MongoResult<u64> {
+        self.collection.estimated_document_count(options).await
+    }
+
+    /// Gets the number of documents matching `filter`.
+    pub async fn count_documents(
+        &self,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<CountOptions>>,
+    ) -> MongoResult<u64> {
+        self.collection.count_documents(filter, options).await
+    }
+
+    /// Finds the distinct values of the field specified by `field_name` across the collection.
+    pub async fn distinct(
+        &self,
+        field_name: impl AsRef<str>,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<DistinctOptions>>,
+    ) -> MongoResult<Vec<Bson>> {
+        self.collection.distinct(field_name, filter, options).await
+    }
+}
+
+impl<T> ReadOnlyCollection<T>
+where
+    T: DeserializeOwned + Unpin + Send + Sync,
+{
+    /// Finds the documents in the collection matching `filter`.
+    pub async fn find(
+        &self,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<FindOptions>>,
+    ) -> MongoResult<Cursor<T>> {
+        self.collection.find(filter, options).await
+    }
+
+    /// Finds a single document in the collection matching `filter`.
+    pub async fn find_one(
+        &self,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<FindOneOptions>>,
+    ) -> MongoResult<Option<T>> {
+        self.collection.find_one(filter, options).await
+    }
+
+    /// Opens a change stream on the collection.
+    ///
+    /// Feature flag _"change-streams"_ is needed to use this method.
+    #[cfg(feature = "change-streams")]
+    pub async fn watch(
+        &self,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<ChangeStreamOptions>>,
+    ) -> MongoResult<ChangeStream<ChangeStreamEvent<T>>> {
+        self.collection.watch(pipeline, options).await
+    }
+}
@@ -0,0 +1,27 @@
+//! This module contains the blocking client shutdown used by database handlers declared with `<shutdown_on_drop>`.
+
+use {
+    crate::mongodb::Client,
+    tokio::runtime::{Builder, Handle, RuntimeFlavor},
+};
+
+/// Shuts down `client` from a synchronous context, eg. a [`Drop`] implementation.
+///
+/// - Inside of a multi-threaded tokio runtime the shutdown blocks the current thread until it is finished.
+/// - Inside of a current-thread tokio runtime blocking would dead-lock, thus the shutdown is spawned onto the runtime and not waited for.
+/// - Outside of a tokio runtime a temporary runtime is created to run the shutdown.
+pub fn shutdown_blocking(client: Client) {
+    match Handle::try_current() {
+        Ok(handle) => match handle.runtime_flavor() {
+            RuntimeFlavor::CurrentThread => {
+                handle.spawn(client.shutdown());
+            }
+            _ => tokio::task::block_in_place(|| handle.block_on(client.shutdown())),
+        },
+        Err(_) => {
+            if let Ok(runtime) = Builder::new_current_thread().enable_all().build() {
+                runtime.block_on(client.shutdown());
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! This module contains helpers enabled by the _"testing"_ feature.
+
+use crate::{
+    mongodb::bson::{self, document::Document, Bson},
+    serde::{de::DeserializeOwned, Serialize},
+};
+
+/// Discrepancies between a document and the collection type it is supposed to be stored as, see [`schema_drift`].
+///
+/// Nested fields are given as dotted paths, eg. `address.zipCode`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDrift {
+    /// Fields of the collection type that are not in the document, thus have been filled with defaults.
+    pub missing: Vec<String>,
+    /// Fields of the document that are not part of the collection type, thus are dropped when deserializing.
+    pub extra: Vec<String>,
+    /// The error message if the document cannot be deserialized (or serialized again) at all.
+    pub error: Option<String>,
+}
+
+impl SchemaDrift {
+    /// Returns `true` if the document matches the collection type exactly.
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.error.is_none()
+    }
+}
+
+/// Compares `document` to the collection type `T` without failing.
+///
+/// `document` is deserialized into `T` and serialized again, the keys of both documents are compared afterwards.
+/// Thus renamed fields, defaults and skipped fields are accounted for the same way the driver sees them.
+/// A `_id` only present in `document` is not reported, since mongoDB always stores one.
+pub fn schema_drift<T>(document: &Document) -> SchemaDrift
+where
+    T: Serialize + DeserializeOwned,
+{
+    let round_trip = bson::from_document::<T>(document.clone())
+        .map_err(|e| e.to_string())
+        .and_then(|typed| {
+            bson::to_raw_document_buf(&typed)
+                .map_err(|e| e.to_string())?
+                .to_document()
+                .map_err(|e| e.to_string())
+        });
+    let mut drift = SchemaDrift::default();
+    match round_trip {
+        Ok(expected) => {
+            compare_keys(document, &expected, "", &mut drift);
+            drift.extra.retain(|field| field != "_id");
+        }
+        Err(error) => drift.error = Some(error),
+    }
+    drift
+}
+
+/// Collects the keys that are only in `actual` / only in `expected` into `drift`, recursing into subdocuments present in both.
+fn compare_keys(actual: &Document, expected: &Document, prefix: &str, drift: &mut SchemaDrift) {
+    for (key, value) in actual {
+        let path = format!("{}{}", prefix, key);
+        match (value, expected.get(key)) {
+            (_, None) => drift.extra.push(path),
+            (Bson::Document(actual), Some(Bson::Document(expected))) => {
+                compare_keys(actual, expected, &format!("{}.", path), drift)
+            }
+            _ => {}
+        }
+    }
+    for key in expected.keys() {
+        if !actual.contains_key(key) {
+            drift.missing.push(format!("{}{}", prefix, key));
+        }
+    }
+}
@@ -2,21 +2,116 @@
 
 use crate::{
     async_trait::async_trait,
+    deserialize_document,
     mongodb::{
-        bson::document::Document, error::Result as MongoResult, Client as DbClient, Database,
+        bson::{doc, document::Document},
+        error::{Error as MongoError, Result as MongoResult},
+        options::{ClientOptions, Compressor, CreateCollectionOptions, FindOptions, TlsOptions},
+        Client as DbClient, ClientSession, Collection, Database, IndexModel,
     },
+    HealthReport, ValidationError,
+};
+
+use serde::de::DeserializeOwned;
+
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
 };
 
 /// Trait that is implemented automatically on each collection struct by [`mongo_db`].
 pub trait MongoCollection {
     /// The collection's name.
     const NAME: &'static str;
+    /// The case of the stored field names, given as in `#[serde(rename_all = "...")]`.
+    ///
+    /// Set it with the `rename_all` collection parameter in [`mongo_db!`](crate::mongo_db), it is `"camelCase"` otherwise.
+    /// Collections declared with `rename_all: none` return `"none"`, their fields are stored as written.
+    const RENAME_ALL: &'static str = "camelCase";
     /// The collection's schema version.
     ///
     /// Change that in your [`mongo_db!`](crate::mongo_db) invocation every time you change your schema.
     ///
     /// You do not actually need to use this in your schema, but it is implemented for your convinience.
     const SCHEMA_VERSION: i32;
+    /// The collection's human-readable schema version, eg. `"v2.1"`.
+    ///
+    /// Set it with the `version_tag` collection parameter in [`mongo_db!`](crate::mongo_db), it is empty otherwise.
+    const SCHEMA_VERSION_TAG: &'static str = "";
+    /// Whether the collection is declared with the `soft_delete` parameter in [`mongo_db!`](crate::mongo_db).
+    ///
+    /// Such collections have a field `deleted_at`, which is set instead of removing documents.
+    const SOFT_DELETE: bool = false;
+
+    /// Default options used by the generated `find_{collection_name}` helper on the database handler if no options are given.
+    ///
+    /// Set them with the `default_find` collection parameter in [`mongo_db!`](crate::mongo_db), returns [`None`] otherwise.
+    fn default_find_options() -> Option<FindOptions> {
+        None
+    }
+
+    /// Sort order of the generated `latest` helpers, eg. `{ "createdAt": -1 }`.
+    ///
+    /// Set it with the `order_by` collection parameter in [`mongo_db!`](crate::mongo_db), returns [`None`] otherwise.
+    fn order_by() -> Option<Document> {
+        None
+    }
+
+    /// The stored date field and the expiry of the collection's TTL index, eg. `("createdAt", 7 days)`.
+    ///
+    /// Set it with the `#[ttl(field = .., seconds = ..)]` collection attribute in [`mongo_db!`](crate::mongo_db), returns [`None`] otherwise.
+    fn ttl() -> Option<(&'static str, Duration)> {
+        None
+    }
+
+    /// Indexes declared with the `indexes` collection parameter in [`mongo_db!`](crate::mongo_db).
+    ///
+    /// These are included in [`MongoCollection::index_models`].
+    fn indexes() -> Vec<IndexModel> {
+        Vec::new()
+    }
+
+    /// Indexes declared with the `indexes` collection parameter, with field attributes, eg. `#[alternate_key]`, and the TTL index of [`MongoCollection::ttl`] in [`mongo_db!`](crate::mongo_db).
+    ///
+    /// These are included in [`MongoClient::index_models`] next to the indexes of the `@indexes` block.
+    fn index_models() -> Vec<IndexModel> {
+        Vec::new()
+    }
+
+    /// Checks the validator field attributes declared in [`mongo_db!`](crate::mongo_db), eg. `#[max_len(10)]`.
+    ///
+    /// Returns the first violation, collections without validator attributes are always valid.
+    /// The generated write helpers call it before writing, see [`validate_before_write`](crate::validate_before_write).
+    fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+
+    /// Applies the normalization field attributes declared in [`mongo_db!`](crate::mongo_db), eg. `#[normalize(lowercase)]`.
+    ///
+    /// The generated `insert` helpers call it before writing, collections without normalization attributes are left unchanged.
+    fn normalize(&mut self) {}
+
+    /// Applies the normalization field attributes to a serialized document of this collection, see [`normalize`](MongoCollection::normalize).
+    ///
+    /// The generated write helpers that borrow or serialize their documents call it on the serialized copy before writing.
+    fn normalize_document(_document: &mut Document) {}
+}
+
+/// Trait that is implemented automatically on each collection struct with an `_id` field by [`mongo_db`], ie. all collections not declared with `_id: none`.
+pub trait MongoCollectionId: MongoCollection {
+    /// The type inside the [`Option`] of the `_id` field, [`DefaultId`](crate::DefaultId) if the collection is declared without `_id`.
+    type Id;
+}
+
+/// Trait that is implemented automatically on each view struct declared with `@view` in [`mongo_db`].
+pub trait MongoView {
+    /// The view's name.
+    const NAME: &'static str;
+    /// The name of the collection the view is backed by.
+    const VIEW_ON: &'static str;
+
+    /// The aggregation pipeline that defines the view.
+    fn pipeline() -> Vec<Document>;
 }
 
 /// Async trait that is implemented automatically on the database handler struct by [`mongo_db`].
@@ -27,16 +122,375 @@ where
 {
     /// The database's name.
     const NAME: &'static str;
+    /// The names of all collections declared in [`mongo_db!`](crate::mongo_db), in declaration order.
+    ///
+    /// Empty for handlers not declared with [`mongo_db!`](crate::mongo_db).
+    const COLLECTION_NAMES: &'static [&'static str] = &[];
+    /// The environment variable [`from_env`](MongoClient::from_env) reads the connection string from.
+    ///
+    /// Set it with the `env` database parameter in [`mongo_db!`](crate::mongo_db), it is `"MONGODB_URI"` otherwise.
+    const ENV_VAR: &'static str = "MONGODB_URI";
+    /// The type of the handles returned by [`collection`](MongoClient::collection).
+    ///
+    /// Handlers declared with [`mongo_db!`](crate::mongo_db) use a [`Collection`], or a [`ReadOnlyCollection`](crate::ReadOnlyCollection) if the database is declared `read_only`.
+    type Handle<T>: From<Collection<T>>;
     /// Initializer funtion of the database.
     ///
-    /// Creates a database [`DbClient`] and calls [`new_with_client`](MongoClient::new_with_client) then.
+    /// Parses `connection_str` and calls [`new_with_options`](MongoClient::new_with_options) then.
     async fn new(connection_str: &str) -> MongoResult<Self>;
+    /// Initializer function that reads the connection string from the environment variable [`ENV_VAR`](MongoClient::ENV_VAR).
+    ///
+    /// Calls [`new`](MongoClient::new) then.
+    /// Fails with a [custom error](MongoError::custom) containing a [`String`] message if the variable is not set or not valid unicode.
+    async fn from_env() -> MongoResult<Self> {
+        let connection_str = std::env::var(Self::ENV_VAR).map_err(|e| {
+            MongoError::custom(format!(
+                "cannot read environment variable `{}`: {}",
+                Self::ENV_VAR,
+                e
+            ))
+        })?;
+        Self::new(&connection_str).await
+    }
+    /// Initializer function that uses fully built client options, eg. for authentication, TLS or an app name.
+    ///
+    /// Creates a database [`DbClient`] using [`DbClient::with_options`] and calls [`new_with_client`](MongoClient::new_with_client) then.
+    async fn new_with_options(options: ClientOptions) -> MongoResult<Self> {
+        Self::new_with_client(DbClient::with_options(options)?)
+    }
     /// Initializer function that uses the given client.
     ///
     /// Useful when interacting with multiple databases.
     fn new_with_client(client: DbClient) -> MongoResult<Self>;
+    /// Initializer function that sets the connection pool's size.
+    ///
+    /// Overrides `minPoolSize` / `maxPoolSize` of the connection string with `min` / `max`.
+    /// Fails with a [custom error](MongoError::custom) containing a [`String`] message if `min` is greater than `max`.
+    async fn new_with_pool(connection_str: &str, min: u32, max: u32) -> MongoResult<Self> {
+        if min > max {
+            return Err(MongoError::custom(format!(
+                "minPoolSize ({}) must not be greater than maxPoolSize ({})",
+                min, max
+            )));
+        }
+        let mut options = ClientOptions::parse(connection_str).await?;
+        options.min_pool_size = Some(min);
+        options.max_pool_size = Some(max);
+        Self::new_with_options(options).await
+    }
+    /// Initializer function that enables or disables retryable writes.
+    ///
+    /// Overrides `retryWrites` of the connection string with `enabled`.
+    /// Retryable writes are enabled by default, disabling them may be needed if a proxy between the client and the server does not support them.
+    ///
+    /// Note that writes inside of a transaction are never retried individually, regardless of this setting.
+    /// Committing / aborting a transaction is retried once independently of this setting, too.
+    async fn new_with_retryable_writes(connection_str: &str, enabled: bool) -> MongoResult<Self> {
+        let mut options = ClientOptions::parse(connection_str).await?;
+        options.retry_writes = Some(enabled);
+        Self::new_with_options(options).await
+    }
+    /// Initializer function that enables network compression.
+    ///
+    /// Overrides `compressors` of the connection string with `compressors`, in order of preference.
+    /// The server picks the first compressor it supports, messages are sent uncompressed if it supports none of them.
+    ///
+    /// Each compressor needs a feature of this crate, which enables the driver feature of the same name:
+    /// `zstd-compression` for `Compressor::Zstd`, `snappy-compression` for `Compressor::Snappy` and `zlib-compression` for `Compressor::Zlib`.
+    async fn new_with_compressors(
+        connection_str: &str,
+        compressors: Vec<Compressor>,
+    ) -> MongoResult<Self> {
+        let mut options = ClientOptions::parse(connection_str).await?;
+        options.compressors = Some(compressors);
+        Self::new_with_options(options).await
+    }
+    /// Initializer function that enables TLS using a custom certificate authority.
+    ///
+    /// `ca_file` is the path to the CA certificate(s) the server certificate is validated against.
+    /// `cert_file` is the path to a PEM file containing both the client certificate and its private key, pass it for mutual TLS.
+    /// All other TLS settings of the connection string are replaced.
+    ///
+    /// TLS is provided by the driver's default `rustls` backend, enable the driver's `openssl-tls` feature to use OpenSSL instead.
+    /// The files are read immediately, thus this fails if they cannot be read or parsed.
+    async fn new_with_tls(
+        connection_str: &str,
+        ca_file: PathBuf,
+        cert_file: Option<PathBuf>,
+    ) -> MongoResult<Self> {
+        let mut options = ClientOptions::parse(connection_str).await?;
+        options.tls = Some(
+            TlsOptions::builder()
+                .ca_file_path(ca_file)
+                .cert_key_file_path(cert_file)
+                .build()
+                .into(),
+        );
+        Self::new_with_options(options).await
+    }
+    /// Rebuilds the client, the database and all collection handles in place using a new connection string.
+    ///
+    /// Useful for long-running services that need to rotate credentials without tearing down the handler.
+    ///
+    /// Replaces the whole handler with one created by [`new`](MongoClient::new) by default, [`mongo_db!`](crate::mongo_db) replaces the fields one by one.
+    async fn reconnect(&mut self, connection_str: &str) -> MongoResult<()> {
+        *self = Self::new(connection_str).await?;
+        Ok(())
+    }
+    /// Runs `command` on the database and returns the server's response.
+    ///
+    /// Escape hatch for commands that have no dedicated method, eg. admin commands.
+    /// Forwards to [`Database::run_command`] without a selection criteria.
+    async fn run_command(&self, command: Document) -> MongoResult<Document> {
+        self.database().run_command(command, None).await
+    }
     /// Method that sends a ping command to the database.
-    async fn ping(&self) -> MongoResult<Document>;
+    async fn ping(&self) -> MongoResult<Document> {
+        self.run_command(doc! { "ping": 1 }).await
+    }
+    /// Sends a ping command to the database and returns the time the round trip took.
+    ///
+    /// Useful to feed monitoring, the response itself carries no information.
+    async fn ping_latency(&self) -> MongoResult<Duration> {
+        let start = Instant::now();
+        self.ping().await?;
+        Ok(start.elapsed())
+    }
+    /// Runs a couple of independent checks that confirm a working connection, see [`HealthReport`].
+    ///
+    /// Errors of single checks are stored in the report instead of being returned, thus this never fails.
+    /// Useful as a single diagnostic at startup.
+    async fn health_report(&self) -> HealthReport {
+        let ping_latency = self.ping_latency().await;
+        let server_version = self
+            .run_command(doc! { "buildInfo": 1 })
+            .await
+            .and_then(|info| {
+                info.get_str("version")
+                    .map(String::from)
+                    .map_err(|e| MongoError::custom(e.to_string()))
+            });
+        let collections = self
+            .database()
+            .list_collection_names(None)
+            .await
+            .map(|existing| {
+                Self::COLLECTION_NAMES
+                    .iter()
+                    .map(|name| (*name, existing.iter().any(|existing| existing == name)))
+                    .collect()
+            });
+        HealthReport {
+            ping_latency,
+            server_version,
+            collections,
+        }
+    }
+    /// Sets the database profiler's level by running the `profile` command.
+    ///
+    /// `level` is `0` (off), `1` (slow operations only) or `2` (all operations).
+    /// Operations taking longer than `slow_ms` milliseconds are considered slow, the server's setting is kept if [`None`].
+    ///
+    /// Returns the command's response, which contains the previous level in `was`.
+    async fn set_profiling_level(&self, level: i32, slow_ms: Option<i32>) -> MongoResult<Document> {
+        let mut command = doc! { "profile": level };
+        if let Some(slow_ms) = slow_ms {
+            command.insert("slowms", slow_ms);
+        }
+        self.run_command(command).await
+    }
+    /// Reads the database profiler's current settings without changing them.
+    ///
+    /// The current level is returned in `was`, the slow operation threshold in `slowms`.
+    async fn profiling_status(&self) -> MongoResult<Document> {
+        self.run_command(doc! { "profile": -1 }).await
+    }
+    /// Starts a new [`ClientSession`].
+    ///
+    /// Starting a session has a cost, thus reuse the returned session for all operations that belong together instead of starting one per operation.
+    /// Pass it to the `*_with_session` methods of the collection handles, eg. [`Collection::insert_one_with_session`](mongodb::Collection::insert_one_with_session),
+    /// and use [`ClientSession::start_transaction`] / [`ClientSession::commit_transaction`] to run multiple transactions one after another on the same session.
+    /// The session ends when it is dropped.
+    ///
+    /// ```rust
+    /// use mongodb_ext::{mongo_db, MongoClient};
+    ///
+    /// mongo_db! {
+    ///     SomeDatabase {
+    ///         Account {
+    ///             balance: i64,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// async fn open_accounts(
+    ///     mongo: &mongo::SomeDatabase,
+    ///     first: mongo::schema::Account,
+    ///     second: mongo::schema::Account,
+    /// ) -> mongodb::error::Result<()> {
+    ///     let mut session = mongo.start_session().await?;
+    ///
+    ///     session.start_transaction(None).await?;
+    ///     mongo.account_coll.insert_one_with_session(first, None, &mut session).await?;
+    ///     mongo.account_coll.insert_one_with_session(second, None, &mut session).await?;
+    ///     session.commit_transaction().await
+    /// }
+    /// ```
+    async fn start_session(&self) -> MongoResult<ClientSession> {
+        self.client().start_session(None).await
+    }
+
+    /// Checks whether a collection called `name` exists in the database.
+    ///
+    /// Only the collection with the given name is listed, thus this is cheap even for databases with many collections.
+    async fn collection_exists(&self, name: &str) -> MongoResult<bool> {
+        Ok(self
+            .database()
+            .list_collection_names(doc! { "name": name })
+            .await?
+            .iter()
+            .any(|collection_name| collection_name == name))
+    }
+
+    /// Returns the namespace `{database}.{name}` of the collection called `name`, eg. for logging and error messages.
+    fn namespace(&self, name: &str) -> String {
+        format!("{}.{}", self.database().name(), name)
+    }
+
+    /// Returns a handle to the collection of `T`, eg. in code that is generic over collection types and does not know the handle's field name.
+    ///
+    /// The handle is created from [`MongoCollection::NAME`] and has the type [`Handle`](MongoClient::Handle), thus databases declared `read_only` only hand out read-only handles.
+    fn collection<T>(&self) -> Self::Handle<T>
+    where
+        T: MongoCollection,
+    {
+        Self::Handle::from(self.database().collection(T::NAME))
+    }
+
+    /// Finds the first document in the collection called `collection_name` that matches `filter` and deserializes it into `T`.
+    ///
+    /// `T` does not need to be the collection's struct, eg. a slimmer read model that only declares some of the fields.
+    /// Errors name the collection and the document's `_id`, see [`deserialize_document`](crate::deserialize_document).
+    async fn find_one_as<T>(
+        &self,
+        collection_name: &str,
+        filter: Document,
+    ) -> MongoResult<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.database()
+            .collection::<Document>(collection_name)
+            .find_one(filter, None)
+            .await?
+            .map(|document| deserialize_document(collection_name, document))
+            .transpose()
+    }
+
+    /// Lists the names of all databases on the server, eg. to discover the databases of all tenants.
+    ///
+    /// Needs the `listDatabases` privilege, users without it only see the databases they have privileges on.
+    async fn list_databases(&self) -> MongoResult<Vec<String>> {
+        self.client().list_database_names(None, None).await
+    }
+
+    /// Runs the `collStats` command on the collection called `name`.
+    ///
+    /// Returns the raw response, which contains eg. the document count in `count`, the storage size in `storageSize` and the size of each index in `indexSizes`.
+    async fn collection_stats(&self, name: &str) -> MongoResult<Document> {
+        self.run_command(doc! { "collStats": name }).await
+    }
+
+    /// Returns all indexes declared in the `@indexes` block of [`mongo_db!`](crate::mongo_db) together with the name of the collection they belong to.
+    ///
+    /// Returns no indexes for handlers not declared with [`mongo_db!`](crate::mongo_db).
+    fn index_models() -> Vec<(&'static str, IndexModel)> {
+        Vec::new()
+    }
+    /// Creates all indexes returned by [`index_models`](MongoClient::index_models).
+    ///
+    /// Creating an index that already exists with the same specification is a no-op on the server.
+    async fn create_indexes(&self) -> MongoResult<()> {
+        for (collection_name, model) in Self::index_models() {
+            self.database()
+                .collection::<Document>(collection_name)
+                .create_index(model, None)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the unique indexes of [`index_models`](MongoClient::index_models), eg. those of fields declared with `#[unique]`, together with the name of the collection they belong to.
+    fn unique_index_models() -> Vec<(&'static str, IndexModel)> {
+        Self::index_models()
+            .into_iter()
+            .filter(|(_, model)| {
+                model
+                    .options
+                    .as_ref()
+                    .and_then(|options| options.unique)
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+    /// Creates all indexes returned by [`unique_index_models`](MongoClient::unique_index_models) across all collections.
+    ///
+    /// Unlike [`create_indexes`](MongoClient::create_indexes) other indexes are left out, thus only the indexes that keep the data consistent are built, eg. when starting an application.
+    /// Indexes are only created, indexes that are no longer declared are not dropped.
+    async fn sync_indexes(&self) -> MongoResult<()> {
+        for (collection_name, model) in Self::unique_index_models() {
+            self.database()
+                .collection::<Document>(collection_name)
+                .create_index(model, None)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the name and the creation options of all views declared with `@view` in [`mongo_db!`](crate::mongo_db).
+    ///
+    /// Returns no views for handlers not declared with [`mongo_db!`](crate::mongo_db).
+    fn view_options() -> Vec<(&'static str, CreateCollectionOptions)> {
+        Vec::new()
+    }
+    /// Creates all views returned by [`view_options`](MongoClient::view_options).
+    ///
+    /// Fails if a collection or view with the same name already exists.
+    async fn create_views(&self) -> MongoResult<()> {
+        for (view_name, options) in Self::view_options() {
+            self.database()
+                .create_collection(view_name, options)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Drops the whole database.
+    ///
+    /// **This is destructive**: all documents in the database are lost, including those of collections that are not declared in [`mongo_db!`](crate::mongo_db).
+    /// Handlers declared with `<read_only>` refuse to drop the database and return an error containing a [`String`] message instead.
+    async fn drop_database(&self) -> MongoResult<()> {
+        self.database().drop(None).await
+    }
+
+    /// Drops the whole database and provisions it again, giving tests a clean slate.
+    ///
+    /// After dropping, all collections in [`COLLECTION_NAMES`](MongoClient::COLLECTION_NAMES) are created,
+    /// followed by the indexes of [`create_indexes`](MongoClient::create_indexes) and the views of [`create_views`](MongoClient::create_views).
+    ///
+    /// **This is destructive**: all documents in the database are lost, including those of collections that are not declared in [`mongo_db!`](crate::mongo_db).
+    /// Only use it against test databases, thus it is only available with the _"testing"_ feature.
+    #[cfg(feature = "testing")]
+    async fn reset(&self) -> MongoResult<()> {
+        self.drop_database().await?;
+        for collection_name in Self::COLLECTION_NAMES {
+            self.database()
+                .create_collection(*collection_name, None)
+                .await?;
+        }
+        self.create_indexes().await?;
+        self.create_views().await
+    }
 
     /// Returns a reference to the database object.
     fn database(&self) -> &Database;
@@ -0,0 +1,215 @@
+//! This module contains the checks used by the validator field attributes of [`mongo_db!`](crate::mongo_db), eg. `#[max_len(10)]`.
+//!
+//! Each check returns a message describing the violation, which the generated `MongoCollection::validate` wraps into a [`ValidationError`].
+//! Fields of type [`Option`] pass all checks if they are [`None`].
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+/// Error returned by [`MongoCollection::validate`](crate::MongoCollection::validate) if a field violates one of its validator attributes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Name of the collection, see [`MongoCollection::NAME`](crate::MongoCollection::NAME).
+    pub collection: &'static str,
+    /// Name of the field as declared in Rust.
+    pub field: &'static str,
+    /// Description of the violation, eg. `length must be at most 10`.
+    pub message: String,
+}
+
+impl ValidationError {
+    /// Creates a new error for `field` of `collection`.
+    pub fn new(collection: &'static str, field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            collection,
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "invalid field `{}` of `{}`: {}",
+            self.field, self.collection, self.message
+        )
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Types that have a length checked by `#[min_len(n)]` / `#[max_len(n)]`.
+///
+/// Strings count characters, collections count elements.
+pub trait Length {
+    /// Returns the length of `self`, or [`None`] if there is no value to check.
+    fn length(&self) -> Option<usize>;
+}
+
+impl Length for String {
+    fn length(&self) -> Option<usize> {
+        Some(self.chars().count())
+    }
+}
+
+impl<T> Length for Vec<T> {
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<T> Length for VecDeque<T> {
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<T, S> Length for HashSet<T, S> {
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<T> Length for BTreeSet<T> {
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<K, V, S> Length for HashMap<K, V, S> {
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<K, V> Length for BTreeMap<K, V> {
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<T: Length> Length for Option<T> {
+    fn length(&self) -> Option<usize> {
+        self.as_ref().and_then(Length::length)
+    }
+}
+
+/// Types that have a value checked by `#[min(value)]` / `#[max(value)]`.
+pub trait Bounded {
+    /// Type of the bounds, eg. `u32` for `Option<u32>`.
+    type Bound: PartialOrd + Display;
+
+    /// Returns the value of `self`, or [`None`] if there is no value to check.
+    fn bounded(&self) -> Option<&Self::Bound>;
+}
+
+macro_rules! impl_bounded {
+    ($($ty:ty),+) => {
+        $(
+            impl Bounded for $ty {
+                type Bound = Self;
+
+                fn bounded(&self) -> Option<&Self::Bound> {
+                    Some(self)
+                }
+            }
+        )+
+    };
+}
+
+impl_bounded!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+impl<T: Bounded> Bounded for Option<T> {
+    type Bound = T::Bound;
+
+    fn bounded(&self) -> Option<&Self::Bound> {
+        self.as_ref().and_then(Bounded::bounded)
+    }
+}
+
+/// Types whose elements are checked by `#[array(...)]`.
+pub trait Array {
+    /// Type of the elements, eg. `String` for `Vec<String>`.
+    type Item;
+
+    /// Returns the elements of `self`, or [`None`] if there is no value to check.
+    fn items(&self) -> Option<&[Self::Item]>;
+}
+
+impl<T> Array for Vec<T> {
+    type Item = T;
+
+    fn items(&self) -> Option<&[Self::Item]> {
+        Some(self)
+    }
+}
+
+impl<T: Array> Array for Option<T> {
+    type Item = T::Item;
+
+    fn items(&self) -> Option<&[Self::Item]> {
+        self.as_ref().and_then(Array::items)
+    }
+}
+
+/// Checks that the length of `value` is at least `min`.
+pub fn min_len<T: Length>(value: &T, min: usize) -> Result<(), String> {
+    match value.length() {
+        Some(length) if length < min => Err(format!("length must be at least {}", min)),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the length of `value` is at most `max`.
+pub fn max_len<T: Length>(value: &T, max: usize) -> Result<(), String> {
+    match value.length() {
+        Some(length) if length > max => Err(format!("length must be at most {}", max)),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that `value` is at least `min`.
+pub fn min<T: Bounded>(value: &T, min: T::Bound) -> Result<(), String> {
+    match value.bounded() {
+        Some(value) if *value < min => Err(format!("must be at least {}", min)),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that `value` is at most `max`.
+pub fn max<T: Bounded>(value: &T, max: T::Bound) -> Result<(), String> {
+    match value.bounded() {
+        Some(value) if *value > max => Err(format!("must be at most {}", max)),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that `value` has at most `max` elements.
+pub fn max_items<T: Array>(value: &T, max: usize) -> Result<(), String> {
+    match value.items() {
+        Some(items) if items.len() > max => Err(format!("must have at most {} items", max)),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that no two elements of `value` are equal.
+pub fn unique_items<T: Array>(value: &T) -> Result<(), String>
+where
+    T::Item: PartialEq,
+{
+    let items = value.items().unwrap_or_default();
+    if items
+        .iter()
+        .enumerate()
+        .any(|(i, item)| items[..i].contains(item))
+    {
+        Err(String::from("items must be unique"))
+    } else {
+        Ok(())
+    }
+}
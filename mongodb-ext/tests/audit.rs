@@ -0,0 +1,32 @@
+use mongodb_ext::{mongo_db, MongoClient};
+
+mongo_db! {
+    Database<audit: AuditLog> {
+        {
+            use mongodb::bson::{Bson, DateTime};
+        }
+        #[derive(Debug)]
+        User {
+            name: String,
+        };
+        #[derive(Debug)]
+        AuditLog {
+            operation: String,
+            collection: String,
+            timestamp: DateTime,
+            document_id: Bson,
+        }
+    }
+}
+
+#[test]
+pub fn check_audited_handler() {
+    let db = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    assert_eq!(db.audit_log_coll.name(), "auditLog");
+
+    // fails before writing anything, thus nothing is audited
+    let user = mongo::schema::User::builder()
+        .name(String::from("alice"))
+        .build();
+    assert!(tokio_test::block_on(db.save_changes_user(&user)).is_err());
+}
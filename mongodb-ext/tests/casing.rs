@@ -0,0 +1,187 @@
+// `user_URL` is not snake case on purpose, generated builders and accessors reuse the field name
+#![allow(non_snake_case)]
+
+use mongodb_ext::{case, mongo_db, serde_case, MongoClient, MongoCollection};
+
+mongo_db! {
+    Database {
+        #[derive(Debug, Default)]
+        Collection1 {
+            user_id: String,
+            area51_code: u32,
+            ipv4_address: String,
+            r#type: String,
+            #[alternate_key]
+            md5sum: String,
+            user_URL: String,
+        };
+        #[derive(Debug, Default)]
+        Collection2<partial, order_by: [created_at desc], rename_all: kebab, soft_delete, indexes: [[created_at, user_id]]> {
+            user_id: String,
+            #[alternate_key]
+            md5sum: String,
+            created_at: i64,
+        };
+        #[derive(Debug, Default)]
+        Collection3<rename_all: none, partial, order_by: [last_name]> {
+            first_name: String,
+            last_name: String,
+        };
+
+        @indexes {
+            Collection1: [user_id] [area51_code, ipv4_address desc] [r#type partial { md5sum: true }] [user_URL];
+            Collection2: [user_id, created_at desc partial { md5sum: true }];
+            Collection3: [first_name, last_name unique];
+        }
+    }
+}
+
+#[test]
+pub fn check_index_keys_match_serialized_keys() {
+    let documents = [
+        (
+            mongo::schema::Collection1::NAME,
+            mongodb::bson::to_document(&mongo::schema::Collection1::default()).unwrap(),
+        ),
+        (
+            mongo::schema::Collection2::NAME,
+            mongodb::bson::to_document(&mongo::schema::Collection2::default()).unwrap(),
+        ),
+        (
+            mongo::schema::Collection3::NAME,
+            mongodb::bson::to_document(&mongo::schema::Collection3::default()).unwrap(),
+        ),
+    ];
+
+    let models = mongo::Database::index_models();
+    assert_eq!(models.len(), 9);
+    for (collection_name, model) in models {
+        let (_, document) = documents
+            .iter()
+            .find(|(name, _)| *name == collection_name)
+            .unwrap();
+        for key in model.keys.keys() {
+            assert!(
+                document.contains_key(key),
+                "index key `{}` is not serialized",
+                key
+            );
+        }
+        if let Some(filter) = model.options.and_then(|o| o.partial_filter_expression) {
+            for key in filter.keys() {
+                assert!(
+                    document.contains_key(key),
+                    "filter key `{}` is not serialized",
+                    key
+                );
+            }
+        }
+    }
+}
+
+#[test]
+pub fn check_common_names_agree() {
+    assert_eq!(case!(user_id => Camel), serde_case!(user_id => "camelCase"));
+    assert_eq!(
+        case!(area51_code => Camel),
+        serde_case!(area51_code => "camelCase")
+    );
+    assert_eq!(
+        case!(ipv4_address => Camel),
+        serde_case!(ipv4_address => "camelCase")
+    );
+    assert_eq!(serde_case!(r#type => "camelCase"), "type");
+}
+
+#[test]
+pub fn check_documented_divergence() {
+    // serde keeps acronyms and does not split after digits, `convert_case` does
+    assert_eq!(serde_case!(user_URL => "camelCase"), "userURL");
+    assert_eq!(case!(user_URL => Camel), "userUrl");
+    assert_eq!(serde_case!(md5sum => "camelCase"), "md5sum");
+    assert_eq!(case!(md5sum => Camel), "md5Sum");
+}
+
+#[test]
+pub fn check_rename_all() {
+    use mongo::schema::{Collection2, PartialCollection2};
+    use mongodb::bson::{doc, from_document, to_document};
+
+    assert_eq!(Collection2::RENAME_ALL, "kebab-case");
+    assert_eq!(mongo::schema::Collection1::RENAME_ALL, "camelCase");
+
+    let document = Collection2::builder()
+        .user_id(String::from("alice"))
+        .md5sum(String::from("d41d8cd98f00b204e9800998ecf8427e"))
+        .created_at(1)
+        .build();
+    // the soft delete field keeps its name
+    assert_eq!(
+        to_document(&document).unwrap(),
+        doc! {
+            "deletedAt": null,
+            "user-id": "alice",
+            "md5sum": "d41d8cd98f00b204e9800998ecf8427e",
+            "created-at": 1_i64,
+        }
+    );
+
+    // `rename_all` is declared after `order_by`, but applies to it anyways
+    assert_eq!(Collection2::order_by(), Some(doc! { "created-at": -1 }));
+    assert_eq!(Collection2::field_key("user_id"), Some("user-id"));
+    assert_eq!(Collection2::field_key("deleted_at"), Some("deletedAt"));
+    assert_eq!(
+        Collection2::field_mapping(),
+        vec![
+            ("_id", "_id"),
+            ("deleted_at", "deletedAt"),
+            ("user_id", "user-id"),
+            ("md5sum", "md5sum"),
+            ("created_at", "created-at"),
+        ]
+    );
+    assert_eq!(
+        Collection2::index_models()[0].keys,
+        doc! { "created-at": 1, "user-id": 1 }
+    );
+    assert_eq!(Collection2::index_models()[1].keys, doc! { "md5sum": 1 });
+
+    let partial = from_document::<PartialCollection2>(doc! { "user-id": "bob" }).unwrap();
+    assert_eq!(partial.user_id.as_deref(), Some("bob"));
+}
+
+#[test]
+pub fn check_rename_none() {
+    use mongo::schema::{Collection3, PartialCollection3};
+    use mongodb::bson::{doc, from_document, to_document};
+
+    assert_eq!(Collection3::RENAME_ALL, "none");
+
+    let document = Collection3::builder()
+        .first_name(String::from("Alice"))
+        .last_name(String::from("Smith"))
+        .build();
+    assert_eq!(
+        to_document(&document).unwrap(),
+        doc! { "first_name": "Alice", "last_name": "Smith" }
+    );
+
+    assert_eq!(Collection3::order_by(), Some(doc! { "last_name": 1 }));
+    assert_eq!(Collection3::field_key("first_name"), Some("first_name"));
+
+    let partial = from_document::<PartialCollection3>(doc! { "first_name": "Bob" }).unwrap();
+    assert_eq!(partial.first_name.as_deref(), Some("Bob"));
+}
+
+#[test]
+pub fn check_unique_index_finders() {
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+
+    // futures are lazy, nothing is sent to the server until they are polled
+    drop(
+        db_handler
+            .collection3()
+            .find_by_first_name_and_last_name(&String::from("Alice"), &String::from("Smith")),
+    );
+    drop(db_handler.collection1().find_by_md5sum(&String::new()));
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "change-streams")]
+
+use mongodb::change_stream::{event::ChangeStreamEvent, ChangeStream};
+use mongodb_ext::{mongo_db, MongoClient};
+
+mongo_db! {
+    Database {
+        #[derive(Debug)]
+        User {
+            name: String,
+        }
+    }
+}
+
+mod reporting {
+    use mongodb_ext::mongo_db;
+
+    mongo_db! {
+        Database<read_only> {
+            #[derive(Debug)]
+            User {
+                name: String,
+            }
+        }
+    }
+}
+
+type UserStream = ChangeStream<ChangeStreamEvent<mongo::schema::User>>;
+
+/// Opening a change stream needs a replica set, thus only the signatures are checked here.
+#[allow(dead_code)]
+async fn check_watch_signatures(
+    db: &mongo::Database,
+    read_only_db: &reporting::mongo::Database,
+) -> mongodb::error::Result<()> {
+    let _: UserStream = db.watch_user(None, None).await?;
+
+    let mut stream: UserStream = db.watch_user_from(None).await?;
+    let token = stream.resume_token();
+    let _: UserStream = db.watch_user_from(token).await?;
+    let _ = stream.next_if_any().await?;
+
+    let _ = read_only_db.watch_user_from(None).await?;
+    let _ = read_only_db.user_coll.watch(None, None).await?;
+    Ok(())
+}
+
+#[test]
+pub fn check_watch_helpers_exist() {
+    let db = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+
+    // futures are lazy, nothing is sent to the server until they are polled
+    drop(db.watch_user(None, None));
+    drop(db.watch_user_from(None));
+}
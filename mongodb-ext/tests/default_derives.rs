@@ -0,0 +1,43 @@
+#![cfg(feature = "default-derives")]
+
+use mongodb_ext::mongo_db;
+
+mongo_db! {
+    Database {
+        Collection1 {
+            name: String,
+        };
+        #[derive(Debug, std::clone::Clone, PartialEq)]
+        Collection2 {
+            name: String,
+        };
+        Collection3 {
+            #[sensitive]
+            password: String,
+        }
+    }
+}
+
+#[test]
+pub fn check_default_derives() {
+    use mongo::schema::{Collection1, Collection2, Collection3};
+
+    let document = Collection1::builder().name(String::from("Alice")).build();
+    assert_eq!(
+        format!("{:?}", document.clone()),
+        "Collection1 { _id: None, name: \"Alice\" }"
+    );
+
+    // declared derives are not duplicated
+    let document = Collection2::builder().name(String::from("Alice")).build();
+    assert_eq!(document.clone(), document);
+
+    // sensitive fields stay redacted
+    let document = Collection3::builder()
+        .password(String::from("secret"))
+        .build();
+    assert_eq!(
+        format!("{:?}", document.clone()),
+        "Collection3 { _id: None, password: \"<redacted>\" }"
+    );
+}
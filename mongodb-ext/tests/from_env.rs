@@ -0,0 +1,32 @@
+use mongodb_ext::{mongo_db, MongoClient};
+
+mongo_db! {
+    Database<read_only, env: "FROM_ENV_TEST_URI"> {
+        User {
+            name: String,
+        }
+    }
+}
+
+#[test]
+pub fn check_from_env() {
+    assert_eq!(mongo::Database::ENV_VAR, "FROM_ENV_TEST_URI");
+
+    std::env::remove_var("FROM_ENV_TEST_URI");
+    let error = tokio_test::block_on(mongo::Database::from_env())
+        .err()
+        .expect("unset variable must fail");
+    assert_eq!(
+        error.get_custom::<String>().map(String::as_str),
+        Some(
+            "cannot read environment variable `FROM_ENV_TEST_URI`: environment variable not found"
+        ),
+    );
+
+    std::env::set_var("FROM_ENV_TEST_URI", "invalid connection string");
+    assert!(tokio_test::block_on(mongo::Database::from_env()).is_err());
+
+    std::env::set_var("FROM_ENV_TEST_URI", "mongodb://example.com");
+    let db = tokio_test::block_on(mongo::Database::from_env()).unwrap();
+    assert_eq!(db.database.name(), "database");
+}
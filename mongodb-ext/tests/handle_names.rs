@@ -0,0 +1,38 @@
+use mongodb_ext::{mongo_db, MongoClient, MongoCollection};
+
+mongo_db! {
+    Database {
+        HTTPCache {};
+        UserID {};
+        IOError {};
+        HttpCache2 {};
+        My2ndCollection {};
+        Collection1 {}
+    }
+}
+
+#[test]
+pub fn check_handle_names() {
+    let db = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+
+    // acronyms are kept together
+    assert_eq!(db.http_cache_coll.name(), mongo::schema::HTTPCache::NAME);
+    assert_eq!(db.user_id_coll.name(), mongo::schema::UserID::NAME);
+    assert_eq!(db.io_error_coll.name(), mongo::schema::IOError::NAME);
+    // digits stick to the preceding word
+    assert_eq!(db.http_cache2_coll.name(), mongo::schema::HttpCache2::NAME);
+    assert_eq!(
+        db.my2nd_collection_coll.name(),
+        mongo::schema::My2ndCollection::NAME
+    );
+    assert_eq!(db.collection1_coll.name(), mongo::schema::Collection1::NAME);
+}
+
+#[test]
+pub fn check_collection_names() {
+    assert_eq!(mongo::schema::HTTPCache::NAME, "httpCache");
+    assert_eq!(mongo::schema::UserID::NAME, "userId");
+    assert_eq!(mongo::schema::IOError::NAME, "ioError");
+    assert_eq!(mongo::schema::HttpCache2::NAME, "httpCache2");
+    assert_eq!(mongo::schema::Collection1::NAME, "collection1");
+}
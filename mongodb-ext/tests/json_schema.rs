@@ -0,0 +1,77 @@
+#![cfg(feature = "json-schema")]
+
+use mongodb_ext::{json_schema::type_schema, mongo_db};
+use serde_json::json;
+
+mongo_db! {
+    Database {
+        {
+            use std::collections::HashMap;
+            use mongodb::bson::DateTime;
+        }
+        Collection1 {
+            first_name: String,
+            age: u8,
+            score: Option<f64>,
+            tags: Vec<String>,
+            created_at: DateTime,
+            counters: HashMap<String, i64>,
+            location: super::super::Location,
+        };
+        Collection2<_id: i64> {
+            flag: bool,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Location;
+
+#[test]
+pub fn check_json_schema() {
+    let schema = mongo::schema::Collection1::json_schema();
+    assert_eq!(
+        schema,
+        json!({
+            "type": "object",
+            "properties": {
+                "_id": { "type": ["string", "null"], "pattern": "^[0-9a-f]{24}$" },
+                "firstName": { "type": "string" },
+                "age": { "type": "integer" },
+                "score": { "type": ["number", "null"] },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "createdAt": { "type": "string", "format": "date-time" },
+                "counters": { "type": "object", "additionalProperties": { "type": "integer" } },
+                "location": { "type": "object", "title": "super::super::Location" },
+            },
+            "required": ["firstName", "age", "tags", "createdAt", "counters", "location"],
+        })
+    );
+
+    let schema = mongo::schema::Collection2::json_schema();
+    assert_eq!(
+        schema["properties"]["_id"],
+        json!({ "type": ["integer", "null"] })
+    );
+    assert_eq!(schema["required"], json!(["flag"]));
+}
+
+#[test]
+pub fn check_type_schema() {
+    assert_eq!(
+        type_schema("Option < Vec < Option < u8 > > >"),
+        json!({ "type": ["array", "null"], "items": { "type": ["integer", "null"] } })
+    );
+    assert_eq!(
+        type_schema("std::sync::Arc<str>"),
+        json!({ "type": "string" })
+    );
+    assert_eq!(
+        type_schema("Option<Location>"),
+        json!({ "type": ["object", "null"], "title": "Location" })
+    );
+    assert_eq!(
+        type_schema("(u8, u8)"),
+        json!({ "type": "object", "title": "(u8,u8)" })
+    );
+}
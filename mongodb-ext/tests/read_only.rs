@@ -0,0 +1,59 @@
+use mongodb_ext::{mongo_db, MongoClient, MongoCollection, ReadOnlyCollection};
+
+mongo_db! {
+    #[derive(Debug, Clone)]
+    Database<read_only> {
+        #[derive(Debug)]
+        Collection1 {
+            counter: u32,
+        };
+        #[derive(Debug)]
+        Collection2<_id: none> {
+            name: String,
+        }
+    }
+}
+
+#[test]
+pub fn check_read_only_handles() {
+    let mut db = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+
+    let collection1: &ReadOnlyCollection<mongo::schema::Collection1> = &db.collection1_coll;
+    assert_eq!(collection1.name(), mongo::schema::Collection1::NAME);
+    assert_eq!(
+        db.collection2_coll.namespace().to_string(),
+        "database.collection2"
+    );
+
+    // handles are rebuilt as read-only handles
+    tokio_test::block_on(db.reconnect("mongodb://example.org")).unwrap();
+    let collection2: ReadOnlyCollection<mongo::schema::Collection2> = db.collection2_coll.clone();
+    assert_eq!(collection2.name(), mongo::schema::Collection2::NAME);
+
+    let collection1: ReadOnlyCollection<mongo::schema::Collection1> = db.collection1_handle();
+    assert_eq!(collection1.name(), mongo::schema::Collection1::NAME);
+
+    // generic handles are read-only as well
+    let collection2: ReadOnlyCollection<mongo::schema::Collection2> = db.collection();
+    assert_eq!(collection2.name(), mongo::schema::Collection2::NAME);
+}
+
+#[test]
+pub fn check_read_only_aggregate() {
+    use mongodb_ext::Pipeline;
+
+    let db = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    // aggregations are reads, futures are lazy, nothing is sent to the server until they are polled
+    drop(db.aggregate_collection1(Pipeline::new().count("total"), None));
+}
+
+#[test]
+pub fn check_read_only_drop_database() {
+    let db = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    // refused without contacting the server
+    let error = tokio_test::block_on(db.drop_database()).unwrap_err();
+    assert_eq!(
+        error.get_custom::<String>().map(String::as_str),
+        Some("cannot drop the database `database` through a read-only handler")
+    );
+}
@@ -0,0 +1,39 @@
+use mongodb_ext::MongoClient;
+
+mod accounts {
+    use mongodb_ext::mongo_db;
+
+    mongo_db! {
+        Accounts {
+            User {
+                name: String,
+            }
+        }
+    }
+}
+
+mod shop {
+    use mongodb_ext::mongo_db;
+
+    mongo_db! {
+        Shop {
+            Order {
+                user_name: String,
+            }
+        }
+    }
+}
+
+#[test]
+pub fn check_shared_client() {
+    let client =
+        tokio_test::block_on(mongodb::Client::with_uri_str("mongodb://example.com")).unwrap();
+
+    let accounts = accounts::mongo::Accounts::new_with_client(client.clone()).unwrap();
+    let shop = shop::mongo::Shop::new_with_client(client).unwrap();
+
+    assert_eq!(accounts.database.name(), "accounts");
+    assert_eq!(accounts.user_coll.name(), "user");
+    assert_eq!(shop.database.name(), "shop");
+    assert_eq!(shop.order_coll.name(), "order");
+}
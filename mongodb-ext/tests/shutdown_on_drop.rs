@@ -0,0 +1,30 @@
+#![cfg(feature = "shutdown-on-drop")]
+
+use mongodb_ext::{mongo_db, MongoClient};
+
+mongo_db! {
+    Database<shutdown_on_drop, read_only> {
+        #[derive(Debug)]
+        User {
+            name: String,
+        }
+    }
+}
+
+#[test]
+pub fn check_drop_outside_runtime() {
+    let db = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    let client = db.client.clone();
+    drop(db);
+
+    // the client is shut down for all clones
+    assert!(tokio_test::block_on(client.list_database_names(None, None)).is_err());
+}
+
+#[test]
+pub fn check_drop_inside_runtime() {
+    tokio_test::block_on(async {
+        let db = mongo::Database::new("mongodb://example.com").await.unwrap();
+        drop(db);
+    });
+}
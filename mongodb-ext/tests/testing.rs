@@ -0,0 +1,109 @@
+#![cfg(feature = "testing")]
+
+use mongodb_ext::mongo_db;
+
+mongo_db! {
+    Database {
+        Collection1 {
+            first_name: String,
+            counter: i32,
+        };
+        Collection2<_id: none> {
+            first_name: String,
+        };
+        Collection3<_id: none> {
+            first_name: String,
+            #[serde(default)]
+            tags: Vec<String>,
+        }
+    }
+}
+
+#[test]
+pub fn check_matches_document() {
+    use mongodb::bson::{doc, oid::ObjectId};
+
+    let oid = ObjectId::parse_str("0123456789ABCDEF01234567").unwrap();
+    let without_id = mongo::schema::Collection1 {
+        _id: None,
+        first_name: String::from("Alice"),
+        counter: 1,
+    };
+
+    // `_id` is ignored if `self` has none, the key order does not matter
+    assert!(without_id.matches_document(&doc! { "firstName": "Alice", "counter": 1 }));
+    assert!(without_id.matches_document(&doc! { "_id": oid, "counter": 1, "firstName": "Alice" }));
+    assert!(!without_id.matches_document(&doc! { "firstName": "Alice", "counter": 2 }));
+    assert!(!without_id.matches_document(&doc! { "firstName": "Alice" }));
+
+    let with_id = mongo::schema::Collection1 {
+        _id: Some(oid),
+        ..without_id
+    };
+    assert!(with_id.matches_document(&doc! { "_id": oid, "firstName": "Alice", "counter": 1 }));
+    assert!(!with_id.matches_document(&doc! { "firstName": "Alice", "counter": 1 }));
+    assert!(!with_id
+        .matches_document(&doc! { "_id": ObjectId::new(), "firstName": "Alice", "counter": 1 }));
+
+    let no_id_field = mongo::schema::Collection2 {
+        first_name: String::from("Bob"),
+    };
+    assert!(no_id_field.matches_document(&doc! { "_id": oid, "firstName": "Bob" }));
+}
+
+#[test]
+pub fn check_schema_drift() {
+    use mongo::schema::{Collection1, Collection2, Collection3};
+    use mongodb::bson::{doc, oid::ObjectId};
+    use mongodb_ext::testing::SchemaDrift;
+
+    let matching = Collection1::schema_drift(
+        &doc! { "_id": ObjectId::new(), "firstName": "Alice", "counter": 1 },
+    );
+    assert!(matching.is_empty());
+
+    // `_id` is always stored by mongoDB, thus it is not reported for collections without `_id`
+    let drift = Collection2::schema_drift(&doc! {
+        "_id": ObjectId::new(),
+        "firstName": "Bob",
+        "lastName": "Smith",
+        "address": { "city": "Berlin" },
+    });
+    assert_eq!(
+        drift,
+        SchemaDrift {
+            missing: Vec::new(),
+            extra: vec![String::from("lastName"), String::from("address")],
+            error: None,
+        }
+    );
+
+    let defaulted = Collection3::schema_drift(&doc! { "firstName": "Carol", "nickname": "C" });
+    assert_eq!(defaulted.missing, vec![String::from("tags")]);
+    assert_eq!(defaulted.extra, vec![String::from("nickname")]);
+
+    let broken = Collection1::schema_drift(&doc! { "firstName": "Alice" });
+    assert!(broken.error.unwrap().contains("counter"));
+    assert!(broken.missing.is_empty());
+}
+
+#[test]
+pub fn check_reset_fails_without_server() {
+    use mongodb::error::ErrorKind;
+    use mongodb::options::ClientOptions;
+    use mongodb_ext::MongoClient;
+    use std::time::Duration;
+
+    let result = tokio_test::block_on(async {
+        let mut options = ClientOptions::parse("mongodb://127.0.0.1:9").await?;
+        options.server_selection_timeout = Some(Duration::from_millis(50));
+        let db = mongo::Database::new_with_client(mongodb::Client::with_options(options)?)?;
+        db.reset().await
+    });
+
+    // dropping is the first step, nothing is provisioned if it fails
+    assert!(matches!(
+        *result.unwrap_err().kind,
+        ErrorKind::ServerSelection { .. }
+    ));
+}
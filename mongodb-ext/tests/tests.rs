@@ -1,8 +1,29 @@
-use mongodb_ext::{mongo_db, DefaultId, MongoClient, MongoCollection};
+use mongodb_ext::{mongo_db, DefaultId, MongoClient, MongoCollection, MongoCollectionId};
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 pub struct MyLocalType;
 
+pub fn next_id() -> u64 {
+    42
+}
+
+pub fn default_label() -> String {
+    String::from("computed")
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub enum Status {
+    InProgress,
+    OnHold { since_days: u32 },
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RenamedStatus {
+    InProgress,
+    OnHold,
+}
+
 mongo_db! {
     #[derive(Debug, Clone)]
     Database {
@@ -10,6 +31,16 @@ mongo_db! {
             use std::collections::HashMap;
             use super::super::MyLocalType;
             use mongodb_ext::MongoCollection;
+
+            pub type Money = mongodb::bson::Decimal128;
+            pub type Ledger = HashMap<String, Vec<Money>>;
+        }
+
+        @base #[derive(Debug, Clone, PartialEq)]
+        Base {
+            tenant_id: String,
+            #[serde(rename = "rev")]
+            revision: u32,
         }
 
         #[derive(Debug, Clone)]
@@ -44,6 +75,240 @@ mongo_db! {
         Collection6 {
             #[builder(default = true)]
             r#type: bool
+        };
+        #[derive(Debug)]
+        Collection7<_id: u64 gen = crate::next_id, display> {};
+        #[derive(Debug)]
+        Collection8<deny_unknown_fields, version_tag: "2024-01", version: 2> {
+            #[serde(rename = "renamed")]
+            some_field: u32,
+        };
+        #[derive(Debug)]
+        Collection9<_id: none, deny_unknown_fields, order_key: someField> {
+            some_field: u32,
+        };
+        #[derive(Debug)]
+        Collection10<_id: none> {
+            stored: u32,
+            #[serde(skip_deserializing, default = "crate::default_label")]
+            label: String,
+            #[serde(skip_deserializing, skip_serializing, default = "crate::default_label")]
+            transient_label: String,
+        };
+        #[derive(Debug, PartialEq)]
+        Collection11<flatten: Base> {
+            name: String,
+        };
+        #[derive(Debug)]
+        Collection12<_id: i64, sequence> {
+            message: String,
+        };
+        #[derive(Debug)]
+        Collection13<_id: none> {
+            balance: Money,
+            ledger: Ledger,
+        };
+        #[derive(Debug)]
+        Collection14 {
+            #[null_as(omit)]
+            #[serde(rename = "nick")]
+            nickname: Option<String>,
+            #[null_as(null)]
+            deleted_at: Option<i64>,
+            comment: Option<String>,
+        };
+        #[derive(Debug)]
+        Collection15<_id: String, no_accessor> {
+            name: String,
+        }-{
+            pub fn id(&self) -> &str {
+                self._id.as_deref().unwrap_or(&self.name)
+            }
+        };
+        #[derive(Debug, PartialEq)]
+        Collection16<_id: none, partial, order_by: [created_at desc]> {
+            #[date_as_iso]
+            created_at: mongodb::bson::DateTime,
+            #[date_as_iso]
+            #[serde(default)]
+            deleted_at: Option<mongodb::bson::DateTime>,
+            updated_at: mongodb::bson::DateTime,
+        };
+        #[derive(Debug, PartialEq)]
+        Collection17<all_default, version: 2, round_trip> {
+            name: String,
+            visits: u32,
+            tags: Vec<String>,
+            #[serde(default = "crate::default_label")]
+            label: String,
+        };
+        #[derive(Debug, PartialEq)]
+        Collection18<_id: none> {
+            current_status: crate::Status,
+            previous_status: crate::RenamedStatus,
+        };
+        #[derive(Debug)]
+        Collection19 {
+            price: u32,
+            amount: u32,
+            #[computed]
+            total: u32,
+        };
+        #[derive(Debug, PartialEq)]
+        Collection20<_id: none, partial> {
+            #[binary]
+            payload: Vec<u8>,
+            #[binary]
+            #[serde(default)]
+            thumbnail: Option<Vec<u8>>,
+            unmarked: Vec<u8>,
+        };
+        #[derive(Debug)]
+        Collection21<version: 3> {
+            #[alternate_key]
+            customer_id: String,
+            name: String,
+            #[serde(default)]
+            schema_version: i32,
+            #[versioned_field(since = 3)]
+            email: String,
+        };
+        #[derive(Debug)]
+        Collection22<_id: String, partial, flatten: Base> {
+            #[serde(rename = "fullName")]
+            name: String,
+            #[binary]
+            payload: Vec<u8>,
+            #[null_as(null)]
+            nickname: Option<String>,
+        };
+        #[derive(Debug)]
+        #[cfg_attr(test, derive(PartialEq))]
+        Collection23<_id: none, partial> {
+            #[serde(rename = "_id")]
+            key: i32,
+            value: f64,
+        };
+        Collection25<_id: String> {
+            #[alternate_key]
+            #[sensitive]
+            email: String,
+            #[sensitive]
+            #[alternate_key]
+            phone: Option<String>,
+            name: String,
+            schema_version: i32,
+        };
+        #[derive(Debug)]
+        #[ttl(field = deletedAt, seconds = 2592000)]
+        Collection26<soft_delete, partial> {
+            email: String,
+        };
+        #[derive(Debug, PartialEq)]
+        Collection27<_id: none, validated> {
+            #[min_len(1)]
+            #[max_len(10)]
+            name: String,
+            #[max(120)]
+            #[serde(default)]
+            age: Option<u8>,
+            #[max_len(2)]
+            tags: Vec<String>,
+            #[array(max_items = 3, unique_items)]
+            #[serde(default)]
+            scores: Option<Vec<u8>>,
+        };
+        #[derive(Debug, PartialEq)]
+        Collection28<omit_none, soft_delete> {
+            nickname: Option<String>,
+            #[null_as(null)]
+            last_login: Option<i64>,
+            scores: std::option::Option<Vec<u8>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            note: core::option::Option<String>,
+            name: String,
+            aliases: Vec<Option<String>>,
+        };
+        #[derive(Debug)]
+        Collection29 {
+            #[normalize(trim, lowercase)]
+            #[unique]
+            email: String,
+            #[normalize(trim)]
+            nickname: Option<String>,
+        };
+        #[derive(Debug)]
+        Collection30<indexes: [[email unique] [created_at desc, email name "recent"]], soft_delete> {
+            email: String,
+            created_at: i64,
+        };
+        #[derive(Debug)]
+        Collection31<default_find: { sort: mongodb::bson::doc! { "counter": -1 }, limit: 10 }> {
+            counter: u16,
+        };
+        #[derive(Debug)]
+        Collection32<display: name> {
+            name: String,
+            counter: u16,
+        };
+        #[derive(Debug)]
+        Collection33<order_by: counter> {
+            counter: u16,
+        };
+        #[derive(Debug)]
+        Collection35<order_by: counter desc> {
+            counter: u16,
+        };
+        #[derive(Debug)]
+        Collection36<_id: none> {
+            #[serde(default, rename = "x")]
+            renamed: u32,
+            #[serde(rename(serialize = "out", deserialize = "in"))]
+            split: u32,
+            #[serde(skip_serializing_if = "Option::is_none", rename = "maybe")]
+            optional: Option<u32>,
+            #[serde(rename = "hidden", skip_serializing)]
+            hidden: u32,
+        };
+        #[derive(Debug)]
+        Collection34<id_gen: crate::next_id, _id: u64> {};
+        #[derive(Debug, PartialEq)]
+        Collection24<_id: none, order_by: [created_at desc, big,], partial> {
+            #[as_string]
+            big: u128,
+            #[as_string]
+            #[serde(default)]
+            ratio: Option<f64>,
+            #[as_i64]
+            created_at: mongodb::bson::DateTime,
+            #[as_i64]
+            #[serde(default)]
+            deleted_at: Option<mongodb::bson::DateTime>,
+        }
+
+        @view #[derive(Debug)]
+        Collection21ByName on Collection21 [
+            mongodb::bson::doc! { "$sort": { "name": 1 } },
+            mongodb::bson::doc! { "$project": { "customerId": 1, "name": 1 } },
+        ] {
+            #[serde(rename = "_id")]
+            id: mongodb_ext::DefaultId,
+            customer_id: String,
+        };
+
+        @readmodel #[derive(Debug)]
+        Collection21WithCollection2 {
+            #[serde(rename = "_id")]
+            id: mongodb_ext::DefaultId,
+            customer_id: String,
+            collection2s: Vec<Collection2>,
+        }
+
+        @indexes {
+            Collection2: [name unique] [counter desc, name];
+            Collection4: [renamed_field] [renamed_field desc name "legacy_lookup" hidden background];
+            Collection12: [message unique partial { message: mongodb::bson::doc! { "$exists": true }, some_flag: 1 }];
+            Collection16: [created_at unique partial { deleted_at: null, }];
         }
     }-{
         pub fn mongo_code() -> bool { true }
@@ -58,6 +323,10 @@ pub fn check_schema_versions() {
     assert_eq!(mongo::schema::Collection2::SCHEMA_VERSION, 3);
     assert_eq!(mongo::schema::Collection3::SCHEMA_VERSION, 1);
     assert_eq!(mongo::schema::Collection4::SCHEMA_VERSION, 29);
+
+    assert_eq!(mongo::schema::Collection8::SCHEMA_VERSION, 2);
+    assert_eq!(mongo::schema::Collection8::SCHEMA_VERSION_TAG, "2024-01");
+    assert_eq!(mongo::schema::Collection1::SCHEMA_VERSION_TAG, "");
 }
 
 #[test]
@@ -125,6 +394,30 @@ pub fn check_doc_serialization() {
     );
 }
 
+#[test]
+pub fn check_try_from_document() {
+    use mongodb::bson::{doc, Document};
+
+    let my_item: Document = doc! {
+        "counter": 0,
+        "name": "my_special_item"
+    };
+
+    assert_eq!(
+        mongo::schema::Collection2::try_from(my_item)
+            .expect("Could not convert mongodb bson Document to collection document"),
+        mongo::schema::Collection2 {
+            _id: None,
+            counter: 0,
+            name: String::from("my_special_item")
+        }
+    );
+
+    // missing fields are reported as error
+    let converted: Result<mongo::schema::Collection2, _> = doc! { "counter": 0 }.try_into();
+    assert!(converted.is_err());
+}
+
 #[test]
 pub fn check_json_serialization_with_id() {
     use {
@@ -151,6 +444,65 @@ pub fn check_json_serialization_with_id() {
     );
 }
 
+#[test]
+pub fn check_tolerant_id_parser() {
+    use {
+        mongodb::bson::{de::from_document, doc, oid::ObjectId},
+        serde_json::{from_value, json},
+    };
+
+    let oid = ObjectId::parse_str("0123456789abcdef01234567").unwrap();
+
+    // hex strings in any case, surrounded by whitespace, and extended JSON objects
+    for id in [
+        json!("0123456789abcdef01234567"),
+        json!("0123456789ABCDEF01234567"),
+        json!(" 0123456789abcdef01234567\n"),
+        json!({ "$oid": "0123456789ABCDEF01234567" }),
+    ] {
+        let entry: mongo::schema::Collection2 =
+            from_value(json!({ "_id": id, "counter": 0, "name": "item" })).unwrap();
+        assert_eq!(entry._id, Some(oid));
+    }
+    let entry: mongo::schema::Collection2 =
+        from_document(doc! { "_id": "0123456789ABCDEF01234567", "counter": 0, "name": "item" })
+            .unwrap();
+    assert_eq!(entry._id, Some(oid));
+    let entry: mongo::schema::Collection2 =
+        from_document(doc! { "_id": oid, "counter": 0, "name": "item" }).unwrap();
+    assert_eq!(entry._id, Some(oid));
+
+    // missing and null ids
+    let entry: mongo::schema::Collection2 =
+        from_value(json!({ "counter": 0, "name": "item" })).unwrap();
+    assert_eq!(entry._id, None);
+    let entry: mongo::schema::Collection2 =
+        from_value(json!({ "_id": null, "counter": 0, "name": "item" })).unwrap();
+    assert_eq!(entry._id, None);
+
+    // invalid ids
+    let error = from_value::<mongo::schema::Collection2>(
+        json!({ "_id": "not an id", "counter": 0, "name": "item" }),
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid ObjectId `not an id`, expected a 24-character hex string"
+    );
+    assert!(from_value::<mongo::schema::Collection2>(
+        json!({ "_id": "0123456789abcdef0123456", "counter": 0, "name": "item" })
+    )
+    .is_err());
+    assert!(from_value::<mongo::schema::Collection2>(
+        json!({ "_id": 42, "counter": 0, "name": "item" })
+    )
+    .is_err());
+    assert!(from_document::<mongo::schema::Collection2>(
+        doc! { "_id": { "id": "0123456789abcdef01234567" }, "counter": 0, "name": "item" }
+    )
+    .is_err());
+}
+
 #[test]
 pub fn check_doc_serialization_with_id() {
     use mongodb::bson::{de::from_document, doc, oid::ObjectId, Document};
@@ -188,11 +540,13 @@ pub fn check_constant_names() {
 pub fn check_initializer() {
     // try to initialize with an invalid connection string
     if let Err(e) = tokio_test::block_on(mongo::Database::new("invalid connection string")) {
-        // make sure the correct error message is produced
-        assert_eq!(
-            format!("{}", e),
-            String::from("An invalid argument was provided: connection string contains no scheme")
-        );
+        // make sure the correct error is produced
+        match *e.kind {
+            mongodb::error::ErrorKind::InvalidArgument { message, .. } => {
+                assert_eq!(message, "connection string contains no scheme")
+            }
+            kind => panic!("Unexpected error kind: {:?}", kind),
+        }
     } else {
         // this should really not happen
         panic!("Somehow constructed a database client without a proper connection string")
@@ -210,6 +564,160 @@ pub fn check_initializer() {
 }
 
 #[test]
+pub fn check_retryable_writes_initializer() {
+    for enabled in [true, false] {
+        let db_handler = tokio_test::block_on(mongo::Database::new_with_retryable_writes(
+            "mongodb://example.com",
+            enabled,
+        ))
+        .unwrap();
+        assert_eq!(db_handler.collection1_coll.name(), "collection1");
+    }
+
+    assert!(
+        tokio_test::block_on(mongo::Database::new_with_retryable_writes(
+            "invalid connection string",
+            true
+        ))
+        .is_err()
+    );
+}
+
+#[test]
+pub fn check_compressors_initializer() {
+    let db_handler = tokio_test::block_on(mongo::Database::new_with_compressors(
+        "mongodb://example.com",
+        Vec::new(),
+    ))
+    .unwrap();
+    assert_eq!(db_handler.collection1_coll.name(), "collection1");
+
+    #[cfg(feature = "zlib-compression")]
+    {
+        use mongodb::options::Compressor;
+
+        let db_handler = tokio_test::block_on(mongo::Database::new_with_compressors(
+            "mongodb://example.com",
+            vec![Compressor::Zlib { level: Some(6) }],
+        ))
+        .unwrap();
+        assert_eq!(db_handler.collection1_coll.name(), "collection1");
+    }
+
+    assert!(tokio_test::block_on(mongo::Database::new_with_compressors(
+        "invalid connection string",
+        Vec::new()
+    ))
+    .is_err());
+}
+
+#[test]
+pub fn check_tls_initializer() {
+    use std::path::PathBuf;
+
+    let result = tokio_test::block_on(mongo::Database::new_with_tls(
+        "mongodb://example.com",
+        PathBuf::from("/nonexistent/ca.pem"),
+        Some(PathBuf::from("/nonexistent/client.pem")),
+    ));
+    // the certificate files are read when the client is created
+    match result.map(|_| ()).unwrap_err().kind.as_ref() {
+        mongodb::error::ErrorKind::Io(e) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+        other => panic!("Unexpected error kind: {:?}", other),
+    }
+}
+
+#[test]
+pub fn check_collection_command_helpers() {
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+
+    // futures are lazy, nothing is sent to the server until they are polled
+    drop(db_handler.collection1_stats());
+    drop(db_handler.collection_stats(mongo::schema::Collection2::NAME));
+    drop(db_handler.collection1_exists());
+    drop(db_handler.collection1_is_empty());
+    drop(
+        db_handler.insert_collection2(
+            mongo::schema::Collection2::builder()
+                .name(String::from("Alice"))
+                .counter(1)
+                .build(),
+        ),
+    );
+    drop(db_handler.collection26().is_empty());
+    drop(db_handler.collection_exists(mongo::schema::Collection2::NAME));
+    drop(db_handler.export_collection2(500));
+    drop(db_handler.run_command(mongodb::bson::doc! { "buildInfo": 1 }));
+    drop(db_handler.drop_collection2());
+    drop(db_handler.create_collection30_indexes());
+    drop(db_handler.collection30().find_by_email(&String::new()));
+    drop(db_handler.find_one_collection2_as::<mongodb::bson::Document>(None));
+    drop(db_handler.find_one_as::<mongodb::bson::Document>(
+        mongo::schema::Collection2::NAME,
+        mongodb::bson::doc! { "name": "Alice" },
+    ));
+    drop(db_handler.drop_database());
+}
+
+#[test]
+pub fn check_generic_collection_handle() {
+    fn name_of<T: MongoCollection>(
+        db: &impl MongoClient<Handle<T> = mongodb::Collection<T>>,
+    ) -> String {
+        db.collection::<T>().name().to_string()
+    }
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+
+    assert_eq!(
+        db_handler.collection::<mongo::schema::Collection2>().name(),
+        "collection2"
+    );
+    assert_eq!(
+        name_of::<mongo::schema::Collection4>(&db_handler),
+        db_handler.collection4_coll.name()
+    );
+}
+
+#[test]
+pub fn check_order_key() {
+    use mongo::schema::Collection9;
+
+    let mut documents: Vec<Collection9> = [3, 1, 2]
+        .into_iter()
+        .map(|some_field| Collection9::builder().some_field(some_field).build())
+        .collect();
+    documents.sort();
+    assert_eq!(
+        documents
+            .iter()
+            .map(|document| document.some_field)
+            .collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+
+    assert!(documents[0] < documents[1]);
+    assert_eq!(documents[2], Collection9::builder().some_field(3).build());
+    assert_eq!(documents.into_iter().max().unwrap().some_field, 3);
+}
+
+#[test]
+pub fn check_namespaces() {
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+
+    assert_eq!(db_handler.collection1_namespace(), "database.collection1");
+    assert_eq!(
+        db_handler.namespace(mongo::schema::Collection2::NAME),
+        "database.collection2"
+    );
+    assert_eq!(
+        db_handler.collection2().namespace(),
+        db_handler.collection2_namespace()
+    );
+}
+
+#[test]
+#[allow(clippy::clone_on_copy)]
 pub fn test_typed_builder() {
     assert_eq!(
         mongo::schema::Collection2::builder()
@@ -265,3 +773,1578 @@ pub fn test_schema_version_default() {
 pub fn test_reserved_keyword_collection_fields() {
     assert!(mongo::schema::Collection6::builder().build().r#type);
 }
+
+#[test]
+pub fn check_reconnect() {
+    let mut db_handler =
+        tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+
+    // an invalid connection string leaves the handler untouched
+    assert!(tokio_test::block_on(db_handler.reconnect("invalid connection string")).is_err());
+    assert_eq!(db_handler.collection1_coll.name(), "collection1");
+
+    tokio_test::block_on(db_handler.reconnect("mongodb://example.org")).unwrap();
+
+    assert_eq!(db_handler.database.name(), "database");
+    assert_eq!(db_handler.collection1_coll.name(), "collection1");
+    assert_eq!(db_handler.collection4_coll.name(), "collection4");
+}
+
+#[test]
+pub fn check_default_env_var() {
+    assert_eq!(mongo::Database::ENV_VAR, "MONGODB_URI");
+}
+
+#[test]
+pub fn check_index_models() {
+    use mongodb::bson::doc;
+
+    let models = mongo::Database::index_models();
+    assert_eq!(models.len(), 13);
+
+    assert_eq!(models[0].0, "collection2");
+    assert_eq!(models[0].1.keys, doc! { "name": 1 });
+    assert_eq!(models[0].1.options.as_ref().unwrap().unique, Some(true));
+
+    assert_eq!(models[1].0, "collection2");
+    assert_eq!(models[1].1.keys, doc! { "counter": -1, "name": 1 });
+    assert_eq!(models[1].1.options.as_ref().unwrap().unique, None);
+
+    // index keys follow the field names, not the serde attributes
+    assert_eq!(models[2].0, "collection4");
+    assert_eq!(models[2].1.keys, doc! { "renamedField": 1 });
+    let options = models[2].1.options.as_ref().unwrap();
+    assert_eq!(options.name, None);
+    assert_eq!(options.hidden, None);
+
+    // build options
+    assert_eq!(models[3].0, "collection4");
+    assert_eq!(models[3].1.keys, doc! { "renamedField": -1 });
+    let options = models[3].1.options.as_ref().unwrap();
+    assert_eq!(options.name.as_deref(), Some("legacy_lookup"));
+    assert_eq!(options.hidden, Some(true));
+    assert_eq!(options.background, Some(true));
+
+    // partial filter fields are converted to `camelCase` as well
+    assert_eq!(models[4].0, "collection12");
+    let options = models[4].1.options.as_ref().unwrap();
+    assert_eq!(options.unique, Some(true));
+    assert_eq!(
+        options.partial_filter_expression,
+        Some(doc! { "message": { "$exists": true }, "someFlag": 1 })
+    );
+
+    // `null` selects documents storing `null`, partial filters cannot select missing fields
+    assert_eq!(models[5].0, "collection16");
+    let options = models[5].1.options.as_ref().unwrap();
+    assert_eq!(options.unique, Some(true));
+    assert_eq!(
+        options.partial_filter_expression,
+        Some(doc! { "deletedAt": { "$type": "null" } })
+    );
+
+    // indexes of field attributes follow the `@indexes` block
+    assert_eq!(models[6].0, "collection21");
+    assert_eq!(models[6].1.keys, doc! { "customerId": 1 });
+    assert_eq!(models[6].1.options.as_ref().unwrap().unique, Some(true));
+    assert_eq!(models[7].0, "collection25");
+    assert_eq!(models[8].0, "collection25");
+
+    // TTL index of the collection attribute
+    assert_eq!(models[9].0, "collection26");
+    assert_eq!(models[9].1.keys, doc! { "deletedAt": 1 });
+    assert_eq!(
+        models[9].1.options.as_ref().unwrap().expire_after,
+        Some(std::time::Duration::from_secs(2592000))
+    );
+    // `#[unique]` field attribute
+    assert_eq!(models[10].0, "collection29");
+    assert_eq!(models[10].1.keys, doc! { "email": 1 });
+    assert_eq!(models[10].1.options.as_ref().unwrap().unique, Some(true));
+
+    // indexes of the `indexes` parameter
+    assert_eq!(models[11].0, "collection30");
+    assert_eq!(models[11].1.keys, doc! { "email": 1 });
+    assert_eq!(models[11].1.options.as_ref().unwrap().unique, Some(true));
+    assert_eq!(models[12].0, "collection30");
+    assert_eq!(models[12].1.keys, doc! { "createdAt": -1, "email": 1 });
+    assert_eq!(
+        models[12].1.options.as_ref().unwrap().name.as_deref(),
+        Some("recent")
+    );
+    assert_eq!(
+        mongo::schema::Collection26::ttl(),
+        Some(("deletedAt", std::time::Duration::from_secs(2592000)))
+    );
+    assert_eq!(mongo::schema::Collection2::ttl(), None);
+}
+
+#[test]
+pub fn check_unique_index_models() {
+    use mongodb::bson::doc;
+
+    let models: Vec<_> = mongo::Database::unique_index_models()
+        .into_iter()
+        .map(|(collection_name, model)| (collection_name, model.keys))
+        .collect();
+    assert_eq!(
+        models,
+        vec![
+            ("collection2", doc! { "name": 1 }),
+            ("collection12", doc! { "message": 1 }),
+            ("collection16", doc! { "createdAt": 1 }),
+            ("collection21", doc! { "customerId": 1 }),
+            ("collection25", doc! { "email": 1 }),
+            ("collection25", doc! { "phone": 1 }),
+            ("collection29", doc! { "email": 1 }),
+            ("collection30", doc! { "email": 1 }),
+        ]
+    );
+
+    // futures are lazy, nothing is sent to the server until they are polled
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    drop(db_handler.sync_indexes());
+}
+
+#[test]
+pub fn check_id_generator() {
+    let mut generated = mongo::schema::Collection7::builder().build();
+    assert_eq!(*generated.ensure_id(), 42);
+    assert_eq!(generated._id, Some(42));
+
+    let mut existing = mongo::schema::Collection7 { _id: Some(1) };
+    assert_eq!(*existing.ensure_id(), 1);
+
+    // `id_gen` is an alias of `_id: {type} gen = {function}`
+    let mut generated = mongo::schema::Collection34 { _id: None };
+    assert_eq!(*generated.ensure_id(), 42);
+
+    // the default `ObjectId` is generated without an `id_gen`
+    let mut generated = mongo::schema::Collection3::builder()
+        .something(None)
+        .build();
+    let id = *generated.ensure_id();
+    assert_eq!(generated._id, Some(id));
+    assert_eq!(*generated.ensure_id(), id);
+}
+
+#[test]
+pub fn check_deny_unknown_fields() {
+    use mongo::schema::{Collection8, Collection9};
+    use mongodb::bson::{de::from_document, doc, oid::ObjectId};
+
+    // `_id` and renamed fields are known
+    let with_id = from_document::<Collection8>(doc! {
+        "_id": ObjectId::parse_str("0123456789ABCDEF01234567").unwrap(),
+        "renamed": 1
+    })
+    .expect("Could not deserialize document with `_id`");
+    assert_eq!(with_id.some_field, 1);
+    assert!(from_document::<Collection8>(doc! { "renamed": 1 }).is_ok());
+
+    // the original name of a renamed field is unknown
+    assert!(from_document::<Collection8>(doc! { "someField": 1 }).is_err());
+    assert!(from_document::<Collection8>(doc! { "renamed": 1, "other": 2 }).is_err());
+
+    // without an `_id` field, `_id` is unknown too
+    assert!(from_document::<Collection9>(doc! { "someField": 1 }).is_ok());
+    assert!(from_document::<Collection9>(doc! {
+        "_id": ObjectId::parse_str("0123456789ABCDEF01234567").unwrap(),
+        "someField": 1
+    })
+    .is_err());
+}
+
+#[test]
+pub fn check_default_find_options() {
+    use mongodb::bson::doc;
+
+    let options = mongo::schema::Collection31::default_find_options()
+        .expect("Collection31 declares default find options");
+    assert_eq!(options.sort, Some(doc! { "counter": -1 }));
+    assert_eq!(options.limit, Some(10));
+    assert_eq!(options.skip, None);
+
+    assert!(mongo::schema::Collection1::default_find_options().is_none());
+}
+
+#[test]
+pub fn check_skip_deserializing_fields() {
+    use mongodb::bson::{de::from_document, doc, ser::to_document};
+
+    // skipped fields are populated from their default, even if the document contains them
+    let loaded: mongo::schema::Collection10 = from_document(doc! {
+        "stored": 1,
+        "label": "from the database",
+        "transientLabel": "from the database"
+    })
+    .expect("Could not deserialize document with skipped fields");
+    assert_eq!(loaded.stored, 1);
+    assert_eq!(loaded.label, "computed");
+    assert_eq!(loaded.transient_label, "computed");
+
+    // `skip_deserializing` alone still serializes the field
+    let document = mongo::schema::Collection10 {
+        stored: 2,
+        label: String::from("changed"),
+        transient_label: String::from("changed"),
+    };
+    assert_eq!(
+        to_document(&document).expect("Could not serialize document with skipped fields"),
+        doc! { "stored": 2_i64, "label": "changed" }
+    );
+}
+
+#[test]
+pub fn check_find_by_id() {
+    fn id_of<T: MongoCollectionId>(_: &T::Id) {}
+    id_of::<mongo::schema::Collection7>(&1_u64);
+    id_of::<mongo::schema::Collection2>(&DefaultId::new());
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+
+    // futures are lazy, nothing is sent to the server until they are polled
+    drop(db_handler.find_collection2_by_id(&DefaultId::new()));
+    drop(db_handler.find_collection7_by_id(&1));
+    drop(db_handler.find_collection12_by_id(&1));
+    drop(db_handler.find_collection15_by_id(&String::from("id")));
+    drop(db_handler.collection22().find_by_id(&String::from("id")));
+    drop(db_handler.delete_collection2_by_id(&DefaultId::new()));
+    drop(db_handler.delete_collection15_by_id(&String::from("id")));
+    drop(db_handler.collection26().delete_by_id(&DefaultId::new()));
+    // unique indexes of the `@indexes` block, partial ones have no finder
+    drop(db_handler.collection2().find_by_name(&String::from("name")));
+}
+
+#[test]
+pub fn check_replace_with() {
+    use mongodb::options::{Acknowledgment, ReplaceOptions, WriteConcern};
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    let options = ReplaceOptions::builder()
+        .upsert(true)
+        .write_concern(WriteConcern::builder().w(Acknowledgment::Majority).build())
+        .build();
+
+    // fails before contacting the server
+    let document = mongo::schema::Collection7::builder().build();
+    assert!(
+        tokio_test::block_on(db_handler.replace_collection7_with(&document, options.clone()))
+            .is_err()
+    );
+
+    // futures are lazy, nothing is sent to the server until they are polled
+    let document = mongo::schema::Collection7 { _id: Some(1) };
+    drop(db_handler.replace_collection7_with(&document, options.clone()));
+    drop(db_handler.collection7().replace_with(&document, options));
+}
+
+#[test]
+pub fn check_save_changes_without_id() {
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+
+    // fails before contacting the server
+    let document = mongo::schema::Collection2::builder()
+        .name(String::from("Alice"))
+        .counter(1)
+        .build();
+    assert!(tokio_test::block_on(db_handler.save_changes_collection2(&document)).is_err());
+}
+
+#[test]
+pub fn check_save_changes_clears_option() {
+    use mongo::schema::Collection14;
+    use mongodb::bson::{doc, oid::ObjectId, to_document};
+    use mongodb_ext::changes_update;
+
+    let id = ObjectId::new();
+    let stored = Collection14 {
+        _id: Some(id),
+        nickname: Some(String::from("Al")),
+        deleted_at: None,
+        comment: Some(String::from("first")),
+    };
+    let cleared = Collection14 {
+        _id: Some(id),
+        nickname: None,
+        deleted_at: None,
+        comment: None,
+    };
+    let stored = to_document(&stored).unwrap();
+
+    // omitted `None` values are removed, others are set to `null`
+    assert_eq!(
+        changes_update(&stored, &to_document(&cleared).unwrap()),
+        Some(doc! { "$set": { "comment": null }, "$unset": { "nick": "" } })
+    );
+    assert_eq!(changes_update(&stored, &stored), None);
+}
+
+#[test]
+pub fn check_upsert_batches() {
+    use mongodb::bson::{doc, Binary};
+    use mongodb_ext::{write_batches, UpsertManyResult, MAX_WRITE_BATCH_SIZE};
+
+    let small = (0..=MAX_WRITE_BATCH_SIZE as i64)
+        .map(|id| doc! { "q": { "_id": id } })
+        .collect();
+    let batches: Vec<usize> = write_batches(small).iter().map(Vec::len).collect();
+    assert_eq!(batches, vec![MAX_WRITE_BATCH_SIZE, 1]);
+
+    let payload = Binary {
+        subtype: mongodb::bson::spec::BinarySubtype::Generic,
+        bytes: vec![0; 6 * 1024 * 1024],
+    };
+    let large = (0..3)
+        .map(|id| doc! { "_id": id, "payload": payload.clone() })
+        .collect();
+    let batches: Vec<usize> = write_batches(large).iter().map(Vec::len).collect();
+    assert_eq!(batches, vec![2, 1]);
+    assert!(write_batches(Vec::new()).is_empty());
+
+    // nothing is sent without documents
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    assert_eq!(
+        tokio_test::block_on(db_handler.upsert_collection2(Vec::new())).unwrap(),
+        UpsertManyResult::default()
+    );
+}
+
+#[test]
+pub fn check_options_initializer() {
+    use mongodb::options::ClientOptions;
+
+    let mut options = tokio_test::block_on(ClientOptions::parse("mongodb://example.com")).unwrap();
+    options.app_name = Some(String::from("mongodb-ext-tests"));
+    let db_handler = tokio_test::block_on(mongo::Database::new_with_options(options)).unwrap();
+    assert_eq!(db_handler.database.name(), "database");
+    assert_eq!(db_handler.collection1_coll.name(), "collection1");
+
+    // `new` parses the connection string into options first
+    assert!(tokio_test::block_on(mongo::Database::new("invalid connection string")).is_err());
+}
+
+#[test]
+pub fn check_pool_initializer() {
+    let db_handler = tokio_test::block_on(mongo::Database::new_with_pool(
+        "mongodb://example.com",
+        2,
+        10,
+    ))
+    .unwrap();
+    assert_eq!(db_handler.collection1_coll.name(), "collection1");
+
+    // `min` and `max` may be equal
+    assert!(tokio_test::block_on(mongo::Database::new_with_pool(
+        "mongodb://example.com",
+        5,
+        5
+    ))
+    .is_ok());
+
+    match tokio_test::block_on(mongo::Database::new_with_pool(
+        "mongodb://example.com",
+        10,
+        2,
+    )) {
+        Err(e) => assert_eq!(
+            e.get_custom::<String>().map(String::as_str),
+            Some("minPoolSize (10) must not be greater than maxPoolSize (2)")
+        ),
+        Ok(_) => panic!("Constructed a database client with `min` greater than `max`"),
+    }
+}
+
+#[test]
+pub fn check_flattened_base() {
+    use mongo::schema::{Base, Collection11};
+    use mongodb::bson::{de::from_document, doc, ser::to_document};
+
+    let document = Collection11::builder()
+        .base(Base {
+            tenant_id: String::from("tenant"),
+            revision: 3,
+        })
+        .name(String::from("Alice"))
+        .build();
+
+    let serialized = to_document(&document).expect("Could not serialize flattened document");
+    assert_eq!(
+        serialized,
+        doc! { "tenantId": "tenant", "rev": 3_i64, "name": "Alice" }
+    );
+    assert_eq!(
+        from_document::<Collection11>(serialized)
+            .expect("Could not deserialize flattened document"),
+        document
+    );
+}
+
+#[test]
+pub fn check_sequence_id_helper() {
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+
+    // futures are lazy, nothing is sent to the server until they are polled
+    let next_counter_id: mongo::Collection12Repository = db_handler.collection12();
+    drop(next_counter_id.next_id());
+    assert_eq!(mongodb_ext::COUNTERS_COLLECTION, "_counters");
+
+    let document = mongo::schema::Collection12 {
+        _id: Some(1),
+        message: String::from("first"),
+    };
+    assert_eq!(document.id(), &Some(1_i64));
+}
+
+#[test]
+pub fn check_assign_id() {
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+
+    let mut generated = mongo::schema::Collection7 { _id: None };
+    tokio_test::block_on(db_handler.collection7().assign_id(&mut generated)).unwrap();
+    assert_eq!(generated._id, Some(42));
+
+    let mut existing = mongo::schema::Collection7 { _id: Some(1) };
+    tokio_test::block_on(db_handler.collection7().assign_id(&mut existing)).unwrap();
+    assert_eq!(existing._id, Some(1));
+
+    // the default `ObjectId` is generated on the client as well
+    let mut generated = mongo::schema::Collection3::builder()
+        .something(None)
+        .build();
+    tokio_test::block_on(db_handler.collection3().assign_id(&mut generated)).unwrap();
+    assert!(generated._id.is_some());
+
+    // ids without generator must be given
+    let mut missing = mongo::schema::Collection15 {
+        _id: None,
+        name: String::from("Alice"),
+    };
+    let error = tokio_test::block_on(db_handler.collection15().assign_id(&mut missing))
+        .expect_err("Assigned an id without generator");
+    assert!(error
+        .get_custom::<String>()
+        .expect("Expected a custom error")
+        .contains("without `_id`"));
+
+    // the counter is only read from the server when the `_id` is missing
+    let mut existing = mongo::schema::Collection12 {
+        _id: Some(7),
+        message: String::from("seventh"),
+    };
+    tokio_test::block_on(db_handler.collection12().assign_id(&mut existing)).unwrap();
+    assert_eq!(existing._id, Some(7));
+    drop(db_handler.insert_collection12(mongo::schema::Collection12 {
+        _id: None,
+        message: String::from("first"),
+    }));
+}
+
+#[test]
+pub fn check_display() {
+    let document = mongo::schema::Collection32::builder()
+        .name(String::from("Alice"))
+        .counter(1)
+        .build();
+    assert_eq!(document.to_string(), "Alice");
+
+    let mut generated = mongo::schema::Collection7::builder().build();
+    assert_eq!(generated.to_string(), "None");
+    generated.ensure_id();
+    assert_eq!(format!("id: {}", generated), "id: 42");
+}
+
+#[test]
+pub fn check_type_alias_fields() {
+    use mongo::schema::{Collection13, Ledger, Money};
+    use mongodb::bson::{de::from_document, doc, ser::to_document};
+
+    let money = Money::from_bytes([0; 16]);
+    let mut ledger = Ledger::new();
+    ledger.insert(String::from("alice"), vec![money]);
+
+    let document = Collection13::builder()
+        .balance(money)
+        .ledger(ledger)
+        .build();
+    let serialized =
+        to_document(&document).expect("Could not serialize document using type aliases");
+    assert_eq!(
+        serialized,
+        doc! { "balance": money, "ledger": { "alice": [money] } }
+    );
+
+    let deserialized: Collection13 =
+        from_document(serialized).expect("Could not deserialize document using type aliases");
+    assert_eq!(deserialized.ledger["alice"], vec![money]);
+}
+
+#[test]
+pub fn check_null_handling() {
+    use mongo::schema::Collection14;
+    use mongodb::bson::{de::from_document, doc, ser::to_document};
+
+    let empty = Collection14::builder()
+        .nickname(None)
+        .deleted_at(None)
+        .comment(None)
+        .build();
+    assert_eq!(
+        to_document(&empty).expect("Could not serialize document with null fields"),
+        doc! { "deletedAt": null, "comment": null }
+    );
+
+    let filled = Collection14::builder()
+        .nickname(Some(String::from("bob")))
+        .deleted_at(Some(1))
+        .comment(None)
+        .build();
+    let serialized = to_document(&filled).expect("Could not serialize document with null fields");
+    assert_eq!(
+        serialized,
+        doc! { "nick": "bob", "deletedAt": 1_i64, "comment": null }
+    );
+
+    let deserialized: Collection14 =
+        from_document(doc! { "comment": "hi" }).expect("Could not deserialize missing fields");
+    assert_eq!(deserialized.nickname, None);
+    assert_eq!(deserialized.comment.as_deref(), Some("hi"));
+}
+
+#[test]
+pub fn check_no_accessor() {
+    use mongo::schema::Collection15;
+
+    let mut document = Collection15::builder().name(String::from("bob")).build();
+    assert_eq!(document.id(), "bob");
+    document._id = Some(String::from("alice"));
+    assert_eq!(document.id(), "alice");
+}
+
+#[test]
+pub fn check_dates_as_iso() {
+    use mongo::schema::Collection16;
+    use mongodb::bson::{de::from_document, doc, to_raw_document_buf, DateTime};
+
+    let date = DateTime::from_millis(1_700_000_000_000);
+    let document = Collection16 {
+        created_at: date,
+        deleted_at: None,
+        updated_at: date,
+    };
+
+    let json = serde_json::to_value(&document).expect("Could not serialize dates to JSON");
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "createdAt": "2023-11-14T22:13:20Z",
+            "deletedAt": null,
+            "updatedAt": { "$date": { "$numberLong": "1700000000000" } },
+        })
+    );
+    let from_json: Collection16 =
+        serde_json::from_value(json).expect("Could not deserialize dates from JSON");
+    assert_eq!(from_json, document);
+
+    let deleted = Collection16 {
+        deleted_at: Some(date),
+        ..document
+    };
+    let bson = to_raw_document_buf(&deleted)
+        .expect("Could not serialize dates to BSON")
+        .to_document()
+        .expect("Could not read serialized BSON");
+    assert_eq!(
+        bson,
+        doc! { "createdAt": date, "deletedAt": date, "updatedAt": date }
+    );
+    let from_bson: Collection16 = from_document(bson).expect("Could not deserialize BSON dates");
+    assert_eq!(from_bson, deleted);
+
+    let missing: Collection16 = from_document(doc! { "createdAt": date, "updatedAt": date })
+        .expect("Could not deserialize missing optional date");
+    assert_eq!(missing.deleted_at, None);
+}
+
+#[test]
+pub fn check_repositories() {
+    use mongo::{Collection1Repository, Collection2Repository};
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+
+    let collection1: Collection1Repository = db_handler.collection1();
+    assert_eq!(
+        collection1.collection().name(),
+        mongo::schema::Collection1::NAME
+    );
+    assert_eq!(collection1.database().name(), mongo::Database::NAME);
+
+    let collection2: Collection2Repository = db_handler.collection2().clone();
+    // futures are lazy, nothing is sent to the server until they are polled
+    drop(collection2.find(None, None));
+    drop(collection2.export(500));
+    drop(collection2.stats());
+    drop(collection2.exists());
+}
+
+#[test]
+pub fn check_all_default() {
+    use mongo::schema::Collection17;
+    use mongodb::bson::{de::from_document, doc};
+
+    let sparse: Collection17 =
+        from_document(doc! { "visits": 3_i64 }).expect("Could not deserialize sparse document");
+    assert_eq!(
+        sparse,
+        Collection17 {
+            _id: None,
+            name: String::new(),
+            visits: 3,
+            tags: Vec::new(),
+            label: crate::default_label(),
+        }
+    );
+    assert_eq!(Collection17::default().label, String::new());
+    assert!(from_document::<Collection17>(doc! {}).is_ok());
+}
+
+#[test]
+pub fn check_enum_variants_not_renamed() {
+    use mongo::schema::Collection18;
+    use mongodb::bson::{de::from_document, doc, ser::to_document};
+
+    let document = Collection18 {
+        current_status: Status::OnHold { since_days: 3 },
+        previous_status: RenamedStatus::InProgress,
+    };
+    let serialized = to_document(&document).expect("Could not serialize enum fields");
+    // only field names are renamed to `camelCase`, variants and their fields keep the enum's own naming
+    assert_eq!(
+        serialized,
+        doc! {
+            "currentStatus": { "OnHold": { "since_days": 3_i64 } },
+            "previousStatus": "in_progress",
+        }
+    );
+    assert_eq!(
+        from_document::<Collection18>(serialized).expect("Could not deserialize enum fields"),
+        document
+    );
+
+    let unit_variant = to_document(&Collection18 {
+        current_status: Status::InProgress,
+        previous_status: RenamedStatus::OnHold,
+    })
+    .expect("Could not serialize enum fields");
+    assert_eq!(unit_variant.get_str("currentStatus"), Ok("InProgress"));
+    assert_eq!(unit_variant.get_str("previousStatus"), Ok("on_hold"));
+}
+
+#[test]
+pub fn check_health_report() {
+    use mongodb_ext::HealthReport;
+    use std::time::Duration;
+
+    assert!(mongo::Database::COLLECTION_NAMES.contains(&mongo::schema::Collection12::NAME));
+    assert_eq!(mongo::Database::COLLECTION_NAMES[0], "collection1");
+    assert_eq!(
+        mongo::Database::COLLECTION_NAMES.last(),
+        Some(&mongo::schema::Collection24::NAME)
+    );
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    // futures are lazy, nothing is sent to the server until they are polled
+    drop(db_handler.health_report());
+    drop(db_handler.list_databases());
+    drop(db_handler.ping_latency());
+
+    let mut report = HealthReport {
+        ping_latency: Ok(Duration::from_millis(3)),
+        server_version: Ok(String::from("6.0.5")),
+        collections: Ok(vec![("collection1", true), ("collection2", true)]),
+    };
+    assert!(report.is_healthy());
+
+    report.collections = Ok(vec![("collection1", true), ("collection2", false)]);
+    assert!(!report.is_healthy());
+
+    report.collections = Ok(Vec::new());
+    report.server_version = Err(mongodb::error::Error::custom(String::from("no buildInfo")));
+    assert!(!report.is_healthy());
+}
+
+#[test]
+pub fn check_computed_fields() {
+    use mongo::schema::Collection19;
+    use mongodb::bson::{de::from_document, doc, ser::to_document};
+
+    let mut document = Collection19::builder().price(3).amount(2).build();
+    assert_eq!(document.total, 0);
+    document.total = 6;
+    assert_eq!(
+        to_document(&document).expect("Could not serialize computed fields"),
+        doc! { "price": 3_i64, "amount": 2_i64 }
+    );
+
+    let aggregated: Collection19 =
+        from_document(doc! { "price": 3_i64, "amount": 2_i64, "total": 6_i64 })
+            .expect("Could not deserialize aggregation output");
+    assert_eq!(aggregated.total, 6);
+    let stored: Collection19 = from_document(doc! { "price": 3_i64, "amount": 2_i64 })
+        .expect("Could not deserialize stored document");
+    assert_eq!(stored.total, 0);
+}
+
+#[test]
+pub fn check_binary_fields() {
+    use mongo::schema::Collection20;
+    use mongodb::bson::{de::from_document, doc, spec::BinarySubtype, Binary, Bson};
+
+    let binary = |bytes: &[u8]| Binary {
+        subtype: BinarySubtype::Generic,
+        bytes: bytes.to_vec(),
+    };
+    let document = Collection20 {
+        payload: vec![0, 1, 255],
+        thumbnail: Some(vec![7]),
+        unmarked: vec![1, 2],
+    };
+
+    let serialized = mongodb::bson::to_raw_document_buf(&document)
+        .expect("Could not serialize binary fields")
+        .to_document()
+        .expect("Could not read serialized binary fields");
+    assert_eq!(
+        serialized,
+        doc! {
+            "payload": binary(&[0, 1, 255]),
+            "thumbnail": binary(&[7]),
+            "unmarked": [1, 2],
+        }
+    );
+    assert_eq!(
+        from_document::<Collection20>(serialized).expect("Could not deserialize binary fields"),
+        document
+    );
+
+    // arrays of numbers written before `#[binary]` was added are still readable
+    let legacy: Collection20 = from_document(doc! {
+        "payload": [0, 1, 255],
+        "thumbnail": Bson::Null,
+        "unmarked": [],
+    })
+    .expect("Could not deserialize legacy arrays");
+    assert_eq!(legacy.payload, vec![0, 1, 255]);
+    assert_eq!(legacy.thumbnail, None);
+    assert!(from_document::<Collection20>(doc! { "payload": [256], "unmarked": [] }).is_err());
+}
+
+#[test]
+pub fn check_alternate_keys() {
+    use mongodb::bson::doc;
+
+    let models = mongo::schema::Collection21::index_models();
+    assert_eq!(models.len(), 1);
+    assert_eq!(models[0].keys, doc! { "customerId": 1 });
+    assert!(mongo::schema::Collection2::index_models().is_empty());
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    // futures are lazy, nothing is sent to the server until they are polled
+    drop(
+        db_handler
+            .collection21()
+            .find_by_customer_id(&String::from("C-42")),
+    );
+    drop(db_handler.collection29().find_by_email(&String::new()));
+}
+
+#[test]
+pub fn check_read_models() {
+    use mongo::schema::Collection21WithCollection2;
+    use mongodb::bson::doc;
+
+    let id = DefaultId::new();
+    let read_model = Collection21WithCollection2::try_from(doc! {
+        "_id": id,
+        "customerId": "C-42",
+        "collection2s": [
+            { "counter": 1, "name": "joined" },
+        ],
+    })
+    .expect("Could not convert read model document");
+    assert_eq!(read_model.id, id);
+    assert_eq!(read_model.customer_id, "C-42");
+    assert_eq!(read_model.collection2s.len(), 1);
+    assert_eq!(read_model.collection2s[0].name, "joined");
+
+    // read models are neither collections nor views
+    assert!(!mongo::Database::COLLECTION_NAMES.contains(&"collection21WithCollection2"));
+    assert_eq!(mongo::Database::view_options().len(), 1);
+    assert!(Collection21WithCollection2::try_from(doc! { "customerId": "C-42" }).is_err());
+}
+
+#[test]
+pub fn check_views() {
+    use mongo::schema::Collection21ByName;
+    use mongodb::bson::{doc, from_document};
+    use mongodb_ext::MongoView;
+
+    assert_eq!(Collection21ByName::NAME, "collection21ByName");
+    assert_eq!(Collection21ByName::VIEW_ON, "collection21");
+    assert_eq!(
+        Collection21ByName::pipeline(),
+        vec![
+            doc! { "$sort": { "name": 1 } },
+            doc! { "$project": { "customerId": 1, "name": 1 } },
+        ]
+    );
+
+    let options = mongo::Database::view_options();
+    assert_eq!(options.len(), 1);
+    assert_eq!(options[0].0, "collection21ByName");
+    assert_eq!(options[0].1.view_on.as_deref(), Some("collection21"));
+    assert_eq!(options[0].1.pipeline, Some(Collection21ByName::pipeline()));
+    // views are no collections
+    assert!(!mongo::Database::COLLECTION_NAMES.contains(&"collection21ByName"));
+
+    let id = DefaultId::new();
+    let view: Collection21ByName = from_document(doc! {
+        "_id": id,
+        "customerId": "C-42",
+        "name": "unused",
+    })
+    .expect("Could not deserialize view document");
+    assert_eq!(view.id, id);
+    assert_eq!(view.customer_id, "C-42");
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    assert_eq!(
+        db_handler.collection21_by_name_view.name(),
+        "collection21ByName"
+    );
+}
+
+#[test]
+pub fn check_count_with() {
+    use mongodb::{
+        bson::doc,
+        options::{CountOptions, Hint},
+    };
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    let options = CountOptions::builder()
+        .hint(Hint::Keys(doc! { "customerId": 1 }))
+        .limit(10)
+        .build();
+    // futures are lazy, nothing is sent to the server until they are polled
+    drop(db_handler.count_collection21_with(doc! { "name": "a" }, options.clone()));
+    drop(db_handler.collection21().count_with(doc! {}, options));
+}
+
+#[test]
+pub fn check_conditional_derives() {
+    use mongo::schema::{Collection23, PartialCollection23};
+
+    // `PartialEq` is only derived in tests, next to the derives of the macro
+    let document = Collection23 { key: 1, value: 0.5 };
+    assert_eq!(document, Collection23 { key: 1, value: 0.5 });
+    assert_ne!(document, Collection23 { key: 2, value: 0.5 });
+    assert_eq!(
+        PartialCollection23 {
+            key: Some(1),
+            value: None
+        },
+        PartialCollection23 {
+            key: Some(1),
+            value: None
+        }
+    );
+}
+
+#[test]
+pub fn check_pipeline() {
+    use mongodb::bson::{doc, Document};
+    use mongodb_ext::Pipeline;
+
+    let pipeline = Pipeline::new()
+        .match_(doc! { "name": "a" })
+        .unwind("$tags")
+        .project(doc! { "tags": 1 })
+        .skip(5)
+        .stage(doc! { "$sample": { "size": 2 } })
+        .count("total");
+    let stages: Vec<Document> = pipeline.clone().into();
+    assert_eq!(
+        stages,
+        vec![
+            doc! { "$match": { "name": "a" } },
+            doc! { "$unwind": "$tags" },
+            doc! { "$project": { "tags": 1 } },
+            doc! { "$skip": 5_i64 },
+            doc! { "$sample": { "size": 2 } },
+            doc! { "$count": "total" },
+        ]
+    );
+    assert!(Pipeline::new().build().is_empty());
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    // futures are lazy, nothing is sent to the server until they are polled
+    drop(db_handler.aggregate_collection21(pipeline, None));
+    drop(
+        db_handler
+            .collection21()
+            .aggregate(vec![doc! { "$match": {} }], None),
+    );
+}
+
+#[test]
+pub fn check_update_and_delete_many() {
+    use mongodb::bson::doc;
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    // futures are lazy, nothing is sent to the server until they are polled
+    drop(db_handler.delete_collection21(doc! { "name": "a" }));
+    drop(db_handler.collection21().delete_many(doc! {}));
+    drop(db_handler.update_collection21(doc! {}, doc! { "$set": { "name": "b" } }));
+    drop(
+        db_handler
+            .collection21()
+            .update_many(doc! { "name": "b" }, doc! { "$unset": { "name": "" } }),
+    );
+}
+
+#[test]
+pub fn check_partial() {
+    use mongo::schema::{PartialCollection22, PartialCollection23};
+    use mongodb::bson::{doc, from_document, spec::BinarySubtype, Binary};
+
+    let partial: PartialCollection22 = from_document(doc! {
+        "_id": "a",
+        "fullName": "Alice",
+        "payload": Binary { subtype: BinarySubtype::Generic, bytes: vec![1, 2] },
+        "tenantId": "t",
+        "rev": 3,
+    })
+    .expect("Could not deserialize partial document");
+    assert_eq!(partial._id.as_deref(), Some("a"));
+    assert_eq!(partial.name.as_deref(), Some("Alice"));
+    assert_eq!(partial.payload, Some(vec![1, 2]));
+    assert_eq!(partial.nickname, None);
+    assert_eq!(partial.base.map(|base| base.revision), Some(3));
+
+    let empty: PartialCollection22 =
+        from_document(doc! {}).expect("Could not deserialize empty document");
+    assert_eq!(empty._id, None);
+    assert_eq!(empty.name, None);
+    assert!(empty.base.is_none());
+
+    let partial: PartialCollection23 =
+        from_document(doc! { "_id": 4 }).expect("Could not deserialize partial document");
+    assert_eq!(partial.key, Some(4));
+    assert_eq!(partial.value, None);
+
+    // optional fields with `#[binary]` / `#[date_as_iso]` keep their serialization
+    let partial: mongo::schema::PartialCollection20 = from_document(doc! {
+        "thumbnail": Binary { subtype: BinarySubtype::Generic, bytes: vec![7] },
+    })
+    .expect("Could not deserialize partial binary document");
+    assert_eq!(partial.thumbnail, Some(Some(vec![7])));
+    assert_eq!(partial.payload, None);
+    let partial: mongo::schema::PartialCollection16 =
+        from_document(doc! { "deletedAt": "2020-01-02T03:04:05Z" })
+            .expect("Could not deserialize partial date document");
+    assert_eq!(
+        partial.deleted_at,
+        Some(Some(
+            mongodb::bson::DateTime::parse_rfc3339_str("2020-01-02T03:04:05Z").unwrap()
+        ))
+    );
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    // futures are lazy, nothing is sent to the server until they are polled
+    drop(
+        db_handler
+            .collection22()
+            .find_projected(doc! {}, &["fullName", "payload"]),
+    );
+}
+
+#[test]
+pub fn check_as_string_and_as_i64() {
+    use mongo::schema::{Collection24, PartialCollection24};
+    use mongodb::bson::{doc, from_document, to_document, Bson, DateTime};
+
+    let document = Collection24 {
+        big: u128::MAX,
+        ratio: Some(0.5),
+        created_at: DateTime::from_millis(1_000),
+        deleted_at: None,
+    };
+    let serialized = to_document(&document).expect("Could not serialize document");
+    assert_eq!(
+        serialized,
+        doc! {
+            "big": u128::MAX.to_string(),
+            "ratio": "0.5",
+            "createdAt": 1_000_i64,
+            "deletedAt": Bson::Null,
+        }
+    );
+    assert_eq!(
+        from_document::<Collection24>(serialized).expect("Could not deserialize document"),
+        document
+    );
+
+    // values written before the attributes were added are still readable
+    let legacy: Collection24 = from_document(doc! {
+        "big": 7,
+        "ratio": 1.5,
+        "createdAt": DateTime::from_millis(2_000),
+        "deletedAt": 3_000_i64,
+    })
+    .expect("Could not deserialize legacy document");
+    assert_eq!(legacy.big, 7);
+    assert_eq!(legacy.ratio, Some(1.5));
+    assert_eq!(legacy.created_at, DateTime::from_millis(2_000));
+    assert_eq!(legacy.deleted_at, Some(DateTime::from_millis(3_000)));
+    assert!(from_document::<Collection24>(doc! { "big": "x", "createdAt": 0_i64 }).is_err());
+    assert!(from_document::<Collection24>(doc! { "big": "1", "createdAt": "0" }).is_err());
+
+    let partial: PartialCollection24 = from_document(doc! { "ratio": "2", "deletedAt": 4_000_i64 })
+        .expect("Could not deserialize partial document");
+    assert_eq!(partial.big, None);
+    assert_eq!(partial.ratio, Some(Some(2.0)));
+    assert_eq!(partial.deleted_at, Some(Some(DateTime::from_millis(4_000))));
+}
+
+#[test]
+pub fn check_collection_handles() {
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    let handle: mongodb::Collection<mongo::schema::Collection21> = db_handler.collection21_handle();
+    // the handle is owned, thus it can be moved into a spawned task
+    let name = std::thread::spawn(move || handle.name().to_string())
+        .join()
+        .unwrap();
+    assert_eq!(name, "collection21");
+}
+
+#[test]
+pub fn check_bump_schema_version() {
+    // tracking the schema version does not add an index
+    assert_eq!(mongo::schema::Collection21::index_models().len(), 1);
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    // futures are lazy, nothing is sent to the server until they are polled
+    drop(db_handler.collection21().bump_schema_version(2));
+}
+
+#[test]
+pub fn check_versioned_fields() {
+    use mongo::schema::Collection21;
+    use mongodb::bson::{doc, from_document, to_document};
+
+    // documents stored before version 3 have no email
+    let old: Collection21 = from_document(doc! {
+        "customerId": "C-42",
+        "name": "a",
+        "schemaVersion": 2,
+    })
+    .expect("Could not deserialize document of version 2");
+    assert_eq!(old.email, "");
+
+    // the current version always writes it
+    let document = to_document(&old).unwrap();
+    assert_eq!(document.get_str("email"), Ok(""));
+}
+
+#[test]
+pub fn check_soft_delete() {
+    use mongo::schema::{Collection1, Collection26, PartialCollection26};
+    use mongodb::bson::{doc, from_document, to_document, Bson, DateTime};
+    use mongodb_ext::DefaultId;
+
+    assert_eq!(
+        [Collection26::SOFT_DELETE, Collection1::SOFT_DELETE],
+        [true, false]
+    );
+
+    // the field is added and stores `null` until the document is deleted
+    let document = Collection26::builder()
+        .email(String::from("a@example.com"))
+        .build();
+    assert_eq!(document.deleted_at, None);
+    assert_eq!(
+        to_document(&document).unwrap().get("deletedAt"),
+        Some(&Bson::Null)
+    );
+    let deleted: Collection26 = from_document(doc! {
+        "email": "a@example.com",
+        "deletedAt": DateTime::from_millis(0),
+    })
+    .expect("Could not deserialize deleted document");
+    assert_eq!(deleted.deleted_at, Some(DateTime::from_millis(0)));
+    let partial: PartialCollection26 =
+        from_document(doc! { "deletedAt": null }).expect("Could not deserialize partial document");
+    assert_eq!(partial.deleted_at, None);
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    let id = DefaultId::new();
+    // futures are lazy, nothing is sent to the server until they are polled
+    drop(db_handler.collection26().soft_delete_by_id(&id));
+    drop(db_handler.collection26().restore_by_id(&id));
+    drop(
+        db_handler
+            .collection26()
+            .find_with_deleted(doc! { "email": "a@example.com" }, None),
+    );
+    drop(
+        db_handler
+            .collection26()
+            .count_with_deleted(doc! {}, Default::default()),
+    );
+}
+
+#[test]
+pub fn check_exclude_soft_deleted() {
+    use mongo::schema::{Collection1, Collection26};
+    use mongodb::bson::doc;
+    use mongodb_ext::exclude_soft_deleted;
+
+    assert_eq!(
+        exclude_soft_deleted::<Collection26>(None),
+        Some(doc! { "deletedAt": null })
+    );
+    assert_eq!(
+        exclude_soft_deleted::<Collection26>(Some(doc! { "email": "a" })),
+        Some(doc! { "email": "a", "deletedAt": null })
+    );
+    // explicit filters select soft deleted documents
+    let deleted = doc! { "deletedAt": { "$exists": true } };
+    assert_eq!(
+        exclude_soft_deleted::<Collection26>(Some(deleted.clone())),
+        Some(deleted)
+    );
+    assert_eq!(
+        exclude_soft_deleted::<Collection1>(Some(doc! { "email": "a" })),
+        Some(doc! { "email": "a" })
+    );
+    assert_eq!(exclude_soft_deleted::<Collection1>(None), None);
+}
+
+#[test]
+pub fn check_read_field() {
+    use mongo::schema::Collection22;
+    use mongodb::bson::rawdoc;
+
+    assert_eq!(Collection22::field_key("_id"), Some("_id"));
+    assert_eq!(Collection22::field_key("name"), Some("fullName"));
+    assert_eq!(Collection22::field_key("nickname"), Some("nickname"));
+    // flattened fields have no key of their own
+    assert_eq!(Collection22::field_key("base"), None);
+    assert_eq!(Collection22::field_key("fullName"), None);
+    assert_eq!(
+        Collection22::field_mapping(),
+        vec![
+            ("_id", "_id"),
+            ("name", "fullName"),
+            ("payload", "payload"),
+            ("nickname", "nickname"),
+        ]
+    );
+
+    // `rename` is found in combined attributes, skipped fields are never stored
+    assert_eq!(
+        mongo::schema::Collection36::field_mapping(),
+        vec![("renamed", "x"), ("split", "out"), ("optional", "maybe")]
+    );
+    assert_eq!(mongo::schema::Collection36::field_key("hidden"), None);
+    assert_eq!(
+        mongo::schema::Collection10::field_key("label"),
+        Some("label")
+    );
+    assert_eq!(
+        mongo::schema::Collection10::field_key("transient_label"),
+        None
+    );
+    assert_eq!(mongo::schema::Collection4::field_key("ignored_field"), None);
+
+    let raw = rawdoc! {
+        "_id": "a",
+        "fullName": "Alice",
+        "nickname": null,
+        "tenantId": "t",
+        "rev": 3,
+    };
+    let name: Option<String> = Collection22::read_field(&raw, "name").unwrap();
+    assert_eq!(name.as_deref(), Some("Alice"));
+    let nickname: Option<Option<String>> = Collection22::read_field(&raw, "nickname").unwrap();
+    assert_eq!(nickname, Some(None));
+    let payload: Option<Vec<u8>> = Collection22::read_field(&raw, "payload").unwrap();
+    assert_eq!(payload, None);
+
+    let error = Collection22::read_field::<String>(&raw, "unknown").unwrap_err();
+    assert!(error.to_string().contains("has no field `unknown`"));
+    let error = Collection22::read_field::<u32>(&raw, "name").unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("failed to read field `fullName` of `collection22`"));
+
+    // the generic helper takes keys
+    let tenant: Option<String> =
+        mongodb_ext::read_raw_field("collection22", &raw, "tenantId").unwrap();
+    assert_eq!(tenant.as_deref(), Some("t"));
+}
+
+#[test]
+pub fn check_get_or_insert() {
+    use mongodb::bson::doc;
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    let default = mongo::schema::Collection21 {
+        _id: None,
+        customer_id: String::from("C-42"),
+        name: String::from("a"),
+        schema_version: 3,
+        email: String::from("a@example.com"),
+    };
+    // futures are lazy, nothing is sent to the server until they are polled
+    drop(db_handler.get_or_insert_collection21(doc! { "customerId": "C-42" }, default));
+}
+
+#[test]
+pub fn check_deserialization_context() {
+    use mongodb::{bson::doc, error::ErrorKind};
+    use mongodb_ext::deserialize_document;
+
+    let document: mongo::schema::Collection23 =
+        deserialize_document("collection23", doc! { "_id": 1, "value": 0.5 })
+            .expect("Could not deserialize document");
+    assert_eq!(document.key, 1);
+
+    let error = deserialize_document::<mongo::schema::Collection23>(
+        "collection23",
+        doc! { "_id": 1, "value": "x" },
+    )
+    .unwrap_err();
+    assert!(matches!(*error.kind, ErrorKind::BsonDeserialization(_)));
+    assert!(error
+        .to_string()
+        .contains("failed to deserialize document with `_id` 1 of `collection23`"));
+
+    let error =
+        deserialize_document::<mongo::schema::Collection23>("collection23", doc! {}).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("failed to deserialize document without `_id` of `collection23`"));
+}
+
+#[test]
+pub fn check_sensitive_fields() {
+    let document = mongo::schema::Collection25 {
+        _id: Some(String::from("a")),
+        email: String::from("alice@example.com"),
+        phone: Some(String::from("+1 555")),
+        name: String::from("Alice"),
+        schema_version: 1,
+    };
+    assert_eq!(
+        format!("{:?}", document),
+        r#"Collection25 { _id: Some("a"), email: "<redacted>", phone: "<redacted>", name: "Alice", schema_version: 1 }"#
+    );
+    // markers of sensitive fields still apply
+    assert_eq!(mongo::schema::Collection25::index_models().len(), 2);
+}
+
+#[test]
+pub fn check_write_helpers_validate() {
+    use mongo::schema::Collection27;
+    use mongodb_ext::ValidationError;
+
+    // the server is unreachable, thus any I/O would fail with another error
+    let db_handler = tokio_test::block_on(mongo::Database::new(
+        "mongodb://localhost:1/?serverSelectionTimeoutMS=50",
+    ))
+    .unwrap();
+    let invalid = || Collection27 {
+        name: String::from("Alice"),
+        age: None,
+        tags: Vec::new(),
+        scores: Some(vec![1, 1]),
+    };
+    let expected = ValidationError::new("collection27", "scores", "items must be unique");
+
+    let error = tokio_test::block_on(db_handler.insert_collection27(invalid())).unwrap_err();
+    assert_eq!(error.get_custom::<ValidationError>(), Some(&expected));
+    let error = tokio_test::block_on(
+        db_handler.get_or_insert_collection27(mongodb::bson::doc! {}, invalid()),
+    )
+    .unwrap_err();
+    assert_eq!(error.get_custom::<ValidationError>(), Some(&expected));
+}
+
+#[test]
+pub fn check_validated_builder() {
+    use mongo::schema::{Collection2, Collection27};
+    use mongodb_ext::{MongoCollection, ValidationError};
+
+    let document = Collection27::builder()
+        .name(String::from("Alice"))
+        .age(Some(30))
+        .tags(vec![String::from("a")])
+        .scores(Some(vec![1, 2, 3]))
+        .try_build()
+        .expect("Could not build valid document");
+    assert_eq!(document.validate(), Ok(()));
+
+    let error = Collection27::builder()
+        .name(String::from("Alice"))
+        .age(None)
+        .tags(vec![
+            String::from("a"),
+            String::from("b"),
+            String::from("c"),
+        ])
+        .scores(None)
+        .try_build()
+        .unwrap_err();
+    assert_eq!(
+        error,
+        ValidationError::new("collection27", "tags", "length must be at most 2")
+    );
+    assert_eq!(
+        error.to_string(),
+        "invalid field `tags` of `collection27`: length must be at most 2"
+    );
+
+    // strings count characters, not bytes
+    let document = Collection27 {
+        name: String::from("ääääääääää"),
+        age: Some(121),
+        tags: Vec::new(),
+        scores: None,
+    };
+    assert_eq!(
+        document.validate().unwrap_err(),
+        ValidationError::new("collection27", "age", "must be at most 120")
+    );
+    let document = Collection27 {
+        name: String::new(),
+        ..document
+    };
+    assert_eq!(
+        document.validate().unwrap_err().message,
+        "length must be at least 1"
+    );
+
+    // array elements are counted and compared
+    let document = Collection27 {
+        name: String::from("Alice"),
+        age: None,
+        tags: Vec::new(),
+        scores: Some(vec![1, 2, 3, 4]),
+    };
+    assert_eq!(
+        document.validate().unwrap_err(),
+        ValidationError::new("collection27", "scores", "must have at most 3 items")
+    );
+    let document = Collection27 {
+        scores: Some(vec![1, 2, 1]),
+        ..document
+    };
+    assert_eq!(
+        document.validate().unwrap_err().message,
+        "items must be unique"
+    );
+
+    // collections without validator attributes are always valid
+    assert_eq!(
+        Collection2 {
+            _id: None,
+            counter: 0,
+            name: String::new()
+        }
+        .validate(),
+        Ok(())
+    );
+}
+
+#[test]
+pub fn check_order_by() {
+    use mongo::schema::{
+        Collection1, Collection16, Collection2, Collection24, Collection33, Collection35,
+    };
+    use mongodb::bson::doc;
+
+    assert_eq!(Collection16::order_by(), Some(doc! { "createdAt": -1 }));
+    assert_eq!(
+        Collection24::order_by(),
+        Some(doc! { "createdAt": -1, "big": 1 })
+    );
+    assert_eq!(Collection33::order_by(), Some(doc! { "counter": 1 }));
+    assert_eq!(Collection35::order_by(), Some(doc! { "counter": -1 }));
+    assert_eq!(Collection2::order_by(), None);
+    assert_eq!(Collection1::order_by(), None);
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    drop(db_handler.latest_collection16());
+    drop(db_handler.latest_collection1());
+}
+
+#[test]
+pub fn check_omit_none() {
+    use mongo::schema::Collection28;
+    use mongodb::bson::{doc, from_document, to_document};
+
+    let document = Collection28::builder()
+        .nickname(None)
+        .last_login(None)
+        .scores(None)
+        .note(None)
+        .name(String::from("Alice"))
+        .aliases(vec![None])
+        .build();
+    // `null_as(null)` and the `deleted_at` field of `soft_delete` keep writing `null`
+    let serialized = to_document(&document).unwrap();
+    assert_eq!(
+        serialized,
+        doc! { "deletedAt": null, "lastLogin": null, "name": "Alice", "aliases": [null] }
+    );
+    assert_eq!(
+        from_document::<Collection28>(serialized).expect("Could not deserialize document"),
+        document
+    );
+
+    let document = Collection28 {
+        nickname: Some(String::from("Al")),
+        scores: Some(vec![1]),
+        note: Some(String::from("note")),
+        ..document
+    };
+    assert_eq!(
+        to_document(&document).unwrap(),
+        doc! {
+            "deletedAt": null,
+            "nickname": "Al",
+            "lastLogin": null,
+            "scores": [1],
+            "note": "note",
+            "name": "Alice",
+            "aliases": [null],
+        }
+    );
+}
+
+#[test]
+pub fn check_from_map() {
+    use mongo::schema::Collection22;
+    use mongodb::bson::Bson;
+    use std::collections::HashMap;
+
+    let row: HashMap<String, Bson> = [
+        ("_id", Bson::from("a")),
+        ("name", Bson::from("Alice")),
+        ("payload", Bson::Array(vec![Bson::Int32(1), Bson::Int32(2)])),
+        ("nickname", Bson::Null),
+        // flattened fields keep their stored keys
+        ("tenantId", Bson::from("t")),
+        ("rev", Bson::Int32(3)),
+    ]
+    .into_iter()
+    .map(|(column, value)| (String::from(column), value))
+    .collect();
+    let document = Collection22::from_map(row.clone()).expect("Could not build document");
+    assert_eq!(document._id.as_deref(), Some("a"));
+    assert_eq!(document.name, "Alice");
+    assert_eq!(document.payload, vec![1, 2]);
+    assert_eq!(document.nickname, None);
+    assert_eq!(document.base.tenant_id, "t");
+    assert_eq!(document.base.revision, 3);
+
+    let mut incomplete = row;
+    incomplete.remove("name");
+    assert!(Collection22::from_map(incomplete).is_err());
+}
+
+#[test]
+pub fn check_normalize() {
+    use mongo::schema::{Collection29, Collection3};
+
+    let mut document = Collection29::builder()
+        .email(String::from("  Alice@Example.COM "))
+        .nickname(Some(String::from(" Ali ")))
+        .build();
+    document.normalize();
+    assert_eq!(document.email, "alice@example.com");
+    assert_eq!(document.nickname.as_deref(), Some("Ali"));
+
+    let mut document = Collection29 {
+        nickname: None,
+        ..document
+    };
+    document.normalize();
+    assert_eq!(document.nickname, None);
+
+    // serialized copies are normalized the same way, `None` is stored as `null`
+    let mut serialized = mongodb::bson::doc! { "email": " Bob@Example.COM", "nickname": null };
+    Collection29::normalize_document(&mut serialized);
+    assert_eq!(
+        serialized,
+        mongodb::bson::doc! { "email": "bob@example.com", "nickname": null }
+    );
+
+    // collections without normalization attributes are left unchanged
+    let mut document = Collection3::builder().something(Some(true)).build();
+    document.normalize();
+    assert_eq!(document.something, Some(true));
+
+    let db_handler = tokio_test::block_on(mongo::Database::new("mongodb://example.com")).unwrap();
+    drop(
+        db_handler.collection29().insert(
+            Collection29::builder()
+                .email(String::from("Bob"))
+                .nickname(None)
+                .build(),
+        ),
+    );
+}
+
+/// Handler implemented by hand, only the items without default implementation are given.
+pub struct HandWritten {
+    client: mongodb::Client,
+    database: mongodb::Database,
+}
+
+#[mongodb_ext::async_trait::async_trait]
+impl MongoClient for HandWritten {
+    const NAME: &'static str = "handWritten";
+    type Handle<T> = mongodb::Collection<T>;
+
+    async fn new(connection_str: &str) -> mongodb::error::Result<Self> {
+        Self::new_with_client(mongodb::Client::with_uri_str(connection_str).await?)
+    }
+
+    fn new_with_client(client: mongodb::Client) -> mongodb::error::Result<Self> {
+        let database = client.database(Self::NAME);
+        Ok(Self { client, database })
+    }
+
+    fn database(&self) -> &mongodb::Database {
+        &self.database
+    }
+
+    fn client(&self) -> &mongodb::Client {
+        &self.client
+    }
+}
+
+#[test]
+pub fn check_hand_written_client() {
+    assert!(HandWritten::COLLECTION_NAMES.is_empty());
+    assert!(HandWritten::index_models().is_empty());
+    assert!(HandWritten::view_options().is_empty());
+
+    let mut db_handler = tokio_test::block_on(HandWritten::new("mongodb://example.com")).unwrap();
+    assert!(tokio_test::block_on(db_handler.reconnect("invalid connection string")).is_err());
+    tokio_test::block_on(db_handler.reconnect("mongodb://example.org")).unwrap();
+    assert_eq!(db_handler.database().name(), "handWritten");
+}
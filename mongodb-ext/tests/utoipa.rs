@@ -0,0 +1,44 @@
+#![cfg(feature = "utoipa")]
+
+use mongodb_ext::mongo_db;
+use utoipa::{
+    openapi::{schema::Schema, RefOr},
+    PartialSchema, ToSchema,
+};
+
+mongo_db! {
+    Database {
+        Collection1<to_schema, soft_delete> {
+            first_name: String,
+            age: Option<u8>,
+            #[schema(value_type = String)]
+            created_at: mongodb::bson::DateTime,
+        };
+        Collection2<_id: String, to_schema> {
+            tags: Vec<String>,
+        };
+        // collections without `to_schema` may keep types without an OpenAPI schema
+        Collection3 {
+            created_at: mongodb::bson::DateTime,
+        }
+    }
+}
+
+fn property_names<T: PartialSchema>() -> Vec<String> {
+    match T::schema() {
+        RefOr::T(Schema::Object(object)) => object.properties.keys().cloned().collect(),
+        _ => panic!("Expected an object schema"),
+    }
+}
+
+#[test]
+pub fn check_schema_uses_stored_keys() {
+    use mongo::schema::{Collection1, Collection2};
+
+    assert_eq!(Collection1::name(), "Collection1");
+    assert_eq!(
+        property_names::<Collection1>(),
+        ["_id", "age", "createdAt", "deletedAt", "firstName"]
+    );
+    assert_eq!(property_names::<Collection2>(), ["_id", "tags"]);
+}